@@ -0,0 +1,110 @@
+// attachments.rs
+// Files pinned to a conversation with /attach. They are read again for every request, so edits
+// made between questions reach the model, and go into that request's system prompt only: the
+// history and saved sessions don't keep copies. Modification times show which files changed since
+// the last request; those are pointed out to the user and the model.
+use crate::ingest;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+struct Attachment {
+    name: String, // as typed, for listings and the model
+    path: PathBuf,
+    sent: Option<SystemTime>, // modification time when last sent, None before the first request
+}
+
+#[derive(Default)]
+pub struct Attachments {
+    files: Vec<Attachment>,
+}
+
+impl Attachments {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn attach(&mut self, name: &str) -> Result<(), String> {
+        let path = PathBuf::from(name);
+        if path.is_dir() {
+            return Err(format!("'{}' is a directory; attach its files one at a time", name));
+        }
+        if !path.exists() {
+            return Err(format!("File '{}' does not exist", name));
+        }
+        ingest::check_file(&path)?;
+        let path = path.canonicalize().unwrap_or(path);
+        if self.files.iter().any(|file| file.path == path) {
+            return Err(format!("'{}' is already attached", name));
+        }
+        self.files.push(Attachment { name: name.to_string(), path, sent: None });
+        Ok(())
+    }
+
+    // By name or by its number in /attachments; every file when `which` is empty. Returns the
+    // names of the detached files
+    pub fn detach(&mut self, which: &str) -> Vec<String> {
+        let index = which.parse::<usize>().ok().filter(|&n| n >= 1 && n <= self.files.len()).map(|n| n - 1);
+        let mut detached = Vec::new();
+        let mut position = 0;
+        self.files.retain(|file| {
+            let matches = which.is_empty() || Some(position) == index || file.name == which;
+            position += 1;
+            if matches {
+                detached.push(file.name.clone());
+            }
+            !matches
+        });
+        detached
+    }
+
+    // Each file as it is now
+    pub fn read(&self) -> Vec<FileRead> {
+        self.files
+            .iter()
+            .map(|file| {
+                let modified = std::fs::metadata(&file.path).and_then(|m| m.modified()).ok();
+                FileRead {
+                    name: file.name.clone(),
+                    text: std::fs::read_to_string(&file.path).map_err(|e| e.to_string()),
+                    changed: file.sent.is_some() && modified != file.sent,
+                    modified,
+                }
+            })
+            .collect()
+    }
+
+    // Remember what went out, so the next request can tell which files changed since
+    pub fn mark_sent(&mut self, files: &[FileRead]) {
+        for read in files {
+            if let Some(file) = self.files.iter_mut().find(|file| file.name == read.name) {
+                file.sent = read.modified;
+            }
+        }
+    }
+}
+
+/// One attached file as read for a request.
+pub struct FileRead {
+    pub name: String,
+    pub text: Result<String, String>, // or why it could not be read
+    pub changed: bool,                // modified since it was last sent
+    modified: Option<SystemTime>,
+}
+
+// The files as read, for the system prompt; changed ones say so, so the model drops what it
+// remembers of the earlier version
+pub fn block(files: &[FileRead]) -> String {
+    let mut block = String::from(
+        "The user attached these files to the conversation. This is their current content; it replaces any earlier version.\n\n",
+    );
+    for file in files {
+        if file.changed {
+            block.push_str(&format!("Note: {} changed since the previous question.\n", file.name));
+        }
+        match &file.text {
+            Ok(text) => block.push_str(&ingest::fenced(&file.name, text)),
+            Err(e) => block.push_str(&format!("## {}\n(could not be read: {})\n\n", file.name, e)),
+        }
+    }
+    block
+}
@@ -0,0 +1,333 @@
+// bedrock.rs
+// AWS Bedrock through the Converse API, for models named `bedrock/<model-id>`
+use crate::endpoints;
+use chrono::Utc;
+use genai::adapter::AdapterKind;
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, ChatResponse, ChatRole, MessageContent, Usage};
+use genai::ModelIden;
+use ring::{digest, hmac};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub const MODEL_PREFIX: &str = "bedrock/";
+
+const SERVICE: &str = "bedrock";
+
+struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+// Minimal INI reader for ~/.aws/credentials and ~/.aws/config
+fn read_ini_section(path: &PathBuf, section: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let Ok(data) = std::fs::read_to_string(path) else {
+        return values;
+    };
+    let mut in_section = false;
+    for line in data.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name.trim() == section;
+        } else if in_section {
+            if let Some((key, value)) = line.split_once('=') {
+                values.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+    values
+}
+
+fn aws_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(".aws")
+}
+
+fn profile_name() -> String {
+    std::env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string())
+}
+
+// Environment first, then the shared credentials file for AWS_PROFILE
+fn load_credentials() -> Result<Credentials, Box<dyn std::error::Error>> {
+    if let (Ok(access_key_id), Ok(secret_access_key)) =
+        (std::env::var("AWS_ACCESS_KEY_ID"), std::env::var("AWS_SECRET_ACCESS_KEY"))
+    {
+        return Ok(Credentials {
+            access_key_id,
+            secret_access_key,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        });
+    }
+    let profile = profile_name();
+    let section = read_ini_section(&aws_dir().join("credentials"), &profile);
+    match (section.get("aws_access_key_id"), section.get("aws_secret_access_key")) {
+        (Some(id), Some(secret)) => Ok(Credentials {
+            access_key_id: id.clone(),
+            secret_access_key: secret.clone(),
+            session_token: section.get("aws_session_token").cloned(),
+        }),
+        _ => Err(format!(
+            "No AWS credentials: set AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY or add profile '{}' to ~/.aws/credentials",
+            profile
+        )
+        .into()),
+    }
+}
+
+fn load_region() -> String {
+    if let Ok(region) = std::env::var("AWS_REGION").or_else(|_| std::env::var("AWS_DEFAULT_REGION")) {
+        return region;
+    }
+    let profile = profile_name();
+    let section = if profile == "default" {
+        "default".to_string()
+    } else {
+        format!("profile {}", profile)
+    };
+    read_ini_section(&aws_dir().join("config"), &section)
+        .remove("region")
+        .unwrap_or_else(|| "us-east-1".to_string())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(digest::digest(&digest::SHA256, data))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data.as_bytes())
+        .as_ref()
+        .to_vec()
+}
+
+// RFC 3986 encoding as SigV4 expects (unreserved characters kept)
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+// Non-S3 services encode each segment of the (already encoded) request path a second time
+fn canonical_uri(path: &str) -> String {
+    path.split('/').map(uri_encode).collect::<Vec<_>>().join("/")
+}
+
+// SigV4 Authorization header for a POST without query string.
+// `headers` must be lowercase names and include host and x-amz-date.
+fn sigv4_authorization(
+    creds: &Credentials,
+    region: &str,
+    service: &str,
+    amz_date: &str,
+    path: &str,
+    headers: &[(String, String)],
+    payload: &[u8],
+) -> String {
+    let mut headers = headers.to_vec();
+    headers.sort();
+    let canonical_headers: String = headers.iter().map(|(k, v)| format!("{}:{}\n", k, v.trim())).collect();
+    let signed_headers = headers.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "POST\n{}\n\n{}\n{}\n{}",
+        canonical_uri(path),
+        canonical_headers,
+        signed_headers,
+        sha256_hex(payload)
+    );
+
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes())
+    );
+    let k_date = hmac_sha256(format!("AWS4{}", creds.secret_access_key).as_bytes(), date);
+    let k_region = hmac_sha256(&k_date, region);
+    let k_service = hmac_sha256(&k_region, service);
+    let k_signing = hmac_sha256(&k_service, "aws4_request");
+    let signature = hex::encode(hmac_sha256(&k_signing, &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        creds.access_key_id, scope, signed_headers, signature
+    )
+}
+
+fn message_text(message: &ChatMessage) -> String {
+    match &message.content {
+        MessageContent::Text(text) => text.clone(),
+        _ => String::new(),
+    }
+}
+
+// Converse request body: system prompts go to `system`, the rest alternate user/assistant
+fn converse_body(chat_req: &ChatRequest, options: Option<&ChatOptions>) -> Value {
+    let mut system: Vec<Value> = chat_req.system.iter().map(|s| json!({ "text": s })).collect();
+    let mut messages = Vec::new();
+    for message in &chat_req.messages {
+        let role = match message.role {
+            ChatRole::System => {
+                system.push(json!({ "text": message_text(message) }));
+                continue;
+            }
+            ChatRole::Assistant => "assistant",
+            _ => "user",
+        };
+        messages.push(json!({ "role": role, "content": [{ "text": message_text(message) }] }));
+    }
+
+    let mut body = json!({ "messages": messages });
+    if !system.is_empty() {
+        body["system"] = Value::Array(system);
+    }
+    if let Some(options) = options {
+        let mut inference = serde_json::Map::new();
+        if let Some(max_tokens) = options.max_tokens {
+            inference.insert("maxTokens".into(), json!(max_tokens));
+        }
+        if let Some(temperature) = options.temperature {
+            inference.insert("temperature".into(), json!(temperature));
+        }
+        if let Some(top_p) = options.top_p {
+            inference.insert("topP".into(), json!(top_p));
+        }
+        if !options.stop_sequences.is_empty() {
+            inference.insert("stopSequences".into(), json!(options.stop_sequences));
+        }
+        if !inference.is_empty() {
+            body["inferenceConfig"] = Value::Object(inference);
+        }
+    }
+    body
+}
+
+// Signed with SigV4, or a Bedrock API key from AWS_BEARER_TOKEN_BEDROCK when set
+pub async fn exec_chat(
+    model: &str,
+    chat_req: ChatRequest,
+    options: Option<&ChatOptions>,
+) -> Result<ChatResponse, Box<dyn std::error::Error>> {
+    let model_id = model.strip_prefix(MODEL_PREFIX).unwrap_or(model);
+    let region = load_region();
+    let host = format!("bedrock-runtime.{}.amazonaws.com", region);
+    let path = format!("/model/{}/converse", uri_encode(model_id));
+    let payload = serde_json::to_vec(&converse_body(&chat_req, options))?;
+
+    let mut request = endpoints::http_client()
+        .post(format!("https://{}{}", host, path))
+        .header("content-type", "application/json");
+    if let Ok(token) = std::env::var("AWS_BEARER_TOKEN_BEDROCK") {
+        request = request.bearer_auth(token);
+    } else {
+        let creds = load_credentials()?;
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let mut headers = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("host".to_string(), host.clone()),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ];
+        if let Some(token) = &creds.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+            request = request.header("x-amz-security-token", token);
+        }
+        let authorization = sigv4_authorization(&creds, &region, SERVICE, &amz_date, &path, &headers, &payload);
+        request = request.header("x-amz-date", amz_date).header("authorization", authorization);
+    }
+
+    let res = request.body(payload).send().await?;
+    let status = res.status();
+    let body: Value = res.json().await?;
+    if !status.is_success() {
+        let message = body.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+        return Err(format!("Bedrock request failed ({}): {}", status, message).into());
+    }
+
+    let content = body
+        .pointer("/output/message/content")
+        .and_then(|c| c.as_array())
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("")
+        });
+    let usage_field = |name: &str| {
+        body.pointer(&format!("/usage/{}", name))
+            .and_then(|v| v.as_i64())
+            .map(|v| v as i32)
+    };
+    let usage = Usage {
+        prompt_tokens: usage_field("inputTokens"),
+        completion_tokens: usage_field("outputTokens"),
+        total_tokens: usage_field("totalTokens"),
+        ..Default::default()
+    };
+    // genai has no Bedrock adapter kind; the model name carries the identity
+    let model_iden = ModelIden::new(AdapterKind::OpenAI, model);
+    Ok(ChatResponse {
+        content: content.map(MessageContent::from),
+        reasoning_content: None,
+        model_iden: model_iden.clone(),
+        provider_model_iden: model_iden,
+        usage,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The example credentials of the AWS SigV4 test suite
+    fn example_credentials() -> Credentials {
+        Credentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        }
+    }
+
+    fn header(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn signatures_match_the_aws_sigv4_test_suite() {
+        let creds = example_credentials();
+        let date = "20150830T123600Z";
+        let headers = [header("host", "example.amazonaws.com"), header("x-amz-date", date)];
+        // post-vanilla
+        assert_eq!(
+            sigv4_authorization(&creds, "us-east-1", "service", date, "/", &headers, b""),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, Signature=5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"
+        );
+        // post-x-www-form-urlencoded, with the headers given out of order
+        let headers = [
+            header("x-amz-date", date),
+            header("host", "example.amazonaws.com"),
+            header("content-type", "application/x-www-form-urlencoded"),
+        ];
+        assert_eq!(
+            sigv4_authorization(&creds, "us-east-1", "service", date, "/", &headers, b"Param1=value1"),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date, Signature=ff11897932ad3f4e8b18135d722051e5ac45fc38421b1da7b9d196a0fe09473a"
+        );
+    }
+
+    #[test]
+    fn model_ids_are_encoded_twice_in_the_canonical_uri() {
+        let path = format!("/model/{}/converse", uri_encode("anthropic.claude-3-haiku-20240307-v1:0"));
+        assert_eq!(path, "/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse");
+        assert_eq!(canonical_uri(&path), "/model/anthropic.claude-3-haiku-20240307-v1%253A0/converse");
+        assert_eq!(canonical_uri("/"), "/");
+    }
+}
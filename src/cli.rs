@@ -1,10 +1,28 @@
+use crate::chat_session::ChatSession;
+use crate::config::load_config;
+use crate::dry_run;
+use crate::endpoints;
+use crate::output;
+use crate::rate_limit;
+use crate::redact;
+use crate::routing;
+use crate::sse_event::{StreamFlush, StreamSpeed};
+use crate::ui::spinner::Spinner;
+use crate::ui::theme;
+use crate::usage::{self, ExchangeStats};
 use clap::{Parser, Subcommand};
+use futures::StreamExt;
 use genai::adapter::AdapterKind;
 use genai::Client;
 use std::fs::File;
+use std::future::Future;
 use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::time::Instant;
 
 pub const DEFAULT_MODEL: &str = "gemini-2.0-flash";
+const DEFAULT_QUERY_SYSTEM_PROMPT: &str = "Answer concisely and clearly";
 
 #[derive(Parser)]
 #[command(
@@ -22,6 +40,48 @@ pub struct Cli {
     pub model: Option<String>,
     #[arg(short, long)]
     pub stream: Option<bool>,
+    /// Suppress the "Using model / Stream" lines and other status output
+    #[arg(long, short = 'q', default_value_t = false)]
+    pub quiet: bool,
+    /// Plain output: no ANSI styling or markdown rendering (for piping to files)
+    #[arg(long, default_value_t = false)]
+    pub raw: bool,
+    /// Screen-reader friendly: plain text, no spinners or meters, responses announced
+    #[arg(long, default_value_t = false)]
+    pub accessible: bool,
+    /// Give up on a request after this many seconds
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+    /// System prompt for a direct query
+    #[arg(long, value_name = "PROMPT", conflicts_with = "role")]
+    pub system: Option<String>,
+    /// Persona for a direct query: a predefined role or one from [roles] in config
+    #[arg(long, value_name = "NAME")]
+    pub role: Option<String>,
+    /// Keep dot files and directories when reading a directory (-f, .file, ctx)
+    #[arg(long, default_value_t = false)]
+    pub include_hidden: bool,
+    /// Attach the project context saved by `llm ctx` to a direct query
+    #[arg(long, default_value_t = false)]
+    pub ctx: bool,
+    /// Read the text in this image (OCR) and put it in front of the query
+    #[arg(long, value_name = "IMAGE")]
+    pub ocr: Option<String>,
+    /// Named configuration: [profiles.<name>] in config.toml or profiles/<name>.toml
+    #[arg(long, value_name = "NAME", env = "LLM_PROFILE")]
+    pub profile: Option<String>,
+    /// Trace routing, endpoint resolution and requests on stderr (-vv: also stream events and HTTP)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+    /// Print the messages that would be sent, with a token estimate, instead of calling the model
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+    /// Refuse network calls except to this machine and the local network (Ollama, LM Studio)
+    #[arg(long, default_value_t = false)]
+    pub offline: bool,
+    /// Ignore proxy_url and HTTP(S)_PROXY (e.g. for a local Ollama)
+    #[arg(long, default_value_t = false)]
+    pub no_proxy: bool,
     #[command(subcommand)]
     pub command: Option<Commands>,
     /// Positional argument for direct query
@@ -36,17 +96,29 @@ pub enum Commands {
     ListModels,
     /// Run a single query and exit (more inside -f -q -m -s)
     Query {
-        /// The question to ask
-        #[arg(short = 'q', long = "question", group = "input")]
+        /// The question to ask (about the file when -f is given too)
+        #[arg(short = 'q', long = "question")]
         question: Option<String>,
         /// File as input
-        #[arg(short = 'f', long = "file", group = "input")]
+        #[arg(short = 'f', long = "file")]
         file: Option<String>,
         /// Stream responses
         #[arg(short, long)]
         stream: Option<bool>,
         #[arg(short = 'm', long = "model")]
         model: Option<String>,
+        /// System prompt instead of the default "Answer concisely and clearly"
+        #[arg(long, value_name = "PROMPT", conflicts_with = "role")]
+        system: Option<String>,
+        /// Persona: a predefined role or one from [roles] in config
+        #[arg(long, value_name = "NAME")]
+        role: Option<String>,
+        /// Attach the project context saved by `llm ctx`
+        #[arg(long, default_value_t = false)]
+        ctx: bool,
+        /// Read the text in this image (OCR) and put it in front of the question
+        #[arg(long, value_name = "IMAGE")]
+        ocr: Option<String>,
     },
     /// alias for -m grok-2
     #[clap(alias = "0")]
@@ -95,6 +167,205 @@ pub enum Commands {
         /// The question to ask after build (optional)
         #[arg(short, long)]
         question: Option<String>,
+        /// Apply the model's patch (after confirmation) and rebuild, up to N rounds (default 3)
+        #[arg(long = "loop", value_name = "N", num_args = 0..=1, default_missing_value = "3")]
+        fix_loop: Option<usize>,
+    },
+
+    /// Transcribe an audio file (wav/mp3) and print it, or ask about it with --ask
+    Transcribe {
+        /// Audio file to transcribe
+        file: String,
+        /// Transcription model (whisper model path/name, see transcribe_* config)
+        #[arg(short = 'm', long = "model")]
+        model: Option<String>,
+        /// Send the transcript to the chat model with this instruction
+        #[arg(short = 'a', long = "ask")]
+        ask: Option<String>,
+        /// Stream responses for --ask
+        #[arg(short, long)]
+        stream: Option<bool>,
+    },
+    /// Translate a file, text or stdin (e.g. translate --to ja -f notes.md)
+    Translate {
+        /// Target language: a code such as ja, zh-TW, fr, or a language name
+        #[arg(short = 't', long = "to")]
+        to: String,
+        /// File to translate
+        #[arg(short = 'f', long = "file", conflicts_with = "text")]
+        file: Option<String>,
+        /// Text to translate (default: stdin)
+        text: Vec<String>,
+        #[arg(short = 'm', long = "model")]
+        model: Option<String>,
+        /// Stream responses
+        #[arg(short, long)]
+        stream: Option<bool>,
+    },
+    /// Fix and restyle text from a file, the command line or stdin, printing only the result
+    Rewrite {
+        /// correct (grammar and spelling only), formal, concise or friendly
+        #[arg(long, default_value = "correct", value_parser = ["correct", "formal", "concise", "friendly"])]
+        style: String,
+        /// File to rewrite
+        #[arg(short = 'f', long = "file", conflicts_with = "text")]
+        file: Option<String>,
+        /// Text to rewrite (default: stdin)
+        text: Vec<String>,
+        /// Show a word diff against the original instead of the rewritten text
+        #[arg(long, default_value_t = false)]
+        diff: bool,
+        #[arg(short = 'm', long = "model")]
+        model: Option<String>,
+    },
+    /// Summarize a file, URL or stdin (--length short|bullet|detailed)
+    Summarize {
+        /// File or http(s) URL to summarize (default: stdin)
+        #[arg(short = 'f', long = "file")]
+        file: Option<String>,
+        /// Preset: short, bullet, detailed, or a name from [summary_templates]
+        #[arg(short = 'l', long = "length", default_value = "short")]
+        length: String,
+        /// Also write the summary to this file
+        #[arg(short = 'o', long = "output")]
+        output: Option<String>,
+        #[arg(short = 'm', long = "model")]
+        model: Option<String>,
+        /// Stream responses
+        #[arg(short, long)]
+        stream: Option<bool>,
+    },
+    /// Run a command and have the model explain its errors or output (explain -- cargo test)
+    Explain {
+        /// Ask this instead of the default "what went wrong" / "explain this output"
+        #[arg(short, long)]
+        question: Option<String>,
+        #[arg(short = 'm', long = "model")]
+        model: Option<String>,
+        /// Stream responses
+        #[arg(short, long)]
+        stream: Option<bool>,
+        /// The command and its arguments
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Bundle the repo (file tree, Cargo.toml, files matching globs) as context for --ctx and /ctx load
+    Ctx {
+        /// Files to include, e.g. 'src/**/*.rs' README.md (.gitignore applies)
+        globs: Vec<String>,
+        /// Size budget for file contents, in bytes
+        #[arg(short, long, default_value_t = crate::tools::ctx::DEFAULT_BUDGET)]
+        budget: usize,
+        /// Print the bundle instead of a summary
+        #[arg(short, long, default_value_t = false)]
+        print: bool,
+    },
+    /// Ask again with the file's content every time it is saved (watch -f prompt.md)
+    Watch {
+        /// Prompt file to watch
+        #[arg(short = 'f', long = "file")]
+        file: String,
+        #[arg(short = 'm', long = "model")]
+        model: Option<String>,
+        /// Stream responses
+        #[arg(short, long)]
+        stream: Option<bool>,
+    },
+    /// Chat over two named pipes: prompts from --in, answers to --out, one persistent session
+    Pipe {
+        /// FIFO to read prompts from (created if missing)
+        #[arg(long = "in", value_name = "FIFO")]
+        input: String,
+        /// FIFO to write answers to (created if missing)
+        #[arg(long = "out", value_name = "FIFO")]
+        output: String,
+    },
+    /// Pull fields out of a document as CSV or JSON rows (extract --fields name,email -f invoice.txt)
+    Extract {
+        /// Names of the fields, comma-separated
+        #[arg(long, value_delimiter = ',', required = true)]
+        fields: Vec<String>,
+        /// Document to read (a directory is walked); stdin when missing
+        #[arg(short = 'f', long = "file")]
+        file: Option<String>,
+        #[arg(long, default_value = "json", value_parser = ["json", "csv"])]
+        format: String,
+        #[arg(short = 'm', long = "model")]
+        model: Option<String>,
+    },
+    /// Apply a prompt to every line of stdin, one answer per line (filter -p "translate to English: {}")
+    Filter {
+        /// Prompt with {} for the line (appended when missing); with --jsonl also {field}
+        #[arg(short, long)]
+        prompt: String,
+        /// Lines are JSON objects; each is printed back with an "output" field
+        #[arg(long, default_value_t = false)]
+        jsonl: bool,
+        /// Requests in flight at once; output keeps the input order
+        #[arg(short = 'j', long, default_value_t = 4)]
+        concurrency: usize,
+        #[arg(short = 'm', long = "model")]
+        model: Option<String>,
+    },
+    /// Check that the default, alias and -m models and the [[endpoints]] answer and accept their keys
+    Ping {
+        /// Model to check instead; repeat for more
+        #[arg(short = 'm', long = "model")]
+        models: Vec<String>,
+    },
+    /// Compare latency of models on one prompt (bench -m llama3.2 -m gpt-4o-mini --prompt-file p.txt)
+    Bench {
+        /// Model to measure; repeat for each one
+        #[arg(short = 'm', long = "model", required = true)]
+        models: Vec<String>,
+        /// The prompt to send
+        #[arg(short, long, conflicts_with = "prompt_file")]
+        prompt: Option<String>,
+        /// Read the prompt from this file
+        #[arg(long, value_name = "FILE")]
+        prompt_file: Option<String>,
+        /// Requests per model
+        #[arg(long, default_value_t = 5)]
+        runs: usize,
+        /// Print every run as JSON instead of the table
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Run a Rhai automation script (ask, session, save, read_file)
+    Script {
+        /// Path to the .rhai script
+        file: String,
+        /// Extra arguments, available to the script as ARGS
+        #[arg(trailing_var_arg = true)]
+        args: Vec<String>,
+    },
+    /// Manage saved interactive sessions
+    Sessions {
+        #[command(subcommand)]
+        action: SessionsCommand,
+    },
+    /// Play back a saved session one message at a time, without loading it
+    Replay {
+        /// Session file name (as in /load) or path
+        session: String,
+        /// Advance on its own after this many seconds instead of on a key
+        #[arg(long, value_name = "SECS")]
+        speed: Option<String>,
+    },
+    /// Encrypted sync of saved sessions with sync_remote
+    Sync {
+        #[command(subcommand)]
+        action: SyncCommand,
+    },
+    /// Summaries of the usage ledger
+    Usage {
+        #[command(subcommand)]
+        action: UsageCommand,
+    },
+    /// Check config.toml
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
     },
 
     #[clap(alias = "set")]
@@ -108,6 +379,42 @@ pub enum Commands {
     Quit,
 }
 
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum SessionsCommand {
+    /// Delete (and optionally archive) sessions not modified for a while
+    Prune {
+        /// Age such as 90d, 12w or 36h
+        #[arg(long, value_name = "AGE")]
+        older_than: String,
+        /// Write the pruned sessions to this .tar.gz first
+        #[arg(long, value_name = "FILE")]
+        archive: Option<String>,
+        /// Only list what would be pruned
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum SyncCommand {
+    /// Merge in the remote sessions, then upload all local ones
+    Push,
+    /// Take the remote sessions that are missing or older here
+    Pull,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum UsageCommand {
+    /// Average /rate score per model
+    Ratings,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigCommand {
+    /// Report unknown keys (with suggestions) and values of the wrong type
+    Validate,
+}
+
 pub async fn list_models(client: &Client) -> Result<(), Box<dyn std::error::Error>> {
     let kinds = &[
         AdapterKind::OpenAI,
@@ -127,40 +434,273 @@ pub async fn list_models(client: &Client) -> Result<(), Box<dyn std::error::Erro
     Ok(())
 }
 
+/// Failures of one-shot commands that map to their own exit codes (see `exit_code` in main.rs).
+#[derive(Debug)]
+pub enum QueryError {
+    NoAnswer,      // the provider replied without any text
+    Input(String), // missing or unreadable question/file
+    Timeout(u64),  // no complete answer within --timeout seconds
+    DryRun,        // --dry-run: the request was printed, not sent
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::NoAnswer => write!(f, "The model returned no answer"),
+            QueryError::Input(msg) => write!(f, "{}", msg),
+            QueryError::Timeout(secs) => write!(f, "Request timed out after {}s", secs),
+            QueryError::DryRun => write!(f, "Dry run: request not sent"),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+// What went wrong at the provider, readable: the HTTP status and the provider's own message
+// instead of genai's debug output
+pub fn error_message(err: &(dyn std::error::Error + 'static)) -> String {
+    use genai::webc;
+    let Some(genai_err) = err.downcast_ref::<genai::Error>() else {
+        return err.to_string();
+    };
+    match genai_err {
+        genai::Error::WebModelCall { webc_error, .. } | genai::Error::WebAdapterCall { webc_error, .. } => {
+            match webc_error {
+                webc::Error::ResponseFailedStatus { status, body } => {
+                    format!("HTTP {}: {}", status, provider_detail(body))
+                }
+                webc::Error::ResponseFailedNotJson { content_type } => {
+                    format!("unexpected response type '{}'", content_type)
+                }
+                webc::Error::Reqwest(e) => e.to_string(),
+                other => other.to_string(),
+            }
+        }
+        genai::Error::StreamEventError { body, .. } => provider_detail(&body.to_string()),
+        genai::Error::WebStream { cause, .. } => format!("stream interrupted: {}", cause),
+        genai::Error::ReqwestEventSource(e) => format!("stream interrupted: {}", e),
+        genai::Error::RequiresApiKey { model_iden } => format!("{} needs an API key", model_iden.model_name),
+        genai::Error::Resolver {
+            model_iden,
+            resolver_error: genai::resolver::Error::ApiKeyEnvNotFound { env_name },
+        } => format!("{} needs an API key: {} is not set", model_iden.model_name, env_name),
+        // Refusals such as offline mode's carry their own message
+        genai::Error::Resolver {
+            resolver_error: genai::resolver::Error::Custom(message),
+            ..
+        } => message.clone(),
+        genai::Error::NoChatResponse { model_iden } => format!("{} sent no response", model_iden.model_name),
+        other => other.to_string(),
+    }
+}
+
+// `error.message` (or `message`) of a JSON error body, else the body itself, shortened
+fn provider_detail(body: &str) -> String {
+    const MAX_CHARS: usize = 300;
+    let parsed: Option<serde_json::Value> = serde_json::from_str(body).ok();
+    let message = parsed.as_ref().and_then(|v| {
+        v.pointer("/error/message")
+            .or_else(|| v.get("message"))
+            .or_else(|| v.get("error").filter(|e| e.is_string()))
+            .and_then(|m| m.as_str())
+            .map(str::to_string)
+    });
+    let text = message.unwrap_or_else(|| body.trim().to_string());
+    if text.chars().count() > MAX_CHARS {
+        format!("{}…", text.chars().take(MAX_CHARS).collect::<String>())
+    } else {
+        text
+    }
+}
+
+// --system as given, or the prompt behind a --role name
+pub fn resolve_system_prompt(system: Option<String>, role: Option<String>) -> Result<Option<String>, QueryError> {
+    match (system, role) {
+        (Some(system), _) => Ok(Some(system)),
+        (None, Some(role)) => ChatSession::role_prompt(&role)
+            .map(Some)
+            .ok_or_else(|| QueryError::Input(format!("Unknown role '{}'", role))),
+        (None, None) => Ok(None),
+    }
+}
+
+// Seconds allowed per request (--timeout / request_timeout), 0 = no limit
+static REQUEST_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(0);
+
+pub fn set_request_timeout(secs: Option<u64>) {
+    REQUEST_TIMEOUT_SECS.store(secs.unwrap_or(0), Ordering::Relaxed);
+}
+
+// Deadline for a request starting now, if a timeout is configured
+pub fn request_deadline() -> Option<Instant> {
+    match REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed) {
+        0 => None,
+        secs => Some(Instant::now() + Duration::from_secs(secs)),
+    }
+}
+
+// Await a provider call, failing with QueryError::Timeout once `deadline` passes
+pub async fn with_deadline<T, E>(
+    deadline: Option<Instant>,
+    fut: impl Future<Output = Result<T, E>>,
+) -> Result<T, Box<dyn std::error::Error>>
+where
+    E: Into<Box<dyn std::error::Error>>,
+{
+    match deadline {
+        Some(deadline) => match tokio::time::timeout_at(deadline, fut).await {
+            Ok(res) => res.map_err(Into::into),
+            Err(_) => Err(QueryError::Timeout(REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed)).into()),
+        },
+        None => fut.await.map_err(Into::into),
+    }
+}
+
+// One question, one printed answer; the answer text is also returned (e.g. for --output)
 pub async fn execute_query(
     client: &Client,
     model: &str,
     question: &str,
     stream: bool,
     save_to_file: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use genai::chat::printer::{print_chat_stream, PrintChatStreamOptions};
-    use genai::chat::{ChatMessage, ChatRequest};
+    system_prompt: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    use genai::chat::{ChatMessage, ChatOptions, ChatRequest, ChatStreamEvent};
+
+    let question = &redact::check_outgoing(question)?;
+    let model = &routing::resolve_model(model, question);
 
     let chat_req = ChatRequest::new(vec![
-        ChatMessage::system("Answer concisely and clearly"),
+        ChatMessage::system(system_prompt.unwrap_or(DEFAULT_QUERY_SYSTEM_PROMPT)),
         ChatMessage::user(question),
     ]);
 
-    if stream {
-        println!("\x1b[92m󰼭 :\x1b[0m");
-        let chat_res = client.exec_chat_stream(model, chat_req, None).await?;
-        print_chat_stream(
-            chat_res,
-            Some(&PrintChatStreamOptions::from_print_events(false)),
-        )
-        .await?;
+    if dry_run::is_enabled() {
+        dry_run::print_request(model, &chat_req, None);
+        return Ok(String::new());
+    }
 
+    let show_header = !output::is_quiet() && !output::is_raw();
+    output::announce(&format!("Waiting for {}.", model));
+    if stream && endpoints::supports_stream(model) {
+        if show_header {
+            println!("{}", theme::PROMPT.paint("󰼭 :"));
+        }
+        let speed = load_config().map(|c| StreamSpeed::from_config(&c)).unwrap_or_default();
+        let deadline = request_deadline();
+        endpoints::prepare(model).await?;
+        let permit = rate_limit::acquire(model, &chat_req).await;
+        // Shown until the first event of the stream arrives
+        let mut spinner = Spinner::start(&format!("Waiting for {}", model));
+        let started = std::time::Instant::now();
+        let options = ChatOptions::default().with_capture_usage(true);
+        tracing::debug!(model, "streaming chat request");
+        let request = client.exec_chat_stream(model, chat_req, Some(&options));
+        let mut chat_res = with_deadline(deadline, request).await?;
+        let mut stdout = std::io::stdout();
+        let mut pending = String::new();
+        let mut answer = String::new();
+        let mut answered = false;
+        let mut first_token = None;
+        let mut captured_usage = None;
+        loop {
+            let next = async { Ok::<_, genai::Error>(chat_res.stream.next().await) };
+            let event = with_deadline(deadline, next).await;
+            spinner.stop();
+            let event: Result<_, Box<dyn std::error::Error>> = match event {
+                Ok(Some(event)) => event.map_err(Into::into),
+                Ok(None) => break,
+                Err(e) => Err(e),
+            };
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    // Keep whatever already arrived before reporting the timeout or provider error
+                    if !pending.is_empty() {
+                        println!("{}", pending);
+                    }
+                    println!();
+                    return Err(e);
+                }
+            };
+            tracing::trace!(?event, "stream event");
+            let chunk = match event {
+                ChatStreamEvent::Chunk(chunk) => chunk,
+                ChatStreamEvent::End(end) => {
+                    captured_usage = end.captured_usage;
+                    continue;
+                }
+                _ => continue,
+            };
+            if first_token.is_none() {
+                output::announce(&format!("Response from {}:", model));
+            }
+            first_token.get_or_insert_with(|| started.elapsed());
+            answered |= !chunk.content.trim().is_empty();
+            answer.push_str(&chunk.content);
+            match speed.flush {
+                // Flush every token as it arrives
+                StreamFlush::Token => {
+                    print!("{}", chunk.content);
+                    stdout.flush()?;
+                }
+                // Hold partial lines back until their newline arrives
+                StreamFlush::Line => {
+                    pending.push_str(&chunk.content);
+                    while let Some(pos) = pending.find('\n') {
+                        println!("{}", &pending[..pos]);
+                        pending.drain(..=pos);
+                    }
+                }
+            }
+        }
+        if !pending.is_empty() {
+            print!("{}", pending);
+        }
+        println!();
+        if !answered {
+            return Err(QueryError::NoAnswer.into());
+        }
+        output::announce("End of response.");
+        let total = started.elapsed();
+        let usage = captured_usage.unwrap_or_default();
+        if let Some(permit) = permit {
+            permit.settle(&usage);
+        }
+        report_stats(&ExchangeStats::new(model, &usage, first_token, total));
+        Ok(answer)
     } else {
-        println!("\x1b[92m󱚠 :\x1b[0m");
-        let chat_res = client.exec_chat(model, chat_req, None).await?;
-        let content = chat_res.content_text_as_str().unwrap_or("NO ANSWER");
+        if show_header {
+            println!("{}", theme::PROMPT.paint("󱚠 :"));
+        }
+        let started = std::time::Instant::now();
+        let request = endpoints::exec_chat(client, model, chat_req, None);
+        let mut spinner = Spinner::start(&format!("Waiting for {}", model));
+        let chat_res = with_deadline(request_deadline(), request).await;
+        spinner.stop();
+        let chat_res = chat_res?;
+        let content = chat_res
+            .content_text_as_str()
+            .filter(|c| !c.trim().is_empty())
+            .ok_or(QueryError::NoAnswer)?;
+        output::announce(&format!("Response from {}:", model));
         println!("{}", content);
+        output::announce("End of response.");
+        let total = started.elapsed();
+        report_stats(&ExchangeStats::new(model, &chat_res.usage, None, total));
 
         if save_to_file {
             let mut file = File::create("/tmp/ans.md")?;
             file.write_all(content.as_bytes())?;
         }
+        Ok(content.to_string())
+    }
+}
+
+// Ledger entry for a one-shot answer, plus the footer on stderr so piped output stays clean
+fn report_stats(stats: &ExchangeStats) {
+    usage::record(stats);
+    if usage::show_stats() && !output::is_quiet() {
+        eprintln!("{}", theme::DIM.paint_err(stats.footer()));
     }
-    Ok(())
 }
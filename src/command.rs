@@ -0,0 +1,485 @@
+// command.rs
+use crate::config::AVAILABLE_MODELS;
+use crate::tools::translate::LANGUAGES;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    Subcommand, // a keyword the command knows (e.g. `rm` in `/word rm`)
+    Flag,       // --something
+    Number,
+    Path, // looks like a file path
+    Text,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandArg<'a> {
+    pub text: &'a str,
+    pub start: usize, // byte offset in the original line
+    pub kind: ArgKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedCommand<'a> {
+    pub name: &'a str, // without the leading '/'
+    pub args: Vec<CommandArg<'a>>,
+}
+
+// Keywords accepted as the first argument of each slash command
+pub fn command_keywords(name: &str) -> Vec<&'static str> {
+    match name {
+        "word" => vec!["rm", "list", "import", "seed"],
+        "system" => vec![
+            "reset",
+            "coding_assistant",
+            "creative_writer",
+            "technical_support",
+            "language_tutor",
+            "general_knowledge",
+        ],
+        "model" => AVAILABLE_MODELS.to_vec(),
+        "think" => vec!["show", "hide"],
+        "log" => vec!["on", "off"],
+        "stopseq" => vec!["add", "rm", "clear"],
+        "maxtokens" => vec!["off"],
+        "speed" => vec!["raw", "line", "token", "default"],
+        "preview" => vec!["on", "off"],
+        "compare" => vec!["stats"],
+        "mirror" => vec!["--append", "off"],
+        "ctx" => vec!["load"],
+        "memory" => vec!["list", "add", "rm"],
+        "snippet" => vec!["save", "rm"],
+        "var" => vec!["set", "rm", "list"],
+        "plugins" => vec!["reload"],
+        "translate" => LANGUAGES.iter().map(|(code, _)| *code).collect(),
+        "help" => COMMAND_HELP.iter().map(|help| help.command.name()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn classify(text: &str, position: usize, keywords: &[&str]) -> ArgKind {
+    if position == 0 && keywords.contains(&text) {
+        ArgKind::Subcommand
+    } else if text.starts_with("--") {
+        ArgKind::Flag
+    } else if text.parse::<f64>().is_ok() {
+        ArgKind::Number
+    } else if text.contains('/') || text.starts_with('~') || text.rsplit_once('.').is_some_and(|(base, ext)| {
+        !base.is_empty() && !ext.is_empty() && ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric())
+    }) {
+        ArgKind::Path
+    } else {
+        ArgKind::Text
+    }
+}
+
+// Split a `/command arg1 arg2` line into the command name and classified arguments
+pub fn parse_command(line: &str) -> Option<ParsedCommand<'_>> {
+    let rest = line.strip_prefix('/')?;
+    let name_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let name = &rest[..name_end];
+    let keywords = command_keywords(name);
+
+    let mut args = Vec::new();
+    let mut offset = 1 + name_end;
+    for (position, text) in line[offset..].split_whitespace().enumerate() {
+        let start = offset + line[offset..].find(text).unwrap_or(0);
+        args.push(CommandArg {
+            text,
+            start,
+            kind: classify(text, position, &keywords),
+        });
+        offset = start + text.len();
+    }
+    Some(ParsedCommand { name, args })
+}
+
+/// A built-in slash command; `Snippet` and `Dictate` are handled by the REPL itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Quit,
+    System,
+    Status,
+    Model,
+    StreamToggle,
+    Preview,
+    Speed,
+    Think,
+    StopSeq,
+    MaxTokens,
+    Continue,
+    Diff,
+    Rate,
+    Memory,
+    Snippet,
+    Cls,
+    Clear,
+    Compare,
+    Translate,
+    Ctx,
+    Var,
+    Attach,
+    Detach,
+    Attachments,
+    Mic,
+    Dictate,
+    Title,
+    Save,
+    Load,
+    Import,
+    Log,
+    Mirror,
+    Word,
+    Plugins,
+    Help,
+}
+
+// Every built-in command name, aliases included; handle_command dispatches on it and /help <cmd>
+// looks entries up through it
+pub const COMMANDS: &[(&str, Command)] = &[
+    ("quit", Command::Quit),
+    ("bye", Command::Quit),
+    ("q", Command::Quit),
+    ("Q", Command::Quit),
+    ("system", Command::System),
+    ("status", Command::Status),
+    ("model", Command::Model),
+    ("ss", Command::StreamToggle),
+    ("preview", Command::Preview),
+    ("speed", Command::Speed),
+    ("think", Command::Think),
+    ("stopseq", Command::StopSeq),
+    ("maxtokens", Command::MaxTokens),
+    ("continue", Command::Continue),
+    ("diff", Command::Diff),
+    ("rate", Command::Rate),
+    ("memory", Command::Memory),
+    ("snippet", Command::Snippet),
+    ("cls", Command::Cls),
+    ("clear", Command::Clear),
+    ("compare", Command::Compare),
+    ("translate", Command::Translate),
+    ("ctx", Command::Ctx),
+    ("var", Command::Var),
+    ("attach", Command::Attach),
+    ("detach", Command::Detach),
+    ("attachments", Command::Attachments),
+    ("mic", Command::Mic),
+    ("dictate", Command::Dictate),
+    ("title", Command::Title),
+    ("save", Command::Save),
+    ("load", Command::Load),
+    ("import", Command::Import),
+    ("log", Command::Log),
+    ("mirror", Command::Mirror),
+    ("word", Command::Word),
+    ("plugins", Command::Plugins),
+    ("help", Command::Help),
+    ("?", Command::Help),
+];
+
+// The built-in command called `name` (without the '/'); None for plugins and unknown names
+pub fn builtin(name: &str) -> Option<Command> {
+    let name = name.trim_start_matches('/');
+    COMMANDS.iter().find(|(n, _)| *n == name).map(|(_, command)| *command)
+}
+
+impl Command {
+    // The name /help lists it under (the first one in COMMANDS)
+    pub fn name(self) -> &'static str {
+        COMMANDS.iter().find(|(_, command)| *command == self).map_or("", |(name, _)| name)
+    }
+}
+
+/// Usage, options and examples of one slash command, for `/help <command>`.
+pub struct CommandHelp {
+    pub command: Command,
+    pub usage: &'static str,
+    pub about: &'static str,
+    pub options: &'static [(&'static str, &'static str)],
+    pub examples: &'static [&'static str],
+}
+
+pub const COMMAND_HELP: &[CommandHelp] = &[
+    CommandHelp {
+        command: Command::Quit,
+        usage: "/quit | /q | /bye",
+        about: "Exit interactive mode; new answers are saved first unless autosave_sessions = false.",
+        options: &[],
+        examples: &["/q"],
+    },
+    CommandHelp {
+        command: Command::System,
+        usage: "/system [<prompt> | <role> | reset]",
+        about: "Replace the system prompt with text or a predefined role, or restore the default. Without an argument, list the roles.",
+        options: &[("reset", "back to the default system prompt")],
+        examples: &["/system You are a terse reviewer of Rust code", "/system coding_assistant", "/system reset"],
+    },
+    CommandHelp {
+        command: Command::Status,
+        usage: "/status",
+        about: "Show the model, role, system prompt, stream and reasoning settings, title and which models answered.",
+        options: &[],
+        examples: &["/status"],
+    },
+    CommandHelp {
+        command: Command::Model,
+        usage: "/model [<name>]",
+        about: "Switch the model for the next questions; without a name, list the known models. Alt-0..Alt-3 switch to the alias models.",
+        options: &[],
+        examples: &["/model", "/model deepseek-chat", "/model openrouter/anthropic/claude-3.5-sonnet"],
+    },
+    CommandHelp {
+        command: Command::StreamToggle,
+        usage: "/ss",
+        about: "Toggle stream mode.",
+        options: &[],
+        examples: &["/ss"],
+    },
+    CommandHelp {
+        command: Command::Preview,
+        usage: "/preview [on | off]",
+        about: "Show every request before it is sent: all messages after attachments, memories and templates, with token estimates. Enter sends, e edits the message in $EDITOR, c cancels. Alone it toggles; confirm_send = true turns it on at start.",
+        options: &[("on", "preview every request"), ("off", "send right away")],
+        examples: &["/preview", "/preview off"],
+    },
+    CommandHelp {
+        command: Command::Speed,
+        usage: "/speed <ms> | raw | line | token | default",
+        about: "How streamed answers are shown: a delay per character, or flushed per line or token.",
+        options: &[("raw", "no delay"), ("line", "flush per line"), ("token", "flush per token"), ("default", "from config")],
+        examples: &["/speed 5", "/speed token"],
+    },
+    CommandHelp {
+        command: Command::Think,
+        usage: "/think [show | hide]",
+        about: "Show or hide the reasoning of thinking models; /think alone prints the last trace.",
+        options: &[("show", "print reasoning above answers"), ("hide", "keep it out of the way (default)")],
+        examples: &["/think show", "/think"],
+    },
+    CommandHelp {
+        command: Command::StopSeq,
+        usage: "/stopseq [add <s> | rm <s> | clear]",
+        about: "Stop generation when the model writes <s>; \\n stands for a newline. Without arguments, list them.",
+        options: &[("add <s>", "add a stop sequence"), ("rm <s>", "remove one"), ("clear", "remove all")],
+        examples: &["/stopseq add ```", "/stopseq add \\n\\n", "/stopseq clear"],
+    },
+    CommandHelp {
+        command: Command::MaxTokens,
+        usage: "/maxtokens [<n> | off]",
+        about: "Limit the length of answers; off goes back to the model default.",
+        options: &[("off", "no limit")],
+        examples: &["/maxtokens 200", "/maxtokens off"],
+    },
+    CommandHelp {
+        command: Command::Continue,
+        usage: "/continue",
+        about: "Ask for the rest of an answer that stopped at the token limit; it is appended to that answer.",
+        options: &[],
+        examples: &["/maxtokens 100", "/continue"],
+    },
+    CommandHelp {
+        command: Command::Diff,
+        usage: "/diff",
+        about: "Word-level diff of the last two answers, with the models that gave them.",
+        options: &[],
+        examples: &["/model gemini-2.0-flash", "/diff"],
+    },
+    CommandHelp {
+        command: Command::Rate,
+        usage: "/rate <1-5> [comment]",
+        about: "Rate the last answer; ratings are saved with the session and `llm usage ratings` averages them per model.",
+        options: &[],
+        examples: &["/rate 4", "/rate 2 missed the question"],
+    },
+    CommandHelp {
+        command: Command::Memory,
+        usage: "/memory [list | add <fact> | rm <id>]",
+        about: "Long-term memory across sessions (memory = true in config).",
+        options: &[("list", "remembered facts with their ids"), ("add <fact>", "remember a fact"), ("rm <id>", "forget one")],
+        examples: &["/memory add I deploy with Nomad, not Kubernetes", "/memory rm 3"],
+    },
+    CommandHelp {
+        command: Command::Snippet,
+        usage: "/snippet [<name> | save <name> | rm <name>]",
+        about: "Send a saved prompt again. Snippets are kept in snippets.toml in the config dir; without an argument, list them.",
+        options: &[("save <name>", "keep the last prompt under <name>"), ("rm <name>", "delete a snippet")],
+        examples: &["/snippet save review", "/snippet review", "/snippet rm review"],
+    },
+    CommandHelp {
+        command: Command::Cls,
+        usage: "/cls",
+        about: "Clear the screen.",
+        options: &[],
+        examples: &["/cls"],
+    },
+    CommandHelp {
+        command: Command::Clear,
+        usage: "/clear",
+        about: "Start a fresh conversation, keeping the system prompt.",
+        options: &[],
+        examples: &["/clear"],
+    },
+    CommandHelp {
+        command: Command::Compare,
+        usage: "/compare <model_a> <model_b> <prompt> | stats",
+        about: "Ask two models the same question and keep the answer you vote for.",
+        options: &[("stats", "wins per model so far")],
+        examples: &["/compare grok-2 deepseek-chat Explain Rust lifetimes briefly", "/compare stats"],
+    },
+    CommandHelp {
+        command: Command::Translate,
+        usage: "/translate <lang>",
+        about: "Translate the last answer; the translation is shown but not added to the conversation.",
+        options: &[],
+        examples: &["/translate ja", "/translate zh-TW"],
+    },
+    CommandHelp {
+        command: Command::Ctx,
+        usage: "/ctx load [globs]",
+        about: "Attach the project context saved by `llm ctx`, or build it for the globs.",
+        options: &[("load", "add the context to the conversation")],
+        examples: &["/ctx load", "/ctx load src/**/*.rs"],
+    },
+    CommandHelp {
+        command: Command::Var,
+        usage: "/var [set <name> <value> | rm <name> | list]",
+        about: "Variables for this conversation: {{name}} in a message (typed, from a snippet or a mic route template) is replaced by the value before it is sent. They are saved with the session.",
+        options: &[("set <name> <value>", "set or replace a variable"), ("rm <name>", "remove one"), ("list", "show them (also without an argument)")],
+        examples: &["/var set project Falcon", "/var set lang Rust", "How do I add logging to {{project}} in {{lang}}?"],
+    },
+    CommandHelp {
+        command: Command::Attach,
+        usage: "/attach <file>",
+        about: "Pin a file to the conversation. It is read again for every question, so edits reach the model; unlike .file it is not added to the history.",
+        options: &[],
+        examples: &["/attach src/main.rs", "/attach Cargo.toml"],
+    },
+    CommandHelp {
+        command: Command::Detach,
+        usage: "/detach [<file> | <number>]",
+        about: "Unpin a file by name or by its number in /attachments; without an argument, unpin all of them.",
+        options: &[],
+        examples: &["/detach src/main.rs", "/detach 2", "/detach"],
+    },
+    CommandHelp {
+        command: Command::Attachments,
+        usage: "/attachments",
+        about: "List the pinned files with the estimated tokens each adds to every question.",
+        options: &[],
+        examples: &["/attachments"],
+    },
+    CommandHelp {
+        command: Command::Mic,
+        usage: "/mic",
+        about: "Record audio and send the transcription as a question.",
+        options: &[],
+        examples: &["/mic"],
+    },
+    CommandHelp {
+        command: Command::Dictate,
+        usage: "/dictate",
+        about: "Live dictation: Enter sends the transcript, Esc cancels.",
+        options: &[],
+        examples: &["/dictate"],
+    },
+    CommandHelp {
+        command: Command::Title,
+        usage: "/title",
+        about: "Generate a title from the conversation; it names the saved session.",
+        options: &[],
+        examples: &["/title"],
+    },
+    CommandHelp {
+        command: Command::Save,
+        usage: "/save [--force] [filename]",
+        about: "Save the session; without a name under its title. Asks before overwriting another session.",
+        options: &[("--force", "overwrite without asking")],
+        examples: &["/save", "/save rust-lifetimes", "/save --force rust-lifetimes"],
+    },
+    CommandHelp {
+        command: Command::Load,
+        usage: "/load [filename | --list]",
+        about: "Load a saved session; without a name, browse them with search and preview.",
+        options: &[("--list", "plain list of saved sessions")],
+        examples: &["/load", "/load rust-lifetimes", "/load --list"],
+    },
+    CommandHelp {
+        command: Command::Import,
+        usage: "/import <file> [index]",
+        about: "Import a ChatGPT export, OpenAI messages JSON or markdown transcript; index picks one conversation of an export.",
+        options: &[],
+        examples: &["/import conversations.json 3", "/import notes.md"],
+    },
+    CommandHelp {
+        command: Command::Log,
+        usage: "/log on <file> | off",
+        about: "Append every prompt and answer to a markdown transcript.",
+        options: &[("on <file>", "start logging"), ("off", "stop")],
+        examples: &["/log on ~/notes/chat.md", "/log off"],
+    },
+    CommandHelp {
+        command: Command::Mirror,
+        usage: "/mirror [--append] <path> | off",
+        about: "Write answers to a file as they stream, e.g. for an editor pane.",
+        options: &[("--append", "keep earlier answers in the file"), ("off", "stop mirroring")],
+        examples: &["/mirror /tmp/answer.md", "/mirror --append /tmp/answers.md", "/mirror off"],
+    },
+    CommandHelp {
+        command: Command::Word,
+        usage: "/word <word> | rm <word> | list | import <file> | seed",
+        about: "Manage the completion vocabulary.",
+        options: &[
+            ("rm <word>", "remove a word"),
+            ("list", "show the vocabulary"),
+            ("import <file>", "add the words of a file"),
+            ("seed", "words from saved sessions"),
+        ],
+        examples: &["/word tokio", "/word rm tokio"],
+    },
+    CommandHelp {
+        command: Command::Plugins,
+        usage: "/plugins [reload]",
+        about: "List the plugin commands found in the plugins dir. They are read when the REPL starts; reload picks up manifests added or changed since, and reports broken ones.",
+        options: &[("reload", "read the manifests again")],
+        examples: &["/plugins", "/plugins reload"],
+    },
+    CommandHelp {
+        command: Command::Help,
+        usage: "/help [command]",
+        about: "List the commands, or show usage, options and examples for one.",
+        options: &[],
+        examples: &["/help", "/help save"],
+    },
+];
+
+// Help for `name` (without the '/'; aliases included); None means it isn't a built-in command
+pub fn command_help(name: &str) -> Option<&'static CommandHelp> {
+    let command = builtin(name)?;
+    COMMAND_HELP.iter().find(|help| help.command == command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_command_has_one_help_entry() {
+        for (name, command) in COMMANDS {
+            let entries = COMMAND_HELP.iter().filter(|help| help.command == *command).count();
+            assert_eq!(entries, 1, "/{} has {} COMMAND_HELP entries", name, entries);
+            assert!(command_help(name).is_some());
+        }
+        for help in COMMAND_HELP {
+            assert_eq!(builtin(help.command.name()), Some(help.command));
+        }
+    }
+
+    #[test]
+    fn aliases_resolve_to_their_command() {
+        assert_eq!(builtin("q"), Some(Command::Quit));
+        assert_eq!(builtin("/bye"), Some(Command::Quit));
+        assert_eq!(builtin("?"), Some(Command::Help));
+        assert_eq!(command_help("Q").map(|help| help.command), Some(Command::Quit));
+        assert_eq!(builtin("not-a-command"), None);
+    }
+}
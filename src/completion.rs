@@ -1,6 +1,12 @@
 // completion.rs
+use crate::command::{command_keywords, parse_command, ArgKind};
 use crate::config::get_sessions_dir;
 use crate::config::AVAILABLE_MODELS;
+use crate::plugins;
+use crate::snippets;
+use crate::ui::theme;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
 use rustyline::completion::{Completer, Pair};
 use rustyline::highlight::Highlighter;
 use rustyline::hint::Hinter;
@@ -33,7 +39,38 @@ lazy_static! {
     ]));
 }
 
-pub struct CommandCompleter;
+#[derive(Default)]
+pub struct CommandCompleter {
+    pub multi_line: bool,           // collecting `:::` multi-line input
+    pub fence_lang: Option<String>, // Some(lang) while inside a ``` fence in multi-line mode
+}
+
+// Fuzzy (subsequence) match `pattern` against each replacement, best scores first.
+// Items are (display, replacement); an empty pattern keeps the original order.
+fn fuzzy_pairs(pattern: &str, items: Vec<(String, String)>) -> Vec<Pair> {
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut scored: Vec<(i64, Pair)> = items
+        .into_iter()
+        .filter_map(|(display, replacement)| {
+            let score = if pattern.is_empty() {
+                0
+            } else {
+                matcher.fuzzy_match(&replacement, pattern)?
+            };
+            Some((score, Pair { display, replacement }))
+        })
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score)); // stable, so ties keep list order
+    scored.into_iter().map(|(_, pair)| pair).collect()
+}
+
+fn wordlist_pairs(pattern: &str) -> Vec<Pair> {
+    let wordlist = WORDLIST.lock().unwrap();
+    fuzzy_pairs(
+        pattern,
+        wordlist.iter().map(|w| (w.clone(), w.clone())).collect(),
+    )
+}
 
 impl Completer for CommandCompleter {
     type Candidate = Pair;
@@ -52,30 +89,24 @@ impl Completer for CommandCompleter {
                 // Complete command names
                 let commands = vec![
                     "/help", "/clear", "/quit", "/system", "/mic", "/cls", "/save", "/load",
-                    "/title", "/status", "/model", "/word",
+                    "/title", "/status", "/model", "/word", "/import", "/dictate", "/log", "/think",
+                    "/stopseq", "/maxtokens", "/continue", "/speed", "/compare", "/mirror", "/translate",
+                    "/ctx", "/memory", "/diff", "/rate", "/snippet", "/attach", "/detach", "/attachments", "/preview", "/var", "/plugins",
                 ];
-                let mut candidates = Vec::new();
-                for command in &commands {
-                    if command.to_lowercase().starts_with(line_to_cursor) {
-                        candidates.push(Pair {
-                            display: command.to_string(),
-                            replacement: command.to_string(),
-                        });
-                    }
+                let mut items: Vec<(String, String)> = commands
+                    .iter()
+                    .map(|c| (c.to_string(), c.to_string()))
+                    .collect();
+                // Plugin commands discovered from manifests
+                for plugin in plugins::loaded() {
+                    let command = format!("/{}", plugin.name);
+                    items.push((command.clone(), command));
                 }
+                let candidates = fuzzy_pairs(line_to_cursor, items);
                 return Ok((0, candidates));
             } else {
                 // Wordlist-based autocompletion for first word
-                let mut candidates = Vec::new();
-                let wordlist = WORDLIST.lock().unwrap();
-                for word in wordlist.iter() {
-                    if word.to_lowercase().starts_with(line_to_cursor) {
-                        candidates.push(Pair {
-                            display: word.clone(),
-                            replacement: word.clone(),
-                        });
-                    }
-                }
+                let candidates = wordlist_pairs(line_to_cursor);
                 return Ok((0, candidates));
             }
         }
@@ -95,6 +126,7 @@ impl Completer for CommandCompleter {
                 // First argument completion for /system
                 if words.len() == 2 {
                     let predefined_roles = vec![
+                        "reset",
                         "coding_assistant",
                         "creative_writer",
                         "technical_support",
@@ -150,15 +182,13 @@ impl Completer for CommandCompleter {
             "/model" => {
                 // Model selection (first argument)
                 if words.len() == 2 {
-                    let mut candidates = Vec::new();
-                    for model in AVAILABLE_MODELS {
-                        if model.starts_with(current_word) {
-                            candidates.push(Pair {
-                                display: model.to_string(),
-                                replacement: model.to_string(),
-                            });
-                        }
-                    }
+                    let candidates = fuzzy_pairs(
+                        current_word,
+                        AVAILABLE_MODELS
+                            .iter()
+                            .map(|m| (m.to_string(), m.to_string()))
+                            .collect(),
+                    );
                     return Ok((current_word_start, candidates));
                 }
                 // Model params (second and subsequent arguments)
@@ -180,7 +210,7 @@ impl Completer for CommandCompleter {
                 // Session file selection (first argument)
                 if words.len() == 2 {
                     let sessions_dir = get_sessions_dir();
-                    let mut candidates = Vec::new();
+                    let mut items = Vec::new();
                     if let Ok(entries) = fs::read_dir(sessions_dir) {
                         for entry in entries.flatten() {
                             let path = entry.path();
@@ -190,17 +220,13 @@ impl Completer for CommandCompleter {
                                         Ok(model) => format!("{} ({})", filename, model),
                                         Err(_) => filename.to_string(),
                                     };
-
-                                    if filename.to_lowercase().starts_with(current_word) {
-                                        candidates.push(Pair {
-                                            display: model_display,
-                                            replacement: filename.to_string(),
-                                        });
-                                    }
+                                    items.push((model_display, filename.to_string()));
                                 }
                             }
                         }
                     }
+                    items.push(("--list".to_string(), "--list".to_string()));
+                    let candidates = fuzzy_pairs(current_word, items);
                     return Ok((current_word_start, candidates));
                 }
                 // Load options (second and subsequent arguments)
@@ -220,82 +246,135 @@ impl Completer for CommandCompleter {
             }
             "/title" => {
                 // For multi-word titles, offer words from the wordlist
-                let mut candidates = Vec::new();
-                let wordlist = WORDLIST.lock().unwrap();
-                for word in wordlist.iter() {
-                    if word.to_lowercase().starts_with(current_word) {
-                        candidates.push(Pair {
-                            display: word.clone(),
-                            replacement: word.clone(),
-                        });
-                    }
-                }
+                let candidates = wordlist_pairs(current_word);
                 return Ok((current_word_start, candidates));
             }
             "/word" => {
                 // add word to wordlist
-                if words.len() == 2 {
-                    // Offer completion from existing wordlist, as a suggestion
+                // Index of the argument under the cursor (a trailing space starts a new one)
+                let arg_index = if line_to_cursor.ends_with(' ') { words.len() } else { words.len() - 1 };
+                if arg_index == 1 {
+                    // Subcommands first, then the existing wordlist as a suggestion
                     let mut candidates = Vec::new();
-                    let wordlist = WORDLIST.lock().unwrap();
-                    for word in wordlist.iter() {
-                        if word.to_lowercase().starts_with(current_word) {
+                    for sub in ["rm", "list", "import", "seed"] {
+                        if sub.starts_with(current_word) {
                             candidates.push(Pair {
-                                display: word.clone(),
-                                replacement: word.clone(),
+                                display: sub.to_string(),
+                                replacement: sub.to_string(),
                             });
                         }
                     }
+                    candidates.extend(wordlist_pairs(current_word));
+                    return Ok((current_word_start, candidates));
+                } else if arg_index == 2 && words[1] == "rm" {
+                    let candidates = wordlist_pairs(current_word);
                     return Ok((current_word_start, candidates));
                 } else {
                     //No completion options after the word.
                     return Ok((pos, Vec::new()));
                 }
             }
+            "/compare" => {
+                // Two model names, `stats` also fits the first slot
+                let arg_index = if line_to_cursor.ends_with(' ') { words.len() } else { words.len() - 1 };
+                if arg_index <= 2 {
+                    let mut items: Vec<(String, String)> =
+                        AVAILABLE_MODELS.iter().map(|m| (m.to_string(), m.to_string())).collect();
+                    if arg_index == 1 {
+                        items.push(("stats".to_string(), "stats".to_string()));
+                    }
+                    let candidates = fuzzy_pairs(current_word, items);
+                    return Ok((current_word_start, candidates));
+                }
+                return Ok((pos, Vec::new()));
+            }
+            "/snippet" => {
+                // Subcommands and snippet names, then the name to replace or remove
+                let arg_index = if line_to_cursor.ends_with(' ') { words.len() } else { words.len() - 1 };
+                let names = || snippets::names().into_iter().map(|n| (n.clone(), n));
+                let items: Vec<(String, String)> = match arg_index {
+                    1 => command_keywords("snippet").iter().map(|k| (k.to_string(), k.to_string())).chain(names()).collect(),
+                    2 if matches!(words[1], "save" | "rm") => names().collect(),
+                    _ => return Ok((pos, Vec::new())),
+                };
+                return Ok((current_word_start, fuzzy_pairs(current_word, items)));
+            }
+            "/think" | "/log" | "/stopseq" | "/maxtokens" | "/speed" | "/mirror" | "/translate" | "/ctx"
+            | "/memory" | "/preview" | "/var" | "/plugins" | "/help" => {
+                if words.len() <= 2 {
+                    let keywords = command_keywords(&command[1..]);
+                    let candidates = fuzzy_pairs(
+                        current_word,
+                        keywords.iter().map(|k| (k.to_string(), k.to_string())).collect(),
+                    );
+                    return Ok((current_word_start, candidates));
+                }
+            }
             // Add more command-specific completions for other commands
             _ => {
-                // For any other command or non-command, do word completion from wordlist
-                let mut candidates = Vec::new();
-                let wordlist = WORDLIST.lock().unwrap();
-                for word in wordlist.iter() {
-                    if word.to_lowercase().starts_with(current_word) {
-                        candidates.push(Pair {
-                            display: word.clone(),
-                            replacement: word.clone(),
-                        });
-                    }
+                // Plugin arguments come from the manifest's completion entries
+                if let Some(plugin) = command
+                    .strip_prefix('/')
+                    .and_then(plugins::find_plugin)
+                {
+                    let candidates = fuzzy_pairs(
+                        current_word,
+                        plugin.completions.iter().map(|c| (c.clone(), c.clone())).collect(),
+                    );
+                    return Ok((current_word_start, candidates));
                 }
+                // For any other command or non-command, do word completion from wordlist
+                let candidates = wordlist_pairs(current_word);
                 return Ok((current_word_start, candidates));
             }
         }
 
         // Default case: use the wordlist for any word completion
-        let mut candidates = Vec::new();
-        let wordlist = WORDLIST.lock().unwrap();
-        for word in wordlist.iter() {
-            if word.to_lowercase().starts_with(current_word) {
-                candidates.push(Pair {
-                    display: word.clone(),
-                    replacement: word.clone(),
-                });
-            }
-        }
+        let candidates = wordlist_pairs(current_word);
         Ok((current_word_start, candidates))
     }
 }
 
+impl CommandCompleter {
+    // `:::` markers, fence lines and fenced code are styled while collecting multi-line input
+    fn highlight_multi_line<'l>(&self, line: &'l str) -> Cow<'l, str> {
+        let trimmed = line.trim();
+        if trimmed == ":::" {
+            Cow::Owned(theme::MARKER.paint(line))
+        } else if let Some(lang) = trimmed.strip_prefix("```") {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            Cow::Owned(format!("{}{}{}", indent, theme::VALUE.paint("```"), theme::KEYWORD.paint(lang)))
+        } else if self.fence_lang.is_some() {
+            Cow::Owned(theme::VALUE.paint(line))
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+}
+
 impl Highlighter for CommandCompleter {
     fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
-        if line.starts_with('/') {
-            // Highlight the command part in green
-            if let Some(space_pos) = line.find(' ') {
-                let (cmd, args) = line.split_at(space_pos);
-                // Command in green, args in a different color
-                Cow::Owned(format!("\x1b[32m{}\x1b[36m{}\x1b[0m", cmd, args))
-            } else {
-                // Just the command, no args yet
-                Cow::Owned(format!("\x1b[32m{}\x1b[0m", line))
+        if self.multi_line {
+            return self.highlight_multi_line(line);
+        }
+        if let Some(parsed) = parse_command(line) {
+            // Command in green, each argument colored by its kind
+            let mut out = theme::GOOD.paint(format!("/{}", parsed.name));
+            let mut last = 1 + parsed.name.len();
+            for arg in &parsed.args {
+                let style = match arg.kind {
+                    ArgKind::Subcommand => theme::KEYWORD,
+                    ArgKind::Flag => theme::TOOL,
+                    ArgKind::Number => theme::LINK,
+                    ArgKind::Path => theme::PATH,
+                    ArgKind::Text => theme::INFO,
+                };
+                out.push_str(&line[last..arg.start]);
+                out.push_str(&style.paint(arg.text));
+                last = arg.start + arg.text.len();
             }
+            out.push_str(&line[last..]);
+            Cow::Owned(out)
         } else {
             // Regular text highlighting
             let wordlist = WORDLIST.lock().unwrap();
@@ -303,12 +382,17 @@ impl Highlighter for CommandCompleter {
                 .iter()
                 .any(|word| line.to_lowercase().starts_with(&word.to_lowercase()))
             {
-                Cow::Owned(format!("\x1b[33m{}\x1b[0m", line))
+                Cow::Owned(theme::VALUE.paint(line))
             } else {
                 Cow::Borrowed(line)
             }
         }
     }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        // Argument colors depend on the whole line, so redraw on every keystroke
+        true
+    }
 }
 
 impl Hinter for CommandCompleter {
@@ -0,0 +1,138 @@
+// condense.rs
+// Documents too large for the model's context (-f, .file, summarize) are condensed before they
+// are sent instead of going out oversized: each part is summarized on its own (with an eye on the
+// question, when there is one), then the notes are joined and condensed again while they still
+// don't fit; the real prompt is answered from the notes. Each part is reported as it is done.
+use crate::cli::QueryError;
+use crate::config::context_window;
+use crate::endpoints::ChatBackend;
+use crate::output;
+use crate::redact;
+use crate::ui::spinner::Spinner;
+use genai::chat::{ChatMessage, ChatRequest};
+
+const CONDENSE_SYSTEM_PROMPT: &str = "You condense one part of a longer document so it can be read in place of \
+the original. Keep every fact, name, number, date, definition, code identifier and conclusion; drop repetition \
+and filler. Answer with the condensed text only, in the language of the document.";
+
+// A document may take this share of the context window; the rest is for history and the answer
+const BUDGET_PERCENT: usize = 50;
+// Passes over the notes before giving up on making them fit
+const MAX_ROUNDS: usize = 3;
+
+// Rough token count (about 4 characters per token)
+fn tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+fn budget(model: &str) -> usize {
+    context_window(model) * BUDGET_PERCENT / 100
+}
+
+// Consecutive pieces of `text` of at most `max_tokens` each, cut at paragraph ends where
+// possible, then at line ends, and inside a line only when one line alone is too long
+fn split(text: &str, max_tokens: usize) -> Vec<String> {
+    let max_chars = max_tokens.max(1) * 4;
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    for line in text.split_inclusive('\n') {
+        let mut line = line;
+        while line.chars().count() > max_chars {
+            let cut = line.char_indices().nth(max_chars).map_or(line.len(), |(i, _)| i);
+            if !part.is_empty() {
+                parts.push(std::mem::take(&mut part));
+            }
+            parts.push(line[..cut].to_string());
+            line = &line[cut..];
+        }
+        if part.chars().count() + line.chars().count() > max_chars {
+            // Back up to the last blank line so paragraphs stay together
+            let carry = match part.rfind("\n\n") {
+                Some(i) if i > part.len() / 2 => part.split_off(i + 2),
+                _ => String::new(),
+            };
+            parts.push(std::mem::replace(&mut part, carry));
+            if part.chars().count() + line.chars().count() > max_chars {
+                parts.push(std::mem::take(&mut part));
+            }
+        }
+        part.push_str(line);
+    }
+    if !part.trim().is_empty() {
+        parts.push(part);
+    }
+    parts
+}
+
+// `text` as it can be sent to `model`: unchanged when it fits the budget, condensed otherwise,
+// keeping what matters for `question`
+pub async fn fit(
+    client: &impl ChatBackend,
+    model: &str,
+    name: &str,
+    text: String,
+    question: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let budget = budget(model);
+    let original = tokens(&text);
+    if original <= budget {
+        return Ok(text);
+    }
+    // The parts go out before the prompt is checked as a whole
+    let mut text = redact::check_outgoing(&text)?;
+    if !output::is_quiet() {
+        eprintln!(
+            "'{}' is about {} tokens, more than {} can take in one prompt; condensing it first",
+            name, original, model
+        );
+    }
+    let mut total_parts = 0;
+    for round in 1..=MAX_ROUNDS {
+        let parts = split(&text, budget);
+        let mut notes = Vec::with_capacity(parts.len());
+        for (i, part) in parts.iter().enumerate() {
+            let label = match round {
+                1 => format!("Condensing {}: part {}/{}", name, i + 1, parts.len()),
+                _ => format!("Condensing {} (pass {}): part {}/{}", name, round, i + 1, parts.len()),
+            };
+            let mut spinner = Spinner::start(&label);
+            let mut prompt = format!("Part {} of {} of '{}':\n\n{}", i + 1, parts.len(), name, part);
+            if let Some(question) = question {
+                prompt.push_str(&format!(
+                    "\n\nThe document will be used to answer: {}\nKeep everything that bears on it.",
+                    question
+                ));
+            }
+            let request = ChatRequest::new(vec![ChatMessage::system(CONDENSE_SYSTEM_PROMPT), ChatMessage::user(prompt)]);
+            let response = client.chat(model, request, None).await;
+            spinner.stop();
+            let note = response?
+                .content_text_as_str()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .ok_or(QueryError::NoAnswer)?;
+            if !output::is_quiet() {
+                eprintln!("  {}: ~{} -> ~{} tokens", label, tokens(part), tokens(&note));
+            }
+            notes.push(note);
+        }
+        total_parts += parts.len();
+        text = notes.join("\n\n");
+        if tokens(&text) <= budget {
+            break;
+        }
+    }
+    if tokens(&text) > budget {
+        return Err(QueryError::Input(format!(
+            "'{}' is still about {} tokens after condensing; try a model with a larger context",
+            name,
+            tokens(&text)
+        ))
+        .into());
+    }
+    if !output::is_quiet() {
+        eprintln!("Condensed '{}' from about {} to {} tokens in {} requests", name, original, tokens(&text), total_parts);
+    }
+    Ok(format!("[Condensed from a longer document; details may be missing]\n\n{}", text))
+}
+
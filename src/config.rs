@@ -1,23 +1,138 @@
 // config.rs
 use crate::completion::WORDLIST;
+use crate::config_schema;
 use directories::ProjectDirs;
+use fs2::FileExt;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
 const WORDLIST_FILE: &str = "wordlist.txt";
 
+// Words removed since the last save, so a merge with the file doesn't bring them back (lowercase)
+static REMOVED_WORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
 #[derive(Deserialize, Serialize, Default)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub default_model: Option<String>,
     pub stream: Option<bool>,
+    pub request_timeout: Option<u64>, // seconds per request, overridden by --timeout
+    pub proxy_url: Option<String>, // e.g. "http://proxy.corp:3128" (HTTP(S)_PROXY is used otherwise)
+    pub ca_cert: Option<String>,   // PEM bundle trusted in addition to the system roots
+    pub llama_server: Option<String>,      // llama.cpp server binary for local:<path.gguf> models
+    pub llama_server_args: Option<String>, // extra arguments, e.g. "-ngl 99 -c 8192"
+    pub auto_fast_model: Option<String>,   // -m auto: short factual questions
+    pub auto_strong_model: Option<String>, // -m auto: code, long or keyword-matched prompts
+    pub auto_max_fast_chars: Option<usize>,
+    pub auto_strong_keywords: Option<Vec<String>>,
     pub zero_alias: Option<String>, // Custom alias for "zero"
     pub one_alias: Option<String>,  // Custom alias for "one"
     pub two_alias: Option<String>,  // Custom alias for "two"
     pub three_alias: Option<String>,  // Custom alias for "three"
     pub temp_dir: Option<String>,
+    pub data_dir: Option<String>,  // sessions, history, wordlist, ledgers (default: XDG data dir)
+    pub cache_dir: Option<String>, // ctx bundle (default: XDG cache dir)
+    pub env_file: Option<String>,  // KEY=VALUE lines (API keys) exported at startup, e.g. per profile
+    pub wordlist_seed: Option<String>, // "default" (built-in list) or "history" (words from saved sessions)
+    pub mic_max_secs: Option<u64>,        // hard recording limit (default 30)
+    pub mic_silence_stop_ms: Option<u64>, // stop after this much silence once speech was heard
+    pub mic_silence_threshold: Option<f32>,
+    pub mic_sample_rate: Option<u32>, // output WAV sample rate (default 16000)
+    pub transcribe_backend: Option<String>, // "command" (default) or "openai"
+    pub transcribe_command: Option<String>, // e.g. "whisper-cli -m {model} -nt -f {file}"
+    pub transcribe_model: Option<String>,
+    pub transcribe_url: Option<String>, // OpenAI-compatible /audio/transcriptions endpoint
+    pub ocr_backend: Option<String>, // --ocr: "command" (default) or "vision"
+    pub ocr_command: Option<String>, // default "tesseract {file} stdout"
+    pub ocr_model: Option<String>,   // vision model for ocr_backend = "vision" (default gpt-4o-mini)
+    pub dictate_chunk_secs: Option<u64>, // /dictate transcription interval (default 4)
+    pub answer_file: Option<String>,     // mic.md answers are written here (default <temp_dir>/ans.md)
+    pub transcript_log: Option<String>,  // markdown transcript appended during interactive sessions
+    pub sync_remote: Option<String>, // `llm sync`: a .git URL, s3://bucket/prefix, webdav+https://host/dir or rclone:remote:path
+    pub sync_passphrase_command: Option<String>, // prints the sync passphrase when LLM_SYNC_PASSPHRASE is unset
+    pub max_sessions: Option<usize>,     // oldest saved sessions are removed beyond this many
+    pub autosave_sessions: Option<bool>, // save under a generated title on exit (default true)
+    pub preference_log: Option<String>,  // /compare votes as JSONL (default <data dir>/preferences.jsonl)
+    pub show_stats: Option<bool>,        // dim timing/token/cost footer after each answer
+    pub usage_ledger: Option<String>,    // JSONL of every answered request (default <data dir>/usage.jsonl)
+    pub pin_status_line: Option<bool>,   // keep the interactive title on the first terminal line
+    pub prompt_template: Option<String>, // interactive prompt, e.g. "{model} {tokens}/{ctx} > "
+    pub stream_char_delay: Option<u64>,  // ms per character of streamed output, 0 = raw
+    pub stream_flush: Option<String>,    // "line" (default) or "token"
+    pub render_latex: Option<bool>,      // render $...$ math as Unicode (default true)
+    pub auto_continue: Option<bool>,     // fetch the rest of answers cut off at /maxtokens (default false)
+    pub confirm_send: Option<bool>,      // show each request and wait for Enter before sending (default false)
+    pub memory: Option<bool>,            // long-term memory across sessions (default false)
+    pub memory_embedding_url: Option<String>, // OpenAI-compatible /embeddings endpoint for memory recall
+    pub memory_embedding_model: Option<String>, // default "text-embedding-3-small"
+    pub memory_embedding_api_key_env: Option<String>, // env var with the key for memory_embedding_url
+    pub redact_mode: Option<String>,     // secrets in prompts: "confirm" (default), "mask" or "off"
+    pub redact_patterns: Option<Vec<String>>, // extra regexes treated as secrets
+    pub sanitize_prompts: Option<bool>,  // scrub home dirs and sanitize_patterns from prompts too (default false)
+    pub sanitize_patterns: Option<Vec<String>>, // hostnames, usernames, domains to scrub from tool output
+    pub accessible: Option<bool>,        // same as --accessible
+    pub offline: Option<bool>,           // same as --offline
+    pub language: Option<String>,        // UI strings: "en" or "zh-TW" (default: from LANG)
+    pub history_save: Option<HistorySave>, // interactive line history: true (default), false or "filtered"
+    // Tables/arrays of tables must stay after the plain values (toml serialization order)
+    pub mic_routes: Option<Vec<MicRoute>>,
+    pub endpoints: Option<Vec<EndpointConfig>>,
+    pub prices: Option<HashMap<String, ModelPrice>>, // per model name, overrides the built-in table
+    pub rate_limits: Option<HashMap<String, RateLimit>>, // per provider: "openai", "gemini", an [[endpoints]] name, ...
+    pub roles: Option<HashMap<String, String>>, // extra personas for /system and --role: name = system prompt
+    pub summary_templates: Option<HashMap<String, String>>, // `summarize --length <name>` presets, `{input}` = text
+    pub model_keys: Option<HashMap<String, String>>, // interactive key = model, e.g. "alt-4" = "gpt-4o" (adds to Alt-0..Alt-3)
+    pub profiles: Option<HashMap<String, toml::Value>>, // [profiles.<name>]: keys that replace the ones above
+}
+
+/// `history_save = true | false | "filtered"`; filtered leaves out lines that look like secrets.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum HistorySave {
+    Flag(bool),
+    Mode(String),
+}
+
+/// USD per million tokens, e.g. `[prices] "my-model" = { input = 0.5, output = 1.5 }`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// Requests and tokens per minute for a provider, e.g. `[rate_limits] openai = { requests_per_minute = 50 }`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u64>,
+}
+
+/// Routing rule for mic.md content, e.g. `[[mic_routes]] pattern = "^code:"`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MicRoute {
+    pub pattern: String,          // regex matched against the start of the content
+    pub model: Option<String>,    // model to answer with (default: session model)
+    pub role: Option<String>,     // predefined role name or a literal system prompt
+    pub template: Option<String>, // message template, `{input}` is the content after the match
+}
+
+/// OpenAI/Anthropic-compatible gateway, used for models named `<name>/<model>`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointConfig {
+    pub name: String,                             // model prefix, e.g. "openrouter"
+    pub url: String,                              // base url, e.g. "https://openrouter.ai/api/v1/"
+    pub adapter: Option<String>,                  // "openai" (default) or "anthropic" wire format
+    pub api_key_env: Option<String>,              // env var holding the key
+    pub headers: Option<HashMap<String, String>>, // extra headers sent with every request
 }
 
 pub fn get_config_file_path() -> PathBuf {
@@ -40,75 +155,341 @@ pub fn get_temp_file_path(temp_dir: &str, filename: &str) -> PathBuf {
 }
 
 pub fn get_sessions_dir() -> PathBuf {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "leware", "ai_llm") {
-        let sessions_dir = proj_dirs.config_dir().join("sessions");
-        std::fs::create_dir_all(&sessions_dir).expect("Failed to create sessions directory");
-        sessions_dir
-    } else {
-        PathBuf::from("sessions") // Fallback in current directory, but config dir is preferred
+    let sessions_dir = get_data_dir().join("sessions");
+    std::fs::create_dir_all(&sessions_dir).expect("Failed to create sessions directory");
+    sessions_dir
+}
+
+// `data_dir` / `cache_dir` from config, set once by init_dirs
+static DIR_OVERRIDES: OnceLock<(Option<PathBuf>, Option<PathBuf>)> = OnceLock::new();
+
+// Files that used to live in the config dir and belong in the data dir
+const DATA_ENTRIES: &[&str] = &["sessions", "history.txt", WORDLIST_FILE, "preferences.jsonl", "usage.jsonl"];
+
+// Sessions, history, wordlist and ledgers (~/.local/share/ai_llm on Linux)
+pub fn get_data_dir() -> PathBuf {
+    let dir = match DIR_OVERRIDES.get().and_then(|(data, _)| data.clone()) {
+        Some(dir) => dir,
+        None => match ProjectDirs::from("com", "leware", "ai_llm") {
+            Some(proj_dirs) => proj_dirs.data_dir().to_path_buf(),
+            None => return get_config_dir(),
+        },
+    };
+    std::fs::create_dir_all(&dir).expect("Failed to create data directory");
+    dir
+}
+
+// Rebuildable files such as the ctx bundle (~/.cache/ai_llm on Linux)
+pub fn get_cache_dir() -> PathBuf {
+    let dir = match DIR_OVERRIDES.get().and_then(|(_, cache)| cache.clone()) {
+        Some(dir) => dir,
+        None => match ProjectDirs::from("com", "leware", "ai_llm") {
+            Some(proj_dirs) => proj_dirs.cache_dir().to_path_buf(),
+            None => return get_config_dir(),
+        },
+    };
+    std::fs::create_dir_all(&dir).expect("Failed to create cache directory");
+    dir
+}
+
+// `data_dir` and `cache_dir` from config, then move data files out of the config dir once;
+// call at startup before anything reads them
+pub fn init_dirs(config: &Config) {
+    let expand = |dir: &Option<String>| dir.as_ref().map(|d| PathBuf::from(expand_home(d)));
+    let _ = DIR_OVERRIDES.set((expand(&config.data_dir), expand(&config.cache_dir)));
+    migrate_data_files();
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    }
+}
+
+// Earlier versions kept everything in the config dir. Entries already present in the data dir
+// are left alone, so this only ever moves things once.
+fn migrate_data_files() {
+    let config_dir = get_config_dir();
+    let data_dir = get_data_dir();
+    if config_dir == data_dir {
+        return; // macOS/Windows: both are the same application support folder
     }
+    for name in DATA_ENTRIES {
+        let from = config_dir.join(name);
+        let to = data_dir.join(name);
+        if !from.exists() || to.exists() {
+            continue;
+        }
+        match fs::rename(&from, &to) {
+            Ok(()) => eprintln!("Moved '{}' to '{}'", from.display(), to.display()),
+            Err(e) => eprintln!("Failed to move '{}' to '{}': {}", from.display(), to.display(), e),
+        }
+    }
+}
+
+// --profile / LLM_PROFILE, set once before the config is first loaded
+static PROFILE: OnceLock<Option<String>> = OnceLock::new();
+
+pub fn set_profile(profile: Option<String>) {
+    let _ = PROFILE.set(profile.filter(|p| !p.is_empty()));
+}
+
+pub fn active_profile() -> Option<&'static str> {
+    PROFILE.get().and_then(|p| p.as_deref())
+}
+
+// profiles/<name>.toml next to config.toml, the alternative to a [profiles.<name>] section
+pub fn get_profile_file_path(name: &str) -> PathBuf {
+    get_config_dir().join("profiles").join(format!("{}.toml", name))
 }
 
 pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
     let config_path = get_config_file_path();
-    if let Ok(config_str) = std::fs::read_to_string(&config_path) {
-        let mut config: Config = toml::from_str(&config_str)?;
-        // Set default temp_dir if not specified in the config
-        //
-        let default_temp_dir = env::temp_dir(); // Get OS-default temp dir
-        if config.temp_dir.is_none() {
-            config.temp_dir = Some(default_temp_dir.to_str().unwrap_or("./").to_string());
-        }
-        Ok(config)
-    } else {
-        Ok(Config::default())
+    let mut config = match std::fs::read_to_string(&config_path) {
+        Ok(config_str) => config_schema::parse(&config_path, &config_str)?,
+        Err(_) => Config::default(),
+    };
+    if let Some(profile) = active_profile() {
+        config = config_schema::apply_profile(config, profile, &get_profile_file_path(profile))?;
+    }
+    // Set default temp_dir if not specified in the config
+    //
+    let default_temp_dir = env::temp_dir(); // Get OS-default temp dir
+    if config.temp_dir.is_none() {
+        config.temp_dir = Some(default_temp_dir.to_str().unwrap_or("./").to_string());
+    }
+    Ok(config)
+}
+
+// Set (or with None, remove) top-level keys of the active profile: its own file when it has one,
+// otherwise its section in config.toml, or config.toml itself without a profile
+pub fn save_settings(settings: &[(&str, Option<toml::Value>)]) -> Result<(), Box<dyn std::error::Error>> {
+    let profile_path = active_profile().map(get_profile_file_path).filter(|p| p.exists());
+    let path = profile_path.clone().unwrap_or_else(get_config_file_path);
+    let text = fs::read_to_string(&path).unwrap_or_default();
+    let mut root: toml::value::Table = toml::from_str(&text)?;
+    let table = match (active_profile(), profile_path) {
+        (Some(profile), None) => root
+            .entry("profiles")
+            .or_insert_with(|| toml::Value::Table(Default::default()))
+            .as_table_mut()
+            .ok_or("`profiles` in config.toml is not a table")?
+            .entry(profile)
+            .or_insert_with(|| toml::Value::Table(Default::default()))
+            .as_table_mut()
+            .ok_or_else(|| format!("`profiles.{}` in config.toml is not a table", profile))?,
+        _ => &mut root,
+    };
+    for (key, value) in settings {
+        match value {
+            Some(value) => table.insert(key.to_string(), value.clone()),
+            None => table.remove(*key),
+        };
     }
+    write_atomic(&path, toml::to_string(&root)?.as_bytes())?;
+    Ok(())
 }
 
-pub fn save_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
-    let config_path = get_config_file_path(); // Use get_config_file_path() internally
-    let toml_str = toml::to_string(config)?;
-    std::fs::write(&config_path, toml_str)?;
+// Export `env_file` (KEY=VALUE per line, # comments) so providers pick up the profile's keys
+pub fn load_env_file(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(path) = &config.env_file else {
+        return Ok(());
+    };
+    let path = expand_home(path);
+    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read env_file '{}': {}", path, e))?;
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        match line.split_once('=') {
+            Some((key, value)) => env::set_var(key.trim(), value.trim().trim_matches(|c| c == '"' || c == '\'')),
+            None => eprintln!("Ignoring line without '=' in env_file '{}': {}", path, line),
+        }
+    }
     Ok(())
 }
 
 pub fn load_wordlist() {
-    let path = get_config_dir().join(WORDLIST_FILE);
+    let path = get_data_dir().join(WORDLIST_FILE);
     if path.exists() {
         match fs::read_to_string(&path) {
             Ok(data) => {
-                let words: Vec<String> = data.lines().map(String::from).collect();
-                let _word_count = words.len(); // Calculate length before moving
+                let words: Vec<String> = data
+                    .lines()
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect();
                 let mut wordlist = WORDLIST.lock().unwrap();
-                *wordlist = words; // Move happens here
-                                   //println!("Loaded {} words from {:?}", word_count, path); // Use word_count instead
+                *wordlist = normalize_words(words);
             }
             Err(e) => {
                 eprintln!("Failed to load wordlist from {:?}: {}", path, e);
             }
         }
+    } else if load_config()
+        .ok()
+        .and_then(|c| c.wordlist_seed)
+        .is_some_and(|seed| seed == "history")
+    {
+        // First run with history seeding: replace the built-in list with words from saved sessions
+        let words = seed_words_from_history(50);
+        if !words.is_empty() {
+            let mut wordlist = WORDLIST.lock().unwrap();
+            *wordlist = normalize_words(words);
+        }
     }
 }
 
+// Merge with what other instances saved since we loaded: their additions are kept, words removed
+// here stay removed. The file is replaced atomically under an advisory lock.
 pub fn save_wordlist() {
+    let path = get_data_dir().join(WORDLIST_FILE);
+    if let Err(e) = merge_and_write_wordlist(&path) {
+        eprintln!("Error saving wordlist to {:?}: {}", path, e);
+    }
+}
+
+fn merge_and_write_wordlist(path: &Path) -> std::io::Result<()> {
+    let lock = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_extension("txt.lock"))?;
+    lock.lock_exclusive()?;
+    let on_disk: Vec<String> = fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
     let data = {
-        let wordlist = WORDLIST.lock().unwrap();
+        let mut removed = REMOVED_WORDS.lock().unwrap();
+        let mut wordlist = WORDLIST.lock().unwrap();
+        let merged = wordlist.iter().cloned().chain(on_disk);
+        *wordlist = normalize_words(merged.filter(|w| !removed.contains(&w.to_lowercase())).collect());
+        removed.clear();
         wordlist.join("\n")
-    }; // Lock is released here
-       //let wordlist = WORDLIST.lock().unwrap();
-       //let data = wordlist.join("\n");
-    let path = get_config_dir().join(WORDLIST_FILE); // Use config dir
-    match fs::File::create(&path) {
-        Ok(mut file) => match file.write_all(data.as_bytes()) {
-            Ok(_) => {}
-            Err(e) => {
-                eprintln!("Error writing to file: {}", e);
+    }; // Locks are released here
+    let result = write_atomic(path, data.as_bytes());
+    let _ = lock.unlock();
+    result
+}
+
+// Write to a temp file next to `path`, then rename over it, so readers never see a partial file
+pub fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    let result = fs::File::create(&tmp)
+        .and_then(|mut file| file.write_all(data).and_then(|_| file.sync_all()))
+        .and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+// Case-insensitive dedup (first spelling wins) and case-insensitive sort
+fn normalize_words(words: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut result: Vec<String> = words
+        .into_iter()
+        .filter(|w| seen.insert(w.to_lowercase()))
+        .collect();
+    result.sort_by_key(|w| w.to_lowercase());
+    result
+}
+
+// Returns false if the word (ignoring case) is already present
+pub fn add_word(word: &str) -> bool {
+    let mut wordlist = WORDLIST.lock().unwrap();
+    if wordlist.iter().any(|w| w.eq_ignore_ascii_case(word)) {
+        return false;
+    }
+    REMOVED_WORDS.lock().unwrap().retain(|w| !w.eq_ignore_ascii_case(word));
+    wordlist.push(word.to_string());
+    true
+}
+
+// Returns false if the word (ignoring case) was not found
+pub fn remove_word(word: &str) -> bool {
+    let mut wordlist = WORDLIST.lock().unwrap();
+    let before = wordlist.len();
+    wordlist.retain(|w| !w.eq_ignore_ascii_case(word));
+    if wordlist.len() == before {
+        return false;
+    }
+    REMOVED_WORDS.lock().unwrap().push(word.to_lowercase());
+    true
+}
+
+// Add every whitespace-separated word in the file, returns how many were new
+pub fn import_words(path: &std::path::Path) -> Result<usize, Box<dyn std::error::Error>> {
+    let data = fs::read_to_string(path)?;
+    let added = data
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != '-'))
+        .filter(|w| !w.is_empty())
+        .filter(|w| add_word(w))
+        .count();
+    Ok(added)
+}
+
+// Approximate "nouns" from saved sessions: the most frequent longer words that aren't common stopwords
+pub fn seed_words_from_history(limit: usize) -> Vec<String> {
+    const STOPWORDS: &[&str] = &[
+        "about", "above", "after", "again", "also", "answer", "because", "been", "before", "being",
+        "below", "between", "both", "can't", "could", "does", "doing", "down", "each", "even",
+        "every", "from", "further", "have", "having", "here", "how", "into", "just", "like",
+        "make", "many", "more", "most", "much", "must", "need", "only", "other", "over", "same",
+        "should", "some", "such", "than", "that", "their", "them", "then", "there", "these",
+        "they", "this", "those", "through", "under", "until", "used", "using", "very", "want",
+        "well", "were", "what", "when", "where", "which", "while", "will", "with", "would",
+        "your", "you're", "assistant", "helpful", "concisely", "clearly",
+    ];
+    let mut counts: std::collections::HashMap<String, (String, usize)> = std::collections::HashMap::new();
+    let entries = match fs::read_dir(get_sessions_dir()) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    for entry in entries.flatten() {
+        let Ok(data) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(&data) else {
+            continue;
+        };
+        let Some(messages) = json.get("messages").and_then(|m| m.as_array()) else {
+            continue;
+        };
+        for text in messages
+            .iter()
+            .filter_map(|m| m.pointer("/content/Text").and_then(|t| t.as_str()))
+        {
+            for word in text.split(|c: char| !c.is_alphanumeric() && c != '_') {
+                if word.len() < 4 || !word.chars().all(|c| c.is_alphabetic()) {
+                    continue;
+                }
+                let key = word.to_lowercase();
+                if STOPWORDS.contains(&key.as_str()) {
+                    continue;
+                }
+                counts.entry(key).or_insert_with(|| (word.to_string(), 0)).1 += 1;
             }
-        },
-        Err(e) => {
-            eprintln!("Error creating file: {}", e);
         }
     }
+    let mut ranked: Vec<(String, usize)> = counts.into_values().filter(|(_, n)| *n >= 2).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.into_iter().take(limit).map(|(w, _)| w).collect()
+}
+
+// Models of the zero/one/two/three commands (and Alt-0..Alt-3 in interactive mode)
+pub fn alias_models(config: &Config) -> [String; 4] {
+    let alias = |configured: &Option<String>, default: &str| configured.clone().unwrap_or_else(|| default.to_string());
+    [
+        alias(&config.zero_alias, "grok-2"),
+        alias(&config.one_alias, "gemini-2.0-flash"),
+        alias(&config.two_alias, "phi4:14b"),
+        alias(&config.three_alias, "gemma3:12b"),
+    ]
 }
 
 pub const AVAILABLE_MODELS: &[&str] = &[
@@ -119,4 +500,24 @@ pub const AVAILABLE_MODELS: &[&str] = &[
     "openthinker:7b",
     "qwen2.5:14b",
     "qwen-max",
+    "auto",
 ];
+
+// Approximate context window in tokens, for the prompt's {ctx} and usage display
+pub fn context_window(model: &str) -> usize {
+    let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    if name.starts_with("gemini") {
+        1_048_576
+    } else if name.starts_with("claude") {
+        200_000
+    } else if name.starts_with("gpt-4o") || name.starts_with("gpt-4.1") || name.starts_with("o1") || name.starts_with("o3") {
+        128_000
+    } else if name.starts_with("grok") {
+        131_072
+    } else if name.starts_with("deepseek") {
+        65_536
+    } else {
+        // qwen-max, and a common configured size for Ollama / llama.cpp models
+        32_768
+    }
+}
@@ -0,0 +1,173 @@
+// config_schema.rs
+// Friendly config.toml errors: every unknown key with a "did you mean", and type errors with the
+// file they came from. Field lists come from the serde derives, so they can't drift from Config.
+use crate::config::{Config, EndpointConfig, MicRoute, ModelPrice, RateLimit};
+use serde::de::{self, Deserialize, Visitor};
+use std::path::Path;
+
+// A Deserializer that only records the field list serde passes to deserialize_struct
+struct FieldNames(Option<&'static [&'static str]>);
+
+impl<'de> de::Deserializer<'de> for &mut FieldNames {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("field names only"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0 = Some(fields);
+        Err(de::Error::custom("field names only"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+fn fields_of<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut names = FieldNames(None);
+    let _ = T::deserialize(&mut names);
+    names.0.unwrap_or(&[])
+}
+
+// Levenshtein distance, for suggestions
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb { previous } else { 1 + previous.min(row[j]).min(row[j + 1]) };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+fn nearest(key: &str, fields: &[&'static str]) -> Option<&'static str> {
+    fields
+        .iter()
+        .map(|f| (distance(key, f), *f))
+        .filter(|(d, f)| *d <= (f.len() / 3).max(2) || f.starts_with(key) || key.starts_with(f))
+        .min()
+        .map(|(_, f)| f)
+}
+
+fn check_table(table: &toml::value::Table, fields: &[&'static str], prefix: &str, report: &mut Vec<String>) {
+    for key in table.keys().filter(|k| !fields.contains(&k.as_str())) {
+        let line = match nearest(key, fields) {
+            Some(suggestion) => format!("unknown key `{}{}`, did you mean `{}`?", prefix, key, suggestion),
+            None => format!("unknown key `{}{}`", prefix, key),
+        };
+        report.push(line);
+    }
+}
+
+// Unknown keys in a config table (the file itself or a profile), one message each
+fn check_config(root: &toml::value::Table, prefix: &str, report: &mut Vec<String>) {
+    check_table(root, fields_of::<Config>(), prefix, report);
+    let tables = |name: &str| root.get(name).and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    for (i, route) in tables("mic_routes").iter().enumerate() {
+        if let Some(route) = route.as_table() {
+            check_table(route, fields_of::<MicRoute>(), &format!("{}mic_routes[{}].", prefix, i), report);
+        }
+    }
+    for (i, endpoint) in tables("endpoints").iter().enumerate() {
+        if let Some(endpoint) = endpoint.as_table() {
+            check_table(endpoint, fields_of::<EndpointConfig>(), &format!("{}endpoints[{}].", prefix, i), report);
+        }
+    }
+    if let Some(prices) = root.get("prices").and_then(|v| v.as_table()) {
+        for (model, price) in prices {
+            if let Some(price) = price.as_table() {
+                check_table(price, fields_of::<ModelPrice>(), &format!("{}prices.\"{}\".", prefix, model), report);
+            }
+        }
+    }
+    if let Some(limits) = root.get("rate_limits").and_then(|v| v.as_table()) {
+        for (provider, limit) in limits {
+            if let Some(limit) = limit.as_table() {
+                check_table(limit, fields_of::<RateLimit>(), &format!("{}rate_limits.{}.", prefix, provider), report);
+            }
+        }
+    }
+}
+
+pub fn unknown_keys(value: &toml::Value) -> Vec<String> {
+    let mut report = Vec::new();
+    let Some(root) = value.as_table() else {
+        return report;
+    };
+    check_config(root, "", &mut report);
+    if let Some(profiles) = root.get("profiles").and_then(|v| v.as_table()) {
+        for (name, profile) in profiles {
+            match profile.as_table() {
+                Some(profile) if profile.contains_key("profiles") => {
+                    report.push(format!("profiles.{} can't contain profiles", name))
+                }
+                Some(profile) => check_config(profile, &format!("profiles.{}.", name), &mut report),
+                None => report.push(format!("profiles.{} must be a table", name)),
+            }
+        }
+    }
+    report
+}
+
+// All problems in a config file as one readable message, or the parsed config
+pub fn parse(path: &Path, text: &str) -> Result<Config, String> {
+    let header = format!("Invalid config '{}':", path.display());
+    let value: toml::Value = toml::from_str(text).map_err(|e| format!("{}\n  {}", header, e))?;
+    let unknown = unknown_keys(&value);
+    if !unknown.is_empty() {
+        return Err(format!("{}\n  {}", header, unknown.join("\n  ")));
+    }
+    // Parsed again from the text so type errors keep their line and column
+    toml::from_str(text).map_err(|e| format!("{}\n  {}", header, e))
+}
+
+// `base` with the keys of profile `name` replacing its own: the [profiles.<name>] section first,
+// then profiles/<name>.toml on top
+pub fn apply_profile(base: Config, name: &str, file: &Path) -> Result<Config, String> {
+    let section = base.profiles.as_ref().and_then(|p| p.get(name)).and_then(|v| v.as_table()).cloned();
+    let from_file = match std::fs::read_to_string(file) {
+        Ok(text) => {
+            let header = format!("Invalid profile '{}':", file.display());
+            let value: toml::Value = toml::from_str(&text).map_err(|e| format!("{}\n  {}", header, e))?;
+            let unknown = unknown_keys(&value);
+            if !unknown.is_empty() {
+                return Err(format!("{}\n  {}", header, unknown.join("\n  ")));
+            }
+            value.as_table().cloned()
+        }
+        Err(_) => None,
+    };
+    if section.is_none() && from_file.is_none() {
+        let mut known: Vec<&String> = base.profiles.iter().flat_map(|p| p.keys()).collect();
+        known.sort();
+        let known = known.iter().map(|k| k.as_str()).collect::<Vec<_>>().join(", ");
+        return Err(format!(
+            "Unknown profile '{}': no [profiles.{}] in config.toml and no '{}' (known: {})",
+            name,
+            name,
+            file.display(),
+            if known.is_empty() { "none" } else { &known }
+        ));
+    }
+    let mut merged = toml::Value::try_from(&base).map_err(|e| e.to_string())?;
+    let table = merged.as_table_mut().ok_or("config is not a table")?;
+    for (key, value) in section.into_iter().chain(from_file).flatten() {
+        table.insert(key, value);
+    }
+    merged
+        .try_into()
+        .map_err(|e| format!("Invalid profile '{}':\n  {}", name, e))
+}
@@ -0,0 +1,98 @@
+// dry_run.rs
+// --dry-run: print the request that would go to the provider (every message, options and a token
+// estimate) instead of sending it. confirm_send shows the same listing before a request goes out.
+use crate::i18n;
+use crate::ui::theme;
+use genai::chat::{ChatOptions, ChatRequest, ContentPart, MessageContent};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+fn content_text(content: &MessageContent) -> String {
+    match content {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text(text) => text.clone(),
+                ContentPart::Image { content_type, .. } => format!("[image {}]", content_type),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        MessageContent::ToolCalls(calls) => format!("[{} tool calls]", calls.len()),
+        MessageContent::ToolResponses(responses) => format!("[{} tool responses]", responses.len()),
+    }
+}
+
+// Same estimate as the interactive prompt: about 4 characters per token
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+// Estimated prompt tokens of a whole request
+pub fn estimate_request(chat_req: &ChatRequest) -> usize {
+    let system = chat_req.system.as_deref().map_or(0, estimate_tokens);
+    system + chat_req.messages.iter().map(|m| estimate_tokens(&content_text(&m.content))).sum::<usize>()
+}
+
+pub fn print_request(model: &str, chat_req: &ChatRequest, options: Option<&ChatOptions>) {
+    print(model, chat_req, options, false);
+}
+
+// The request about to be sent, for confirm_send
+pub fn print_preview(model: &str, chat_req: &ChatRequest, options: Option<&ChatOptions>) {
+    print(model, chat_req, options, true);
+}
+
+fn print(model: &str, chat_req: &ChatRequest, options: Option<&ChatOptions>, preview: bool) {
+    let mut messages: Vec<(String, String)> = Vec::new();
+    if let Some(system) = &chat_req.system {
+        messages.push(("system".to_string(), system.clone()));
+    }
+    for message in &chat_req.messages {
+        messages.push((format!("{:?}", message.role).to_lowercase(), content_text(&message.content)));
+    }
+    let tokens: usize = messages.iter().map(|(_, text)| estimate_tokens(text)).sum();
+
+    let title = if preview { "dry_run.preview" } else { "dry_run.heading" };
+    println!("{}", theme::DIM.paint(format!("--- {} ---", i18n::tf(title, &[&model]))));
+    for (i, (role, text)) in messages.iter().enumerate() {
+        println!(
+            "{} {}",
+            theme::HEADING.paint(format!("[{}] {}", i, role)),
+            theme::DIM.paint(format!("({})", i18n::tf("attachments.tokens", &[&estimate_tokens(text)])))
+        );
+        println!("{}\n", text.trim_end());
+    }
+    if let Some(options) = options {
+        let mut set = Vec::new();
+        if let Some(max_tokens) = options.max_tokens {
+            set.push(format!("max_tokens={}", max_tokens));
+        }
+        if let Some(temperature) = options.temperature {
+            set.push(format!("temperature={}", temperature));
+        }
+        if !options.stop_sequences.is_empty() {
+            set.push(format!("stop={:?}", options.stop_sequences));
+        }
+        if !set.is_empty() {
+            println!("{}", theme::DIM.paint(i18n::tf("dry_run.options", &[&set.join(", ")])));
+        }
+    }
+    println!(
+        "{}",
+        theme::DIM.paint(format!(
+            "--- {}{} ---",
+            i18n::tf("dry_run.total", &[&messages.len(), &tokens]),
+            if preview { "" } else { i18n::t("dry_run.not_sent") }
+        ))
+    );
+}
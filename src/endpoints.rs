@@ -0,0 +1,239 @@
+// endpoints.rs
+use crate::bedrock;
+use crate::cli::QueryError;
+use crate::dry_run;
+use crate::local;
+use crate::offline;
+use crate::rate_limit;
+use crate::trace;
+use crate::config::{Config, EndpointConfig};
+use futures::future::LocalBoxFuture;
+use futures::StreamExt;
+use genai::adapter::AdapterKind;
+use genai::chat::{ChatOptions, ChatRequest, ChatResponse, ChatStreamEvent, Usage};
+use genai::resolver::{AuthData, Endpoint};
+use genai::{Client, ModelIden, ServiceTarget};
+use std::sync::OnceLock;
+
+// Must match the version genai's Anthropic adapter sends
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+static ENDPOINTS: OnceLock<Vec<EndpointConfig>> = OnceLock::new();
+static HTTP: OnceLock<reqwest::Client> = OnceLock::new();
+
+// Register `[[endpoints]]` from config; call once before building the client
+pub fn init(config: &Config) {
+    let _ = ENDPOINTS.set(config.endpoints.clone().unwrap_or_default());
+}
+
+// The client built from `proxy_url` / `ca_cert` / --no-proxy; call once at startup
+pub fn set_http_client(http: reqwest::Client) {
+    let _ = HTTP.set(http);
+}
+
+// For requests made outside genai (embeddings, Bedrock, sync), so they go through the same proxy
+pub fn http_client() -> reqwest::Client {
+    HTTP.get().cloned().unwrap_or_default()
+}
+
+// Endpoint for a `<name>/<model>` model name, with the model part
+fn lookup(model_name: &str) -> Option<(&'static EndpointConfig, &str)> {
+    let (prefix, model) = model_name.split_once('/')?;
+    let endpoint = ENDPOINTS.get()?.iter().find(|e| e.name == prefix)?;
+    Some((endpoint, model))
+}
+
+// `[[endpoints]]` from config
+pub fn configured() -> &'static [EndpointConfig] {
+    ENDPOINTS.get().map_or(&[], Vec::as_slice)
+}
+
+// GET <url>/models with the endpoint's key and headers: a request that costs no tokens but still
+// shows whether the endpoint is up and accepts the key
+pub fn models_request(http: &reqwest::Client, endpoint: &EndpointConfig) -> reqwest::RequestBuilder {
+    let api_key = endpoint
+        .api_key_env
+        .as_deref()
+        .and_then(|env| std::env::var(env).ok())
+        .unwrap_or_default();
+    let url = format!("{}/models", endpoint.url.trim_end_matches('/'));
+    let mut request = match endpoint.adapter.as_deref() {
+        Some("anthropic") => http.get(url).header("x-api-key", api_key).header("anthropic-version", ANTHROPIC_VERSION),
+        _ => http.get(url).bearer_auth(api_key),
+    };
+    for (name, value) in endpoint.headers.iter().flatten() {
+        request = request.header(name, value);
+    }
+    request
+}
+
+fn has_headers(endpoint: &EndpointConfig) -> bool {
+    endpoint.headers.as_ref().is_some_and(|h| !h.is_empty())
+}
+
+// Used by the client's ServiceTargetResolver
+pub fn resolve(target: &ServiceTarget) -> Option<ServiceTarget> {
+    let (endpoint, model) = lookup(&target.model.model_name)?;
+    let kind = match endpoint.adapter.as_deref() {
+        Some("anthropic") => AdapterKind::Anthropic,
+        _ => AdapterKind::OpenAI,
+    };
+    let base_url = if endpoint.url.ends_with('/') {
+        endpoint.url.clone()
+    } else {
+        format!("{}/", endpoint.url)
+    };
+    let api_key = endpoint
+        .api_key_env
+        .as_deref()
+        .and_then(|env| std::env::var(env).ok())
+        .unwrap_or_default();
+
+    // genai only lets a resolver add headers by overriding url and headers together (honored
+    // for streaming requests), so the auth headers the adapter would send are rebuilt here
+    let auth = if has_headers(endpoint) {
+        let (url, mut headers) = match kind {
+            AdapterKind::Anthropic => (
+                format!("{}messages", base_url),
+                vec![
+                    ("x-api-key".to_string(), api_key),
+                    ("anthropic-version".to_string(), ANTHROPIC_VERSION.to_string()),
+                ],
+            ),
+            _ => (
+                format!("{}chat/completions", base_url),
+                vec![("Authorization".to_string(), format!("Bearer {}", api_key))],
+            ),
+        };
+        headers.extend(endpoint.headers.iter().flatten().map(|(k, v)| (k.clone(), v.clone())));
+        tracing::debug!(endpoint = %endpoint.name, %url, headers = %trace::redact_headers(&headers), "resolver: endpoint with headers");
+        AuthData::RequestOverride { url, headers }
+    } else {
+        tracing::debug!(endpoint = %endpoint.name, url = %base_url, adapter = ?kind, model, "resolver: endpoint");
+        AuthData::from_single(api_key)
+    };
+    if let Some(env) = endpoint.api_key_env.as_deref().filter(|env| std::env::var(env).is_err()) {
+        tracing::debug!(endpoint = %endpoint.name, env, "api_key_env is not set; sending an empty key");
+    }
+
+    Some(ServiceTarget {
+        endpoint: Endpoint::from_owned(base_url),
+        auth,
+        model: ModelIden::new(kind, model),
+    })
+}
+
+// Start whatever a model needs before genai can reach it (local llama-server)
+pub async fn prepare(model: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if model.starts_with(local::MODEL_PREFIX) {
+        local::ensure_server(model).await?;
+    }
+    Ok(())
+}
+
+// The provider a model's requests count against for [rate_limits]: the [[endpoints]] name,
+// "bedrock", "local", or genai's adapter for the model ("openai", "gemini", ...)
+pub fn provider(model: &str) -> String {
+    if model.starts_with(bedrock::MODEL_PREFIX) {
+        return "bedrock".to_string();
+    }
+    if model.starts_with(local::MODEL_PREFIX) {
+        return "local".to_string();
+    }
+    if let Some((endpoint, _)) = lookup(model) {
+        return endpoint.name.clone();
+    }
+    AdapterKind::from_model(model).map_or_else(|_| model.to_string(), |kind| kind.as_lower_str().to_string())
+}
+
+// Models that genai can stream itself (Bedrock goes through its own client)
+pub fn supports_stream(model: &str) -> bool {
+    !model.starts_with(bedrock::MODEL_PREFIX)
+}
+
+/// The provider call behind a ChatSession; `Client` goes through `exec_chat` below, tests
+/// answer with canned responses.
+pub trait ChatBackend {
+    fn chat<'a>(
+        &'a self,
+        model: &'a str,
+        chat_req: ChatRequest,
+        options: Option<&'a ChatOptions>,
+    ) -> LocalBoxFuture<'a, Result<ChatResponse, Box<dyn std::error::Error>>>;
+}
+
+impl ChatBackend for Client {
+    fn chat<'a>(
+        &'a self,
+        model: &'a str,
+        chat_req: ChatRequest,
+        options: Option<&'a ChatOptions>,
+    ) -> LocalBoxFuture<'a, Result<ChatResponse, Box<dyn std::error::Error>>> {
+        Box::pin(exec_chat(self, model, chat_req, options))
+    }
+}
+
+// Drop-in for `client.exec_chat`: Bedrock models use the Converse API, endpoints with extra
+// headers are sent as a stream (the only request type that carries them) and collected.
+pub async fn exec_chat(
+    client: &Client,
+    model: &str,
+    chat_req: ChatRequest,
+    options: Option<&ChatOptions>,
+) -> Result<ChatResponse, Box<dyn std::error::Error>> {
+    if dry_run::is_enabled() {
+        dry_run::print_request(model, &chat_req, options);
+        return Err(QueryError::DryRun.into());
+    }
+    tracing::debug!(model, messages = chat_req.messages.len(), "chat request");
+    let permit = rate_limit::acquire(model, &chat_req).await;
+    let chat_res = send(client, model, chat_req, options).await?;
+    if let Some(permit) = permit {
+        permit.settle(&chat_res.usage);
+    }
+    Ok(chat_res)
+}
+
+// The call itself, once through the rate limit
+async fn send(
+    client: &Client,
+    model: &str,
+    chat_req: ChatRequest,
+    options: Option<&ChatOptions>,
+) -> Result<ChatResponse, Box<dyn std::error::Error>> {
+    if model.starts_with(bedrock::MODEL_PREFIX) {
+        tracing::debug!(model, "via Bedrock Converse");
+        if offline::is_enabled() {
+            return Err(offline::refuse_model(model, "https://bedrock-runtime.amazonaws.com").into());
+        }
+        return bedrock::exec_chat(model, chat_req, options).await;
+    }
+    prepare(model).await?;
+    if !lookup(model).is_some_and(|(endpoint, _)| has_headers(endpoint)) {
+        return Ok(client.exec_chat(model, chat_req, options).await?);
+    }
+    tracing::debug!(model, "endpoint has extra headers: sending as a stream and collecting it");
+    let options = options
+        .cloned()
+        .unwrap_or_default()
+        .with_capture_content(true)
+        .with_capture_usage(true)
+        .with_capture_reasoning_content(true);
+    let mut res = client.exec_chat_stream(model, chat_req, Some(&options)).await?;
+    let mut end = None;
+    while let Some(event) = res.stream.next().await {
+        let event = event?;
+        tracing::trace!(?event, "stream event");
+        if let ChatStreamEvent::End(stream_end) = event {
+            end = Some(stream_end);
+        }
+    }
+    let end = end.unwrap_or_default();
+    Ok(ChatResponse {
+        content: end.captured_content,
+        reasoning_content: end.captured_reasoning_content,
+        model_iden: res.model_iden.clone(),
+        provider_model_iden: res.model_iden,
+        usage: end.captured_usage.unwrap_or_else(Usage::default),
+    })
+}
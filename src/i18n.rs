@@ -0,0 +1,492 @@
+// i18n.rs
+// UI strings (help, status, usage and error messages, the error label) by `language` in config: "en"
+// (default) or "zh-TW". Without the key, LANG/LC_ALL pick zh-TW for zh_TW and zh_Hant locales.
+// Strings missing from a locale fall back to English; `{}` in a string is filled by `tf`.
+use crate::config::Config;
+use crate::ui::theme;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+pub const LANGUAGES: &[&str] = &["en", "zh-TW"];
+
+const EN: &[(&str, &str)] = &[
+    ("error", "Error"),
+    ("unknown_command", "Unknown command: {}"),
+    ("history_cleared", "Conversation history cleared."),
+    ("session_saved", "Session saved to '{}'"),
+    ("session_loaded", "Session loaded from '{}'"),
+    ("not_saved", "Not saved."),
+    ("no_sessions", "No saved sessions found."),
+    ("nothing_to_rate", "Nothing to rate yet."),
+    ("model_set", "Model set to: {}"),
+    ("title_set", "Session title set to:"),
+    ("status.heading", "--- Current settings ---"),
+    ("status.model", "Model"),
+    ("status.role", "Role"),
+    ("status.system_prompt", "System prompt"),
+    ("status.reasoning", "Reasoning"),
+    ("status.shown", "shown"),
+    ("status.hidden", "hidden"),
+    ("status.stop_sequences", "Stop sequences"),
+    ("status.max_tokens", "Max tokens"),
+    ("status.stream", "Stream mode"),
+    ("status.enabled", "enabled"),
+    ("status.disabled", "disabled"),
+    ("status.title", "Title"),
+    ("status.answered_by", "Answered by"),
+    ("help.heading", "Available commands:"),
+    ("help.plugins", "Plugins:"),
+    ("help.quit", "Exit interactive mode"),
+    ("help.system", "Change system prompt (e.g., /system You are a coding assistant)"),
+    ("help.system_reset", "Restore the default system prompt"),
+    ("help.status", "Show current model and title ..."),
+    ("help.ss", "toggle stream mode"),
+    ("help.preview", "Show each request before it is sent and wait for Enter (e edits, c cancels)"),
+    ("help.speed", "Typing speed per char (raw, line, token, default)"),
+    ("help.think", "Show or hide reasoning traces (/think prints the last one)"),
+    ("help.stopseq", "Stop generation at <s> (rm <s>, clear; \\n for newline)"),
+    ("help.maxtokens", "Limit the response length (off for the model default)"),
+    ("help.continue", "Ask for the rest of an answer cut off at the token limit"),
+    ("help.diff", "Word diff of the last two answers"),
+    ("help.rate", "Rate the last answer (`llm usage ratings` for averages per model)"),
+    ("help.memory", "Long-term memory: list, add <fact>, rm <id> (memory = true)"),
+    ("help.snippet", "Send a saved prompt; save <name> keeps the last one, rm <name> deletes"),
+    ("help.cls", "Clear the screen"),
+    ("help.clear", "Clear conversation history (keeps the system prompt)"),
+    ("help.compare", "Ask two models, vote for the better answer (stats)"),
+    ("help.translate", "Translate the last answer (ja, zh-TW, fr, ...)"),
+    ("help.ctx", "Attach the project context (saved by `llm ctx`, or built for the globs)"),
+    ("help.var", "Set a variable used as {{name}} in messages (list, rm <name>)"),
+    ("help.plugin_list", "List plugin commands; reload reads the manifests again"),
+    ("help.attach", "Pin a file: its current content goes with every question"),
+    ("help.detach", "Unpin a file (name or number), or all of them"),
+    ("help.attachments", "List pinned files and their token cost"),
+    ("help.mic", "Record audio use the transcription as a query"),
+    ("help.dictate", "Live dictation, Enter sends the transcript, Esc cancels"),
+    ("help.file", "Send a file, optionally with a question about it"),
+    ("help.multi_line", "Toggle multi-line mode (or paste / Alt-Enter for new lines)"),
+    ("help.title", "ai generate title"),
+    ("help.save", "Save the session (default: title); --force overwrites without asking"),
+    ("help.log", "Append every prompt and answer to a markdown transcript (/log off)"),
+    ("help.mirror", "Write answers to a file as they stream (--append, off)"),
+    ("help.load", "Load a session (no name: browse with search and preview, --list: plain list)"),
+    ("help.import", "Import a ChatGPT export, OpenAI messages JSON or markdown transcript"),
+    ("help.word", "Add word to vocabulary (rm <w>, list, import <file>, seed)"),
+    ("help.help", "Show this help message, or usage and examples for one command"),
+    ("warning", "Warning"),
+    ("help.none", "No help for '{}'. /help lists the commands."),
+    ("help.options", "Options:"),
+    ("help.examples", "Examples:"),
+    ("title.mode", "Interactive Mode"),
+    ("title.hint", "(type 'q' to quit, '/help' for help)"),
+    ("title.role", "role: {}"),
+    ("title.accessible", "Interactive mode. Model: {}. Streaming {}."),
+    ("announce.response", "Response from {}:"),
+    ("announce.end", "End of response."),
+    ("spinner.waiting", "Waiting for {}"),
+    ("spinner.continuing", "Continuing with {}"),
+    ("spinner.translating", "Translating with {}"),
+    ("spinner.memory", "Updating memory"),
+    ("tool.calling", "Calling tool {} with {}"),
+    ("tool.done", "Tool {} done"),
+    ("tool.failed", "Tool {} failed: {}"),
+    ("dry_run.heading", "dry run: {}"),
+    ("dry_run.preview", "about to send to {}"),
+    ("dry_run.options", "options: {}"),
+    ("dry_run.total", "{} messages, ~{} tokens"),
+    ("dry_run.not_sent", ", not sent"),
+    ("history.none", "No previous history found at '{}'"),
+    ("multi_line.on", "Entering multi-line mode. Type ':::' to finish."),
+    ("repeat.nothing", "No previous input to repeat."),
+    ("quit.eof", "CTRL-D Quitted"),
+    ("system.reset", "System prompt reset to default."),
+    ("system.set", "System prompt set to: {}"),
+    ("system.roles", "Predefined roles:"),
+    ("model.list", "Available models:"),
+    ("word.list", "Wordlist ({} words):"),
+    ("word.added", "Word '{}' added to wordlist."),
+    ("word.exists", "Word '{}' already in wordlist."),
+    ("word.removed", "Word '{}' removed from wordlist."),
+    ("word.missing", "Word '{}' not in wordlist."),
+    ("word.imported", "Imported {} new words from '{}'."),
+    ("word.seeded", "Seeded {} new words from session history."),
+    ("save.exists", "Session '{}' already exists. [o]verwrite, save as '{}' (default), or [c]ancel? "),
+    ("save.rotated", "Removed {} old sessions (max_sessions = {})"),
+    ("load.list", "Saved sessions:"),
+    ("load.modified", "Last Modified: {}"),
+    ("import.done", "Imported {} messages from '{}' ({})"),
+    ("import.empty", "No messages found in '{}'."),
+    ("import.untitled", "untitled"),
+    ("log.on", "Transcript logging to '{}'"),
+    ("log.off", "Transcript logging stopped."),
+    ("compare.incomplete", "Not every model answered; no vote recorded."),
+    ("compare.vote", "Better answer? [1/2/s(kip)]: "),
+    ("compare.kept", "Kept the answer from {}."),
+    ("compare.skipped", "Vote skipped; neither answer was added to the conversation."),
+    ("compare.no_votes", "No votes in '{}' yet."),
+    ("compare.tally", "{} wins / {} votes ({}%)"),
+    ("translate.nothing", "No answer to translate yet."),
+    ("ctx.attached", "Project context attached ({} bytes)."),
+    ("mirror.on", "Mirroring answers to '{}'"),
+    ("mirror.append", " (append)"),
+    ("mirror.off", "Mirroring stopped."),
+    ("mic.canceled", "Recording canceled."),
+    ("dictate.canceled", "Dictation canceled."),
+    ("think.none", "No reasoning content in the last answer."),
+    ("stopseq.cleared", "Stop sequences cleared."),
+    ("memory.off", "Long-term memory is off; set `memory = true` in config."),
+    ("memory.none", "No memories yet."),
+    ("memory.recalled", "{} of them in this conversation's system prompt."),
+    ("memory.added", "Remembered #{}: {}"),
+    ("memory.known", "Already remembered."),
+    ("memory.removed", "Forgot #{}."),
+    ("memory.missing", "No memory #{}."),
+    ("memory.learned", "Remembered {} new fact(s) (/memory list)"),
+    ("var.removed", "Removed {}."),
+    ("var.missing", "No variable '{}'."),
+    ("var.unknown", "no /var {} set; sent as typed"),
+    ("attach.done", "Attached '{}'; it is sent with every question until /detach."),
+    ("detach.done", "Detached {}."),
+    ("detach.none", "Nothing is attached."),
+    ("detach.missing", "'{}' is not attached."),
+    ("attachments.tokens", "~{} tokens"),
+    ("attachments.unreadable", "unreadable: {}"),
+    ("attachments.changed", "changed, sent again with the next question"),
+    ("attachments.total", "~{} tokens with every question"),
+    ("rate.done", "Rated the answer from {} {}/5."),
+    ("diff.unknown_model", "unknown model"),
+    ("diff.counts", "({} words removed, {} added)"),
+    ("continue.nothing", "Nothing to continue."),
+    ("continue.cut_off", "(cut off at the token limit, /continue for the rest)"),
+    ("maxtokens.set", "Max tokens set to: {}"),
+    ("maxtokens.default", "model default"),
+    ("speed.set", "Stream speed: {}"),
+    ("preview.on", "Preview ON: every request is shown first; Enter sends it, e edits the message, c cancels."),
+    ("preview.off", "Preview OFF: questions are sent right away."),
+    ("plugins.loaded", "{} plugins loaded."),
+    ("plugins.none", "No plugins in {}"),
+    ("plugins.no_output", "Plugin '{}' produced no output."),
+    ("snippet.none", "No snippets yet; /snippet save <name> keeps the last prompt."),
+    ("snippet.nothing", "Nothing to save as '{}' yet: send a prompt first."),
+    ("snippet.saved", "Snippet '{}' saved."),
+    ("snippet.replaced", "Snippet '{}' replaced."),
+    ("snippet.removed", "Snippet '{}' removed."),
+    ("snippet.missing", "No snippet '{}'."),
+    ("snippet.unknown", "No snippet '{}' (/snippet lists them)."),
+    ("usage.word", "Usage: /word <new_word> | rm <word> | list | import <file> | seed"),
+    ("usage.import", "Usage: /import <file> [index]  (ChatGPT export, OpenAI messages JSON, or markdown transcript)"),
+    ("usage.log", "Usage: /log on <file> | /log off"),
+    ("usage.log_off", "Transcript logging is off. Usage: /log on <file> | /log off"),
+    ("usage.compare", "Usage: /compare <model_a> <model_b> <prompt> | stats"),
+    ("usage.translate", "Usage: /translate <lang> (e.g. ja, zh-TW, fr)"),
+    ("usage.ctx", "Usage: /ctx load [globs...] (e.g. /ctx load 'src/**/*.rs')"),
+    ("usage.mirror", "Usage: /mirror [--append] <path> | /mirror off"),
+    ("usage.mirror_off", "Mirroring is off. Usage: /mirror [--append] <path> | /mirror off"),
+    ("usage.think", "Usage: /think show | hide"),
+    ("usage.stopseq", "Usage: /stopseq add <s> | rm <s> | clear"),
+    ("usage.stopseq_none", "No stop sequences. Usage: /stopseq add <s> | rm <s> | clear"),
+    ("usage.memory", "Usage: /memory list | add <fact> | rm <id>"),
+    ("usage.memory_rm", "Usage: /memory rm <id>"),
+    ("usage.var", "Usage: /var set <name> <value> | rm <name> | list"),
+    ("usage.var_set", "Usage: /var set <name> <value> (names: letters, digits, _ and -)"),
+    ("usage.var_none", "No variables. Usage: /var set <name> <value>, then {{name}} in a message"),
+    ("usage.attach", "Usage: /attach <file>"),
+    ("usage.attach_none", "Nothing is attached. Usage: /attach <file>"),
+    ("usage.rate", "Usage: /rate 1-5 [comment]"),
+    ("usage.maxtokens", "Usage: /maxtokens <n> | off"),
+    ("usage.speed", "Usage: /speed raw | line | token | default | <ms per char>"),
+    ("usage.preview", "Usage: /preview [on | off]"),
+    ("usage.snippet", "Usage: /snippet [save <name> | rm <name> | <name>]"),
+    ("usage.file", "Usage: .file <filename> [question]"),
+    ("error.file_missing", "File '{}' does not exist."),
+    ("error.file_empty", "File '{}' is empty or contains only whitespace."),
+    ("error.read", "Failed to read {}: {}"),
+    ("error.read_file", "Failed to read file '{}': {}"),
+    ("error.write", "Failed to write {}: {}"),
+    ("error.remove", "Failed to remove {}: {}"),
+    ("error.open", "Failed to open '{}': {}"),
+    ("error.run", "Failed to run {}: {}"),
+    ("error.channel", "Failed to pass the file content on: {}"),
+    ("error.save", "Failed to save session: {}"),
+    ("error.rotate", "Failed to rotate sessions: {}"),
+    ("error.import", "Import failed: {}"),
+    ("error.word_import", "Failed to import '{}': {}"),
+    ("error.transcript_open", "Failed to open transcript '{}': {}"),
+    ("error.mirror_write", "Failed to write mirror {}: {}"),
+    ("error.memory_update", "Failed to update memory: {}"),
+    ("error.memory_add", "Failed to add memory: {}"),
+    ("error.memory_remove", "Failed to remove memory: {}"),
+    ("error.title", "Could not generate a title ({}); saving under the date."),
+    ("error.diff_needs_two", "/diff needs two answers in the conversation."),
+    ("error.history_save", "Unknown history_save '{}' (use true, false or \"filtered\"); saving filtered"),
+    ("error.model_key", "Unknown key '{}' in model_keys (use alt-<c>, ctrl-<c> or f1-f12)"),
+    ("error.no_mic_file", "Skip: mic.md does not exist"),
+];
+
+const ZH_TW: &[(&str, &str)] = &[
+    ("error", "錯誤"),
+    ("unknown_command", "未知的指令：{}"),
+    ("history_cleared", "已清除對話紀錄。"),
+    ("session_saved", "工作階段已儲存至「{}」"),
+    ("session_loaded", "已從「{}」載入工作階段"),
+    ("not_saved", "未儲存。"),
+    ("no_sessions", "沒有已儲存的工作階段。"),
+    ("nothing_to_rate", "還沒有可以評分的回答。"),
+    ("model_set", "模型已切換為：{}"),
+    ("title_set", "工作階段標題："),
+    ("status.heading", "--- 目前設定 ---"),
+    ("status.model", "模型"),
+    ("status.role", "角色"),
+    ("status.system_prompt", "系統提示"),
+    ("status.reasoning", "推理過程"),
+    ("status.shown", "顯示"),
+    ("status.hidden", "隱藏"),
+    ("status.stop_sequences", "停止序列"),
+    ("status.max_tokens", "最大 token 數"),
+    ("status.stream", "串流模式"),
+    ("status.enabled", "開啟"),
+    ("status.disabled", "關閉"),
+    ("status.title", "標題"),
+    ("status.answered_by", "回答的模型"),
+    ("help.heading", "可用指令："),
+    ("help.plugins", "外掛："),
+    ("help.quit", "離開互動模式"),
+    ("help.system", "變更系統提示（例如 /system 你是程式設計助理）"),
+    ("help.system_reset", "還原預設的系統提示"),
+    ("help.status", "顯示目前的模型與標題等設定"),
+    ("help.ss", "切換串流模式"),
+    ("help.preview", "送出前先顯示完整的請求並等待 Enter（e 編輯、c 取消）"),
+    ("help.speed", "每個字元的輸出速度（raw、line、token、default）"),
+    ("help.think", "顯示或隱藏推理過程（/think 顯示上一次的內容）"),
+    ("help.stopseq", "遇到 <s> 時停止生成（rm <s>、clear；\\n 代表換行）"),
+    ("help.maxtokens", "限制回答長度（off 使用模型預設值）"),
+    ("help.continue", "取得因 token 上限而中斷的回答的其餘部分"),
+    ("help.diff", "比較最後兩個回答的逐字差異"),
+    ("help.rate", "為上一個回答評分（`llm usage ratings` 顯示各模型平均）"),
+    ("help.memory", "長期記憶：list、add <事實>、rm <id>（memory = true）"),
+    ("help.snippet", "送出已存的提示；save <名稱> 保存上一個提示，rm <名稱> 刪除"),
+    ("help.cls", "清除畫面"),
+    ("help.clear", "清除對話紀錄（保留系統提示）"),
+    ("help.compare", "詢問兩個模型並投票選出較好的回答（stats）"),
+    ("help.translate", "翻譯上一個回答（ja、zh-TW、fr ...）"),
+    ("help.ctx", "附加專案內容（`llm ctx` 儲存的，或依 globs 建立）"),
+    ("help.var", "設定變數，在訊息中以 {{name}} 使用（list、rm <名稱>）"),
+    ("help.plugin_list", "列出外掛指令；reload 重新讀取 manifest"),
+    ("help.attach", "釘選檔案：每次提問都附上它目前的內容"),
+    ("help.detach", "取消釘選檔案（名稱或編號），或全部取消"),
+    ("help.attachments", "列出釘選的檔案與其 token 用量"),
+    ("help.mic", "錄音並以轉錄文字提問"),
+    ("help.dictate", "即時聽寫，Enter 送出，Esc 取消"),
+    ("help.file", "傳送檔案，可附帶問題"),
+    ("help.multi_line", "切換多行模式（也可以直接貼上或用 Alt-Enter 換行）"),
+    ("help.title", "由 AI 產生標題"),
+    ("help.save", "儲存工作階段（預設用標題）；--force 直接覆寫不詢問"),
+    ("help.log", "將每個問題與回答附加到 markdown 紀錄檔（/log off）"),
+    ("help.mirror", "串流時將回答寫入檔案（--append、off）"),
+    ("help.load", "載入工作階段（不給名稱：搜尋與預覽瀏覽，--list：純清單）"),
+    ("help.import", "匯入 ChatGPT 匯出檔、OpenAI 訊息 JSON 或 markdown 紀錄"),
+    ("help.word", "加入詞彙（rm <w>、list、import <檔案>、seed）"),
+    ("help.help", "顯示說明，或單一指令的用法與範例"),
+    ("warning", "警告"),
+    ("help.none", "沒有「{}」的說明。/help 會列出所有指令。"),
+    ("help.options", "選項："),
+    ("help.examples", "範例："),
+    ("title.mode", "互動模式"),
+    ("title.hint", "（輸入 'q' 離開，'/help' 查看說明）"),
+    ("title.role", "角色：{}"),
+    ("title.accessible", "互動模式。模型：{}。串流{}。"),
+    ("announce.response", "{} 的回應："),
+    ("announce.end", "回應結束。"),
+    ("spinner.waiting", "等待 {}"),
+    ("spinner.continuing", "以 {} 繼續"),
+    ("spinner.translating", "以 {} 翻譯"),
+    ("spinner.memory", "更新記憶中"),
+    ("tool.calling", "呼叫工具 {}，參數 {}"),
+    ("tool.done", "工具 {} 完成"),
+    ("tool.failed", "工具 {} 失敗：{}"),
+    ("dry_run.heading", "試執行：{}"),
+    ("dry_run.preview", "即將傳送至 {}"),
+    ("dry_run.options", "選項：{}"),
+    ("dry_run.total", "{} 則訊息，約 {} 個 token"),
+    ("dry_run.not_sent", "，未傳送"),
+    ("history.none", "在「{}」找不到先前的輸入紀錄"),
+    ("multi_line.on", "進入多行模式。輸入 ':::' 結束。"),
+    ("repeat.nothing", "沒有可重複的上一筆輸入。"),
+    ("quit.eof", "已按 CTRL-D 離開"),
+    ("system.reset", "系統提示已恢復為預設值。"),
+    ("system.set", "系統提示已設為：{}"),
+    ("system.roles", "預設角色："),
+    ("model.list", "可用的模型："),
+    ("word.list", "詞彙表（{} 個詞）："),
+    ("word.added", "已將「{}」加入詞彙表。"),
+    ("word.exists", "「{}」已在詞彙表中。"),
+    ("word.removed", "已將「{}」從詞彙表移除。"),
+    ("word.missing", "「{}」不在詞彙表中。"),
+    ("word.imported", "已匯入 {} 個新詞（來自「{}」）。"),
+    ("word.seeded", "已從工作階段紀錄加入 {} 個新詞。"),
+    ("save.exists", "工作階段「{}」已存在。[o]覆寫、另存為「{}」（預設），或 [c]取消？"),
+    ("save.rotated", "已移除 {} 個舊的工作階段（max_sessions = {}）"),
+    ("load.list", "已儲存的工作階段："),
+    ("load.modified", "最後修改：{}"),
+    ("import.done", "已匯入 {} 則訊息，來源「{}」（{}）"),
+    ("import.empty", "在「{}」中找不到訊息。"),
+    ("import.untitled", "未命名"),
+    ("log.on", "對話紀錄寫入「{}」"),
+    ("log.off", "已停止記錄對話。"),
+    ("compare.incomplete", "並非每個模型都有回答；未記錄投票。"),
+    ("compare.vote", "哪個回答較好？[1/2/s(略過)]："),
+    ("compare.kept", "已保留 {} 的回答。"),
+    ("compare.skipped", "已略過投票；兩個回答都未加入對話。"),
+    ("compare.no_votes", "「{}」中還沒有投票。"),
+    ("compare.tally", "{} 勝 / {} 票（{}%）"),
+    ("translate.nothing", "還沒有可翻譯的回答。"),
+    ("ctx.attached", "已附加專案脈絡（{} 位元組）。"),
+    ("mirror.on", "回答同步寫入「{}」"),
+    ("mirror.append", "（附加）"),
+    ("mirror.off", "已停止鏡像輸出。"),
+    ("mic.canceled", "已取消錄音。"),
+    ("dictate.canceled", "已取消聽寫。"),
+    ("think.none", "上一個回答沒有推理內容。"),
+    ("stopseq.cleared", "已清除停止序列。"),
+    ("memory.off", "長期記憶未開啟；請在設定中設定 `memory = true`。"),
+    ("memory.none", "還沒有記憶。"),
+    ("memory.recalled", "其中 {} 則在此對話的系統提示中。"),
+    ("memory.added", "已記住 #{}：{}"),
+    ("memory.known", "已經記住了。"),
+    ("memory.removed", "已忘記 #{}。"),
+    ("memory.missing", "沒有記憶 #{}。"),
+    ("memory.learned", "已記住 {} 則新事實（/memory list）"),
+    ("var.removed", "已移除 {}。"),
+    ("var.missing", "沒有變數「{}」。"),
+    ("var.unknown", "未以 /var 設定 {}；依輸入原樣送出"),
+    ("attach.done", "已附加「{}」；在 /detach 之前每個問題都會一併送出。"),
+    ("detach.done", "已取消附加 {}。"),
+    ("detach.none", "沒有附加檔案。"),
+    ("detach.missing", "「{}」未附加。"),
+    ("attachments.tokens", "約 {} 個 token"),
+    ("attachments.unreadable", "無法讀取：{}"),
+    ("attachments.changed", "已變更，將隨下一個問題重新送出"),
+    ("attachments.total", "每個問題約 {} 個 token"),
+    ("rate.done", "已為 {} 的回答評分 {}/5。"),
+    ("diff.unknown_model", "未知模型"),
+    ("diff.counts", "（移除 {} 個詞，新增 {} 個）"),
+    ("continue.nothing", "沒有可繼續的內容。"),
+    ("continue.cut_off", "（已達 token 上限而截斷，/continue 取得其餘內容）"),
+    ("maxtokens.set", "最大 token 數已設為：{}"),
+    ("maxtokens.default", "模型預設值"),
+    ("speed.set", "串流速度：{}"),
+    ("preview.on", "預覽已開啟：每個請求會先顯示；Enter 送出，e 編輯訊息，c 取消。"),
+    ("preview.off", "預覽已關閉：問題會立即送出。"),
+    ("plugins.loaded", "已載入 {} 個外掛。"),
+    ("plugins.none", "{} 中沒有外掛"),
+    ("plugins.no_output", "外掛「{}」沒有輸出。"),
+    ("snippet.none", "還沒有片段；/snippet save <名稱> 會保存上一個提示。"),
+    ("snippet.nothing", "還沒有可存為「{}」的內容：請先送出一個提示。"),
+    ("snippet.saved", "已儲存片段「{}」。"),
+    ("snippet.replaced", "已取代片段「{}」。"),
+    ("snippet.removed", "已移除片段「{}」。"),
+    ("snippet.missing", "沒有片段「{}」。"),
+    ("snippet.unknown", "沒有片段「{}」（/snippet 會列出所有片段）。"),
+    ("usage.word", "用法：/word <新詞> | rm <詞> | list | import <檔案> | seed"),
+    ("usage.import", "用法：/import <檔案> [索引]（ChatGPT 匯出檔、OpenAI 訊息 JSON 或 markdown 紀錄）"),
+    ("usage.log", "用法：/log on <檔案> | /log off"),
+    ("usage.log_off", "紀錄檔未開啟。用法：/log on <檔案> | /log off"),
+    ("usage.compare", "用法：/compare <模型A> <模型B> <提示> | stats"),
+    ("usage.translate", "用法：/translate <語言>（例如 ja、zh-TW、fr）"),
+    ("usage.ctx", "用法：/ctx load [globs...]（例如 /ctx load 'src/**/*.rs'）"),
+    ("usage.mirror", "用法：/mirror [--append] <路徑> | /mirror off"),
+    ("usage.mirror_off", "鏡像輸出未開啟。用法：/mirror [--append] <路徑> | /mirror off"),
+    ("usage.think", "用法：/think show | hide"),
+    ("usage.stopseq", "用法：/stopseq add <s> | rm <s> | clear"),
+    ("usage.stopseq_none", "沒有停止序列。用法：/stopseq add <s> | rm <s> | clear"),
+    ("usage.memory", "用法：/memory list | add <事實> | rm <id>"),
+    ("usage.memory_rm", "用法：/memory rm <id>"),
+    ("usage.var", "用法：/var set <名稱> <值> | rm <名稱> | list"),
+    ("usage.var_set", "用法：/var set <名稱> <值>（名稱：字母、數字、_ 與 -）"),
+    ("usage.var_none", "沒有變數。用法：/var set <名稱> <值>，再於訊息中使用 {{name}}"),
+    ("usage.attach", "用法：/attach <檔案>"),
+    ("usage.attach_none", "沒有附加檔案。用法：/attach <檔案>"),
+    ("usage.rate", "用法：/rate 1-5 [評語]"),
+    ("usage.maxtokens", "用法：/maxtokens <n> | off"),
+    ("usage.speed", "用法：/speed raw | line | token | default | <每字毫秒數>"),
+    ("usage.preview", "用法：/preview [on | off]"),
+    ("usage.snippet", "用法：/snippet [save <名稱> | rm <名稱> | <名稱>]"),
+    ("usage.file", "用法：.file <檔名> [問題]"),
+    ("error.file_missing", "檔案 '{}' 不存在。"),
+    ("error.file_empty", "檔案 '{}' 是空的或只有空白。"),
+    ("error.read", "無法讀取 {}：{}"),
+    ("error.read_file", "無法讀取檔案 '{}'：{}"),
+    ("error.write", "無法寫入 {}：{}"),
+    ("error.remove", "無法刪除 {}：{}"),
+    ("error.open", "無法開啟 '{}'：{}"),
+    ("error.run", "無法執行 {}：{}"),
+    ("error.channel", "無法傳遞檔案內容：{}"),
+    ("error.save", "無法儲存工作階段：{}"),
+    ("error.rotate", "無法輪替工作階段：{}"),
+    ("error.import", "匯入失敗：{}"),
+    ("error.word_import", "無法匯入 '{}'：{}"),
+    ("error.transcript_open", "無法開啟紀錄檔 '{}'：{}"),
+    ("error.mirror_write", "無法寫入鏡像檔 {}：{}"),
+    ("error.memory_update", "無法更新記憶：{}"),
+    ("error.memory_add", "無法新增記憶：{}"),
+    ("error.memory_remove", "無法刪除記憶：{}"),
+    ("error.title", "無法產生標題（{}）；改用日期儲存。"),
+    ("error.diff_needs_two", "/diff 需要對話中至少有兩個回答。"),
+    ("error.history_save", "未知的 history_save '{}'（可用 true、false 或 \"filtered\"）；改用 filtered"),
+    ("error.model_key", "model_keys 中未知的按鍵 '{}'（可用 alt-<c>、ctrl-<c> 或 f1-f12）"),
+    ("error.no_mic_file", "略過：mic.md 不存在"),
+];
+
+static STRINGS: OnceLock<&'static [(&'static str, &'static str)]> = OnceLock::new();
+
+// `language` from config, else the locale; call once at startup
+pub fn init(config: &Config) -> Result<(), String> {
+    let language = match &config.language {
+        Some(language) => language.clone(),
+        None => ["LC_ALL", "LANG"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+            .filter(|locale| locale.starts_with("zh_TW") || locale.starts_with("zh_Hant"))
+            .map_or_else(|| "en".to_string(), |_| "zh-TW".to_string()),
+    };
+    let strings = match language.as_str() {
+        "en" => EN,
+        "zh-TW" | "zh_TW" => ZH_TW,
+        other => {
+            return Err(format!("Unknown language '{}' (available: {})", other, LANGUAGES.join(", ")));
+        }
+    };
+    let _ = STRINGS.set(strings);
+    Ok(())
+}
+
+fn lookup(strings: &[(&str, &'static str)], key: &str) -> Option<&'static str> {
+    strings.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+}
+
+// The string for `key` in the chosen language; English until init runs (tests), the key itself
+// when no locale has it
+pub fn t(key: &'static str) -> &'static str {
+    let strings = STRINGS.get().copied().unwrap_or(EN);
+    lookup(strings, key).or_else(|| lookup(EN, key)).unwrap_or(key)
+}
+
+// `t(key)` with each `{}` replaced by the next argument
+pub fn tf(key: &'static str, args: &[&dyn Display]) -> String {
+    let mut pieces = t(key).split("{}");
+    let mut text = pieces.next().unwrap_or_default().to_string();
+    let mut args = args.iter();
+    for piece in pieces {
+        match args.next() {
+            Some(arg) => text.push_str(&arg.to_string()),
+            None => text.push_str("{}"),
+        }
+        text.push_str(piece);
+    }
+    text
+}
+
+// "Error:" in red, in the chosen language
+pub fn error_label() -> String {
+    theme::BAD.paint(format!("{}:", t("error")))
+}
@@ -0,0 +1,198 @@
+// import.rs
+use genai::chat::{ChatMessage, ChatRole};
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// A conversation converted from another client's export.
+pub struct ImportedConversation {
+    pub messages: Vec<ChatMessage>,
+    pub title: Option<String>,
+}
+
+// Detect the format from the extension/content and convert it to chat messages.
+// `index` selects a conversation when a ChatGPT export contains several.
+pub fn import_conversation(
+    path: &Path,
+    index: usize,
+) -> Result<ImportedConversation, Box<dyn std::error::Error>> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read '{}': {}", path.display(), e))?;
+    let stem_title = path.file_stem().and_then(|s| s.to_str()).map(String::from);
+
+    let is_markdown = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md") | Some("markdown") | Some("txt")
+    );
+    if is_markdown {
+        return Ok(ImportedConversation {
+            messages: parse_markdown_transcript(&data),
+            title: stem_title,
+        });
+    }
+
+    let json: Value = serde_json::from_str(&data)
+        .map_err(|e| anyhow::anyhow!("'{}' is neither markdown nor valid JSON: {}", path.display(), e))?;
+    match &json {
+        // ChatGPT export: conversations.json is an array of conversations with a `mapping` tree
+        Value::Array(items) if items.first().is_some_and(|c| c.get("mapping").is_some()) => {
+            let conversation = items.get(index).ok_or_else(|| {
+                anyhow::anyhow!("Conversation index {} out of range (export has {})", index, items.len())
+            })?;
+            Ok(parse_chatgpt_conversation(conversation))
+        }
+        Value::Object(obj) if obj.contains_key("mapping") => Ok(parse_chatgpt_conversation(&json)),
+        // OpenAI messages array, bare or wrapped in {"messages": [...]}
+        Value::Array(items) => Ok(ImportedConversation {
+            messages: parse_openai_messages(items),
+            title: stem_title,
+        }),
+        Value::Object(obj) => match obj.get("messages").and_then(|m| m.as_array()) {
+            Some(items) => Ok(ImportedConversation {
+                messages: parse_openai_messages(items),
+                title: obj
+                    .get("title")
+                    .and_then(|t| t.as_str())
+                    .map(String::from)
+                    .or(stem_title),
+            }),
+            None => Err(anyhow::anyhow!("Unrecognized conversation format in '{}'", path.display()).into()),
+        },
+        _ => Err(anyhow::anyhow!("Unrecognized conversation format in '{}'", path.display()).into()),
+    }
+}
+
+fn make_message(role: &str, content: String) -> Option<ChatMessage> {
+    if content.trim().is_empty() {
+        return None;
+    }
+    match role {
+        "system" | "developer" => Some(ChatMessage::system(content)),
+        "user" | "human" => Some(ChatMessage::user(content)),
+        "assistant" | "model" | "ai" => Some(ChatMessage::assistant(content)),
+        _ => None, // tool / function messages are dropped
+    }
+}
+
+// Content may be a plain string or an array of {type: "text", text: "..."} parts
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p.as_str().or_else(|| p.get("text").and_then(|t| t.as_str())))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn parse_openai_messages(items: &[Value]) -> Vec<ChatMessage> {
+    items
+        .iter()
+        .filter_map(|m| {
+            let role = m.get("role")?.as_str()?.to_lowercase();
+            make_message(&role, content_text(m.get("content")?))
+        })
+        .collect()
+}
+
+// Walk from `current_node` up through `parent` links, then reverse into chronological order
+fn parse_chatgpt_conversation(conversation: &Value) -> ImportedConversation {
+    let title = conversation
+        .get("title")
+        .and_then(|t| t.as_str())
+        .map(String::from);
+    let mapping = match conversation.get("mapping").and_then(|m| m.as_object()) {
+        Some(mapping) => mapping,
+        None => return ImportedConversation { messages: Vec::new(), title },
+    };
+
+    let mut node_id = conversation
+        .get("current_node")
+        .and_then(|n| n.as_str())
+        .map(String::from);
+    let mut chain = Vec::new();
+    while let Some(id) = node_id {
+        let node = match mapping.get(&id) {
+            Some(node) => node,
+            None => break,
+        };
+        if let Some(message) = node.get("message").filter(|m| !m.is_null()) {
+            let role = message
+                .pointer("/author/role")
+                .and_then(|r| r.as_str())
+                .unwrap_or("")
+                .to_lowercase();
+            let text = message
+                .pointer("/content/parts")
+                .map(content_text)
+                .unwrap_or_default();
+            if let Some(msg) = make_message(&role, text) {
+                chain.push(msg);
+            }
+        }
+        node_id = node.get("parent").and_then(|p| p.as_str()).map(String::from);
+    }
+    chain.reverse();
+    ImportedConversation { messages: chain, title }
+}
+
+// Recognizes "## User", "**Assistant:**", "User: text" style turn headers
+fn parse_markdown_transcript(data: &str) -> Vec<ChatMessage> {
+    // Either a heading that only names the role, or a role label followed by a colon
+    let header_re = Regex::new(
+        r"(?i)^\s*(?:#+\s*\**(user|you|assistant|ai|system)\**\s*:?\s*$|\**(user|you|assistant|ai|system)\**\s*:\s*\**\s*(.*)$)",
+    )
+    .unwrap();
+    let mut messages = Vec::new();
+    let mut role: Option<String> = None;
+    let mut buffer: Vec<&str> = Vec::new();
+
+    let mut flush = |role: &Option<String>, buffer: &mut Vec<&str>| {
+        if let Some(r) = role {
+            if let Some(msg) = make_message(r, buffer.join("\n").trim().to_string()) {
+                messages.push(msg);
+            }
+        }
+        buffer.clear();
+    };
+
+    let mut in_code = false;
+    for line in data.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        let header = if in_code { None } else { header_re.captures(line) };
+        match header {
+            Some(caps) => {
+                flush(&role, &mut buffer);
+                let r = caps
+                    .get(1)
+                    .or_else(|| caps.get(2))
+                    .map(|m| m.as_str().to_lowercase())
+                    .unwrap_or_default();
+                role = Some(match r.as_str() {
+                    "you" => "user".to_string(),
+                    "ai" => "assistant".to_string(),
+                    _ => r,
+                });
+                if let Some(rest) = caps.get(3).filter(|m| !m.as_str().is_empty()) {
+                    buffer.push(rest.as_str());
+                }
+            }
+            None => buffer.push(line),
+        }
+    }
+    flush(&role, &mut buffer);
+    messages
+}
+
+// Ensure the conversation starts with a system message, as ChatSession expects messages[0] to be one
+pub fn with_system_prompt(mut messages: Vec<ChatMessage>, default_prompt: &str) -> Vec<ChatMessage> {
+    if !matches!(messages.first().map(|m| &m.role), Some(ChatRole::System)) {
+        messages.insert(0, ChatMessage::system(default_prompt));
+    }
+    messages
+}
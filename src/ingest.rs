@@ -0,0 +1,131 @@
+// ingest.rs
+// Reading files and directories the user hands to the model (-f, .file, ctx globs): binary files
+// are refused, very large ones warned about, and directory walks honor .gitignore.
+use crate::ui::theme;
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Warn above this size; the request will be slow and expensive
+pub const LARGE_FILE_BYTES: u64 = 512 * 1024;
+// How much of a file is sniffed for binary content
+const SNIFF_BYTES: usize = 8192;
+
+// --include-hidden: walks keep dot files and directories
+static INCLUDE_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+pub fn set_include_hidden(include: bool) {
+    INCLUDE_HIDDEN.store(include, Ordering::Relaxed);
+}
+
+fn include_hidden() -> bool {
+    INCLUDE_HIDDEN.load(Ordering::Relaxed)
+}
+
+// NUL bytes or invalid UTF-8 in the first block (a multi-byte char cut at the end is fine)
+pub fn is_binary(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => false,
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+// Refuse binary files and warn about large ones before they are read
+pub fn check_file(path: &Path) -> Result<(), String> {
+    let metadata = std::fs::metadata(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    std::fs::File::open(path)
+        .and_then(|f| f.take(SNIFF_BYTES as u64).read_to_end(&mut head))
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    if is_binary(&head) {
+        return Err(format!("'{}' looks like a binary file, skipped", path.display()));
+    }
+    if metadata.len() > LARGE_FILE_BYTES {
+        eprintln!(
+            "{} '{}' is large ({} KB); the request may be slow or exceed the model's context",
+            theme::VALUE.paint_err("Warning:"),
+            path.display(),
+            metadata.len() / 1024
+        );
+    }
+    Ok(())
+}
+
+pub fn read_file(path: &Path) -> Result<String, String> {
+    check_file(path)?;
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))
+}
+
+// Files under `dir` (relative to it, '/'-separated), optionally limited to globs. .gitignore,
+// .ignore and the global git excludes apply, in a git repository or not. Globs follow .gitignore
+// syntax: `*.rs` matches at any depth, `src/**/*.rs` is relative to `dir`.
+pub fn list_files(dir: &Path, globs: &[String]) -> Result<Vec<String>, String> {
+    let mut builder = WalkBuilder::new(dir);
+    builder.hidden(!include_hidden()).require_git(false);
+    if !globs.is_empty() {
+        let mut overrides = OverrideBuilder::new(dir);
+        for glob in globs {
+            overrides.add(glob).map_err(|e| format!("Invalid glob '{}': {}", glob, e))?;
+        }
+        builder.overrides(overrides.build().map_err(|e| e.to_string())?);
+    }
+    let mut files: Vec<String> = builder
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(dir).ok()?;
+            Some(relative.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+// A directory as one text: each text file fenced under its path, binaries listed as skipped
+pub fn read_dir(dir: &Path) -> Result<String, String> {
+    let mut out = String::new();
+    let mut skipped = Vec::new();
+    for file in list_files(dir, &[])? {
+        match read_file(&dir.join(&file)) {
+            Ok(content) => out.push_str(&fenced(&file, &content)),
+            Err(_) => skipped.push(file),
+        }
+    }
+    if out.is_empty() {
+        return Err(format!("No text files in '{}'", dir.display()));
+    }
+    if !skipped.is_empty() {
+        out.push_str(&format!("Skipped (binary or unreadable): {}\n", skipped.join(", ")));
+    }
+    if out.len() as u64 > LARGE_FILE_BYTES {
+        eprintln!(
+            "{} '{}' adds up to {} KB",
+            theme::VALUE.paint_err("Warning:"),
+            dir.display(),
+            out.len() / 1024
+        );
+    }
+    Ok(out)
+}
+
+// A file's text, or a whole directory via read_dir
+pub fn read_path(path: &Path) -> Result<String, String> {
+    if path.is_dir() {
+        read_dir(path)
+    } else {
+        read_file(path)
+    }
+}
+
+// One file as a markdown section
+pub fn fenced(path: &str, content: &str) -> String {
+    let lang = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    let fence = if content.contains("```") { "````" } else { "```" };
+    format!("## {}\n{}{}\n{}\n{}\n\n", path, fence, lang, content.trim_end(), fence)
+}
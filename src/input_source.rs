@@ -0,0 +1,121 @@
+// input_source.rs
+// Everything that can turn into a message for the model in interactive mode. The sources only
+// differ in how they are announced and signalled; sending and rendering is shared
+// (see interactive::dispatch_input).
+use crate::output;
+use crate::ui::theme;
+use fs2::FileExt;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+pub enum InputSource {
+    Typed,               // readline, or a line queued while the last answer was rendering
+    MultiLine,           // a ::: block
+    Repeat,              // "." resends the last input
+    Snippet(String),     // /snippet <name>
+    Dictation,           // /dictate
+    File {
+        path: PathBuf,
+        question: Option<String>, // .file <path> <question...>
+    },
+    MicCommand(PathBuf), // jc: mic.md loaded on request
+    MicWatch,            // mic.md changed on disk
+    Plugin(String),      // output of a /<plugin> command
+}
+
+impl InputSource {
+    // Announce the input above the answer
+    pub fn preview(&self, content: &str) {
+        let head = content.lines().take(3).collect::<Vec<_>>().join("\n");
+        let text = match self {
+            InputSource::Typed => return,
+            InputSource::MultiLine => format!("\r{}\n{}", theme::PROMPT.paint("󰭻 Multi-line input:"), content),
+            InputSource::Repeat => format!("\r{}: {}", theme::PROMPT.paint("󰭻 "), content),
+            InputSource::Snippet(name) => format!("\r{}{}: {}", theme::PROMPT.paint("󰭻 "), name, content),
+            InputSource::Dictation => format!("\r{}: {}", theme::PROMPT.paint("󰍬 "), content),
+            InputSource::File { path, question } => {
+                let mut text = format!("{} --- load from {} ---\n{}\n", theme::VALUE.paint("Preview:"), path.display(), head);
+                if let Some(question) = question {
+                    text.push_str(&format!("\r{}: {}\n", theme::PROMPT.paint("󰭻 "), question));
+                }
+                text + theme::GOOD.paint("Machine response:").as_str()
+            }
+            InputSource::MicCommand(path) => format!(
+                "{} --- load from {} ---\n{}\n{}",
+                theme::VALUE.paint("Preview:"),
+                path.display(),
+                head,
+                theme::GOOD.paint("Machine response:")
+            ),
+            InputSource::MicWatch => format!(
+                "{}-- mic.md\n{}\n{}",
+                theme::TOOL.paint(" 󰑉 "),
+                head,
+                theme::GOOD.paint("Response from machine (based on mic.md):")
+            ),
+            InputSource::Plugin(name) => format!("{}-- {}\n{}", theme::TOOL.paint(" 󰐱 "), name, head),
+        };
+        // Plain text in accessible mode
+        println!("{}", output::styled(&text));
+    }
+
+    // What the model is sent for this input's content
+    pub fn message(&self, content: &str) -> String {
+        match self {
+            InputSource::File {
+                path,
+                question: Some(question),
+            } => with_file_context(&path.to_string_lossy(), content, question),
+            _ => content.to_string(),
+        }
+    }
+
+    // Whether "." should resend this input
+    pub fn records_history(&self) -> bool {
+        matches!(
+            self,
+            InputSource::Typed
+                | InputSource::MultiLine
+                | InputSource::Dictation
+                | InputSource::File { .. }
+                | InputSource::Snippet(_)
+        )
+    }
+
+    // The mic.md bridge gets mic routes, the answer file and a JSON ack
+    pub fn is_mic_bridge(&self) -> bool {
+        matches!(self, InputSource::MicWatch)
+    }
+
+    // Collect keys typed during the answer; the watcher fires while readline owns the terminal
+    pub fn takes_type_ahead(&self) -> bool {
+        !matches!(self, InputSource::MicWatch)
+    }
+}
+
+// Read a file shared with external tools (mic.md, .file) under an exclusive lock
+pub fn read_locked(path: &Path) -> std::io::Result<String> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    file.lock_exclusive()?;
+    let content = std::fs::read_to_string(path);
+    if file.unlock().is_err() {
+        eprintln!("Failed to unlock '{}'.", path.display());
+    }
+    content
+}
+
+// A file as fenced context followed by the instruction about it
+pub fn with_file_context(name: &str, content: &str, question: &str) -> String {
+    let lang = Path::new(name).extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    // Keep code fences inside the file from closing the block early
+    let fence = if content.contains("```") { "````" } else { "```" };
+    format!(
+        "{}:\n{}{}\n{}\n{}\n\n{}",
+        name,
+        fence,
+        lang,
+        content.trim_end(),
+        fence,
+        question.trim()
+    )
+}
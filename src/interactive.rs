@@ -1,200 +1,518 @@
 // in src/interactive.rs
-use crate::chat_session::ChatSession;
+use crate::chat_session::{ChatSession, LastExchange};
+use crate::cli::error_message;
+use crate::command::command_help;
 use crate::completion::CommandCompleter;
-use crate::config::get_config_dir;
+use crate::condense;
+use crate::config::get_data_dir;
+use crate::endpoints::ChatBackend;
 use crate::config::get_temp_file_path;
-use fs2::FileExt; // For file locking
-use genai::Client;
+use crate::config::{active_profile, alias_models, load_config, Config, HistorySave};
+use crate::i18n;
+use crate::ingest;
+use crate::input_source::{read_locked, InputSource};
+use crate::line_reader::{parse_key, LineReader};
+use crate::memory::MemoryStore;
+use crate::mic::{dictate_main, MicOptions};
+use crate::plugins::{self, find_plugin, run_plugin};
+use crate::redact;
+use crate::routing::MicRouter;
+use crate::snippets;
 use rustyline::error::ReadlineError;
-use rustyline::Editor;
+use rustyline::{Editor, KeyCode, KeyEvent, Modifiers};
+use std::collections::VecDeque;
 use std::fs;
-use std::fs::OpenOptions;
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::task;
-use tokio::task::spawn_blocking;
 use tokio::time::{sleep, Duration};
 
-use crate::markdown_render::MarkdownRender;
-use crate::sse_event::SseEvent;
+use crate::markdown_render::{current_fence_lang, MarkdownRender};
+use crate::output::{self, render_stream};
+use crate::sse_event::StreamSpeed;
+use crate::transcribe::Transcriber;
+use crate::typeahead::TypeAhead;
+use crate::ui::text_width;
+use crate::ui::theme;
+
+// Continuation requests after one truncated answer when auto_continue is on
+const MAX_AUTO_CONTINUE: usize = 3;
 
 pub fn write_act(act_file_path: &PathBuf) {
     if let Err(e) = fs::write(act_file_path, "busy") {
-        eprintln!("Failed to write to {}: {}", act_file_path.display(), e);
+        eprintln!("{}", i18n::tf("error.write", &[&act_file_path.display(), &e]));
     }
 }
 
 pub fn write_ai_ack(act_file_path: &PathBuf, ai_ack_file_path: &PathBuf) {
     if act_file_path.exists() {
         if let Err(e) = fs::remove_file(act_file_path) {
-            eprintln!("Failed to remove {}: {}", act_file_path.display(), e);
+            eprintln!("{}", i18n::tf("error.remove", &[&act_file_path.display(), &e]));
         }
     }
     if let Err(e) = fs::write(ai_ack_file_path, "OK") {
-        eprintln!("Failed to write to {}: {}", ai_ack_file_path.display(), e);
+        eprintln!("{}", i18n::tf("error.write", &[&ai_ack_file_path.display(), &e]));
     }
 }
 
-fn powerline_section_title(
-    model: &str,
-    stream: bool,
-    custom_message: Option<&str>,
-    custom_color: Option<&str>,
-) {
+// JSON ack for the mic.md bridge, replacing the bare "OK"
+pub fn write_ai_ack_status(act_file_path: &PathBuf, ai_ack_file_path: &PathBuf, status: &serde_json::Value) {
+    if act_file_path.exists() {
+        if let Err(e) = fs::remove_file(act_file_path) {
+            eprintln!("{}", i18n::tf("error.remove", &[&act_file_path.display(), &e]));
+        }
+    }
+    if let Err(e) = fs::write(ai_ack_file_path, status.to_string()) {
+        eprintln!("{}", i18n::tf("error.write", &[&ai_ack_file_path.display(), &e]));
+    }
+}
+
+fn exchange_status(exchange: &LastExchange) -> serde_json::Value {
+    serde_json::json!({
+        "status": "OK",
+        "model": exchange.model,
+        "prompt_tokens": exchange.usage.prompt_tokens,
+        "completion_tokens": exchange.usage.completion_tokens,
+        "total_tokens": exchange.usage.total_tokens,
+        "duration_ms": exchange.duration.as_millis() as u64,
+    })
+}
+
+fn powerline_section_title(model: &str, stream: bool, custom_message: Option<&str>) -> String {
     // Default message if no custom message is provided
-    let message = custom_message.unwrap_or(" (type 'q' to quit, '/help' for help)");
+    let hint = format!(" {}", i18n::t("title.hint"));
+    let message = custom_message.unwrap_or(&hint);
 
-    // Default color if no custom color is provided
-    let color = custom_color.unwrap_or("\x1b[33m"); // Yellow as default
+    if output::is_accessible() {
+        let stream = if stream { "status.enabled" } else { "status.disabled" };
+        return format!("{}{}", i18n::tf("title.accessible", &[&model, &i18n::t(stream)]), message);
+    }
+    // Without colors the arrows would only be glyphs with no visible edge
+    if !theme::colors() {
+        let stream = if stream { " (stream)" } else { "" };
+        return format!("{} | {}{} |{}", i18n::t("title.mode"), model, stream, message);
+    }
 
-    println!(
-        "\x1b[43m\x1b[30m Interactive Mode \x1b[0m{}\x1b[44m\x1b[30m {} \x1b[0m{}{}\x1b[0m{}",
-        color, // Transition arrow
-        model,
-        if stream {
-            // White background (47m) with black text (30m) for the stream segment
-            "\x1b[34m\x1b[47m\x1b[30m (stream)\x1b[0m".to_string()
-        } else {
-            String::new()
-        },
+    format!(
+        "{}{}{}{}{}",
+        theme::TITLE.paint(format!(" {} ", i18n::t("title.mode"))),
+        theme::TITLE_ARROW.paint(""),
+        theme::TITLE_MODEL.paint(format!(" {} ", model)),
         if stream {
-            // White arrow (37m) transitioning to default background (49m)
-            "\x1b[37m\x1b[49m"
+            // Blue arrow into the white stream segment, then a white arrow out of it
+            format!(
+                "{}{}{}",
+                theme::TITLE_MODEL_ARROW.paint(""),
+                theme::TITLE_STREAM.paint(" (stream)"),
+                theme::TITLE_STREAM_END.paint("")
+            )
         } else {
-            // Default arrow (34m, blue) transitioning to default background (49m)
-            "\x1b[34m\x1b[49m"
+            theme::TITLE_MODEL_END.paint("")
         },
         message // Custom or default message
-    );
+    )
+}
+
+// Print the title for the session's current model/stream/role; when pinned it is redrawn in
+// place on the first line, which stays outside the scrolling region
+fn show_status_line(session: &ChatSession, pinned: bool) {
+    let role_message = session.role().map(|role| format!(" {} {}", i18n::tf("title.role", &[&role]), i18n::t("title.hint")));
+    let title = powerline_section_title(session.model(), session.is_stream(), role_message.as_deref());
+    if pinned {
+        // A pinned line that wraps would push into the scrolling region
+        let columns = crossterm::terminal::size().map(|(columns, _)| columns as usize).unwrap_or(80);
+        print!("\x1b7\x1b[1;1H\x1b[2K{}\x1b8", text_width::truncate(&title, columns));
+        let _ = std::io::Write::flush(&mut std::io::stdout());
+    } else {
+        println!("{}", title);
+    }
+}
+
+// What the status line shows, to tell when it needs redrawing
+fn status_state(session: &ChatSession) -> (String, bool, Option<String>) {
+    (session.model().to_string(), session.is_stream(), session.role().map(String::from))
+}
+
+// Reserve the first terminal line for the status line and scroll everything else below it
+fn pin_status_line(session: &ChatSession) {
+    let rows = crossterm::terminal::size().map(|(_, rows)| rows).unwrap_or(24);
+    print!("\x1b[2J\x1b[2;{}r\x1b[2;1H", rows);
+    show_status_line(session, true);
+}
+
+// `.file <path> <question...>`: a quoted path or the first word is the file. The whole argument
+// is taken as the path when such a file exists, so names with spaces keep working unquoted.
+fn split_file_args(args: &str) -> (&str, Option<&str>) {
+    if let Some(quoted) = args.strip_prefix('"') {
+        if let Some((path, rest)) = quoted.split_once('"') {
+            return (path, Some(rest.trim()).filter(|q| !q.is_empty()));
+        }
+    }
+    if std::path::Path::new(args).exists() {
+        return (args, None);
+    }
+    match args.split_once(char::is_whitespace) {
+        Some((path, rest)) => (path, Some(rest.trim()).filter(|q| !q.is_empty())),
+        None => (args, None),
+    }
+}
+
+// `/snippet [save <name> | rm <name> | <name>]`; returns the name and prompt of a snippet to send
+fn snippet_command(args: &str, last_input: &str) -> Option<(String, String)> {
+    match args.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [] => {
+            let snippets = snippets::load();
+            if snippets.is_empty() {
+                println!("{}", i18n::t("snippet.none"));
+            }
+            for (name, prompt) in snippets {
+                let first_line = prompt.lines().next().unwrap_or("");
+                println!("{}  {}", theme::VALUE.paint(&name), text_width::truncate(first_line, 60));
+            }
+        }
+        ["save", name] if last_input.is_empty() => println!("{}", i18n::tf("snippet.nothing", &[name])),
+        ["save", name] => match snippets::save(name, last_input) {
+            Ok(true) => println!("{}", i18n::tf("snippet.replaced", &[name])),
+            Ok(false) => println!("{}", i18n::tf("snippet.saved", &[name])),
+            Err(e) => println!("{} {}", i18n::error_label(), e),
+        },
+        ["rm", name] => match snippets::remove(name) {
+            Ok(true) => println!("{}", i18n::tf("snippet.removed", &[name])),
+            Ok(false) => println!("{}", i18n::tf("snippet.missing", &[name])),
+            Err(e) => println!("{} {}", i18n::error_label(), e),
+        },
+        [name] => match snippets::get(name) {
+            Some(prompt) => return Some((name.to_string(), prompt)),
+            None => println!("{}", i18n::tf("snippet.unknown", &[name])),
+        },
+        _ => println!("{}", i18n::t("usage.snippet")),
+    }
+    None
+}
+
+// Signal files and mic routes shared by every input source
+struct InputEnv {
+    act_file_path: PathBuf,
+    ai_ack_file_path: PathBuf,
+    answer_file_path: PathBuf,
+    mic_router: MicRouter,
+}
+
+// `history_save`: where line history goes (history.txt, history-<profile>.txt with a profile, or
+// nowhere) and whether lines that look like secrets are left out
+struct HistoryPolicy {
+    file: Option<PathBuf>,
+    filtered: bool,
+}
+
+impl HistoryPolicy {
+    fn from_config(config: &Config) -> Self {
+        let (save, filtered) = match &config.history_save {
+            None | Some(HistorySave::Flag(true)) => (true, false),
+            Some(HistorySave::Flag(false)) => (false, false),
+            Some(HistorySave::Mode(mode)) if mode == "filtered" => (true, true),
+            Some(HistorySave::Mode(mode)) => {
+                eprintln!("{}", i18n::tf("error.history_save", &[&mode]));
+                (true, true)
+            }
+        };
+        let name = match active_profile() {
+            Some(profile) => format!("history-{}.txt", profile),
+            None => "history.txt".to_string(),
+        };
+        HistoryPolicy {
+            file: save.then(|| get_data_dir().join(name)),
+            filtered,
+        }
+    }
+
+    fn keeps(&self, line: &str) -> bool {
+        !(self.filtered && redact::contains_secret(line))
+    }
+}
+
+// Alt-0..Alt-3 switch to the alias models; `model_keys` in config adds or replaces bindings
+fn model_keys(config: &Config) -> Vec<(KeyEvent, String)> {
+    let mut keys: Vec<(KeyEvent, String)> = alias_models(config)
+        .into_iter()
+        .enumerate()
+        .map(|(i, model)| (KeyEvent(KeyCode::Char((b'0' + i as u8) as char), Modifiers::ALT), model))
+        .collect();
+    for (spec, model) in config.model_keys.iter().flatten() {
+        match parse_key(spec) {
+            Some(key) => {
+                keys.retain(|(k, _)| *k != key);
+                keys.push((key, model.clone()));
+            }
+            None => eprintln!("{}", i18n::tf("error.model_key", &[&spec])),
+        }
+    }
+    keys
+}
+
+// Carried from one input to the next
+#[derive(Default)]
+struct InputState {
+    last_input: String,
+    queued: VecDeque<String>, // lines typed while an answer was rendering
+    typed_ahead: String,      // unfinished line typed during the last answer
+}
+
+// Send one input to the model whatever its source: preview, act/ack signalling, "." history,
+// type-ahead and the streamed answer
+async fn dispatch_input(
+    source: InputSource,
+    content: &str,
+    session: &mut ChatSession,
+    client: &impl ChatBackend,
+    render: &mut MarkdownRender,
+    env: &InputEnv,
+    state: &mut InputState,
+) -> Result<(), Box<dyn std::error::Error>> {
+    source.preview(content);
+    let message = source.message(content);
+    let content = message.as_str();
+    if source.records_history() {
+        state.last_input = content.to_string();
+    }
+    write_act(&env.act_file_path);
+
+    let mut answering = session.model().to_string();
+    let result = match env.mic_router.route(content).filter(|_| source.is_mic_bridge()) {
+        Some(routed) => {
+            println!(
+                "{}",
+                output::styled(&format!(
+                    "{}route '{}' -> {}",
+                    theme::TOOL.paint(" 󰑉 "),
+                    routed.pattern,
+                    routed.model.as_deref().unwrap_or("session model")
+                ))
+            );
+            if let Some(model) = &routed.model {
+                answering = model.clone();
+            }
+            session.add_routed_message(&routed, client, render).await
+        }
+        None => session.add_message(content, client, render).await,
+    };
+    let stream = match result {
+        Ok(stream) => stream,
+        Err(e) => {
+            // A failed request ends this exchange, not the session
+            let message = error_message(e.as_ref());
+            println!("{} {}", i18n::error_label(), message);
+            if source.is_mic_bridge() {
+                // The dictation tool is waiting on the ack
+                let status = serde_json::json!({ "status": "error", "error": message });
+                write_ai_ack_status(&env.act_file_path, &env.ai_ack_file_path, &status);
+            }
+            return Ok(());
+        }
+    };
+
+    output::announce(&i18n::tf("announce.response", &[&answering]));
+    let speed = session.stream_speed();
+    if source.takes_type_ahead() {
+        let mut typeahead = TypeAhead::start();
+        render_stream(stream, render, &speed, || typeahead.poll(&mut state.queued)).await;
+        state.typed_ahead = typeahead.finish(&mut state.queued);
+    } else {
+        render_stream(stream, render, &speed, || {}).await;
+    }
+    session.print_stats();
+    let mut rounds = 0;
+    while session.truncated() {
+        if !session.auto_continue() || rounds == MAX_AUTO_CONTINUE {
+            println!("{}", theme::DIM.paint(i18n::t("continue.cut_off")));
+            break;
+        }
+        rounds += 1;
+        if let Err(e) = session.continue_answer(client).await {
+            println!("{} {}", i18n::error_label(), error_message(e.as_ref()));
+            break;
+        }
+        session.print_stats();
+    }
+    output::announce(i18n::t("announce.end"));
+
+    match session.last_exchange().filter(|_| source.is_mic_bridge()) {
+        Some(exchange) => {
+            // Hand the full answer back to the dictation tool
+            if let Err(e) = fs::write(&env.answer_file_path, &exchange.response) {
+                eprintln!("{}", i18n::tf("error.write", &[&env.answer_file_path.display(), &e]));
+            }
+            write_ai_ack_status(&env.act_file_path, &env.ai_ack_file_path, &exchange_status(exchange));
+        }
+        None => write_ai_ack(&env.act_file_path, &env.ai_ack_file_path),
+    }
+    Ok(())
 }
 
 pub async fn interactive_mode(
-    client: &Client,
+    client: &impl ChatBackend,
     model: &str,
     stream: bool,
     user_prompt: &str,
     temp_dir: &str,
+    config: &Config,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let act_file_path = get_temp_file_path(temp_dir, "act");
     let ai_ack_file_path = get_temp_file_path(temp_dir, "ai_ack");
     let mic_file_path = get_temp_file_path(temp_dir, "mic.md");
+    let answer_file_path = config
+        .answer_file
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| get_temp_file_path(temp_dir, "ans.md"));
 
-    powerline_section_title(model, stream, None, None);
+    // A screen reader would read the redrawn line again and again
+    let pinned = config.pin_status_line.unwrap_or(false) && !output::is_accessible();
+    if !pinned {
+        println!("{}", powerline_section_title(model, stream, None));
+    }
     let mut render = MarkdownRender::new();
+    render.set_latex(config.render_latex.unwrap_or(true));
 
     crate::config::load_wordlist();
 
     if mic_file_path.exists() {
         if let Err(e) = fs::remove_file(&mic_file_path) {
-            eprintln!("Failed to remove mic.md: {}", e);
+            eprintln!("{}", i18n::tf("error.remove", &[&"mic.md", &e]));
         }
     }
 
     let mut session = ChatSession::new(model.to_string(), stream, user_prompt.to_string());
-    let history_file = get_config_dir().join("history.txt");
-    let rl: Arc<Mutex<Editor<CommandCompleter>>> = Arc::new(Mutex::new(
-        Editor::<CommandCompleter>::new().map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?,
-    ));
-    rl.lock().unwrap().set_helper(Some(CommandCompleter));
-    rl.lock().unwrap().bind_sequence(
+    session.set_stream_speed(StreamSpeed::from_config(config));
+    session.set_prompt_template(config.prompt_template.clone());
+    session.set_auto_continue(config.auto_continue.unwrap_or(false));
+    session.set_confirm_send(config.confirm_send.unwrap_or(false));
+    session.set_memory(MemoryStore::from_config(config));
+    plugins::load();
+    if pinned {
+        pin_status_line(&session);
+    }
+    if let Some(path) = &config.transcript_log {
+        if let Err(e) = session.start_transcript(std::path::Path::new(path)) {
+            eprintln!("{}", i18n::tf("error.transcript_open", &[&path, &e]));
+        }
+    }
+    let history = HistoryPolicy::from_config(config);
+    let mut rl = Editor::<CommandCompleter>::new().map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
+    rl.set_helper(Some(CommandCompleter::default()));
+    rl.bind_sequence(
         rustyline::KeyEvent(rustyline::KeyCode::Tab, rustyline::Modifiers::NONE),
         rustyline::Cmd::Complete,
     );
-    if rl.lock().unwrap().load_history(&history_file).is_err() {
-        println!("No previous history found at '{}'", history_file.display());
+    // Alt-Enter / Shift-Enter insert a newline instead of submitting; bracketed paste keeps
+    // pasted multi-line text in the buffer, so both end up as a single multi-line message
+    for modifiers in [rustyline::Modifiers::ALT, rustyline::Modifiers::SHIFT] {
+        rl.bind_sequence(
+            rustyline::KeyEvent(rustyline::KeyCode::Enter, modifiers),
+            rustyline::Cmd::Newline,
+        );
     }
+    if let Some(history_file) = &history.file {
+        if rl.load_history(history_file).is_err() {
+            println!("{}", i18n::tf("history.none", &[&history_file.display()]));
+        }
+    }
+    let mut reader = LineReader::spawn(rl, history.file.clone(), model_keys(config));
 
     let (tx, mut rx) = mpsc::channel::<String>(32);
     let mic_file_path_clone = mic_file_path.clone();
-    let act_file_path_clone = act_file_path.clone();
-    let ai_ack_file_path_clone = ai_ack_file_path.clone();
+    let env = InputEnv {
+        act_file_path,
+        ai_ack_file_path,
+        answer_file_path,
+        mic_router: MicRouter::from_config(config),
+    };
 
     let file_monitor_handle = task::spawn(async move {
         let mut last_content = String::new();
         loop {
             sleep(Duration::from_secs(2)).await;
-            let file = match OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open(&mic_file_path_clone)
-            {
-                Ok(file) => file,
-                Err(_) => continue,
-            };
-            if file.lock_exclusive().is_err() {
-                eprintln!("Failed to acquire lock on mic.md");
+            if !mic_file_path_clone.exists() {
                 continue;
             }
-            let content = match std::fs::read_to_string(&mic_file_path_clone) {
+            let content = match read_locked(&mic_file_path_clone) {
                 Ok(content) => content,
-                Err(_) => {
-                    file.unlock()
-                        .unwrap_or_else(|_| eprintln!("Failed to unlock mic.md"));
+                Err(e) => {
+                    eprintln!("{}", i18n::tf("error.read", &[&"mic.md", &e]));
                     continue;
                 }
             };
-            if file.unlock().is_err() {
-                eprintln!("Failed to unlock mic.md");
+            // Don't draw over the recording meter; the change is picked up on a later tick
+            if crate::mic::is_recording() {
+                continue;
             }
             if content != last_content && !content.trim().is_empty() {
                 last_content = content.clone();
-                write_act(&act_file_path);
-                println!(
-                    "\x1b[35m 󰑉 \x1b[0m-- mic.md\n{}",
-                    content.lines().take(3).collect::<Vec<_>>().join("\n")
-                );
                 if let Err(e) = tx.send(content).await {
-                    eprintln!("Error sending file content to channel: {}", e);
+                    eprintln!("{}", i18n::tf("error.channel", &[&e]));
                 }
             }
         }
     });
 
-    let mut last_input = String::new();
+    let mut state = InputState::default();
     let mut should_exit = false;
     let mut multi_line_mode = false; // Flag for multi-line input mode
     let mut multi_line_buffer = Vec::<String>::new(); // Buffer to collect multi-line input
 
     while !should_exit {
-        let prompt = if multi_line_mode {
-            "\x1b[32m󰇙 \x1b[0m".to_string() // Custom prompt for multi-line mode
+        let fence_lang = if multi_line_mode {
+            current_fence_lang(&multi_line_buffer)
         } else {
-            session.get_user_prompt().to_string()
+            None
+        };
+        let prompt = match (&fence_lang, multi_line_mode) {
+            // Show the language of the open code fence in the prompt line
+            (Some(lang), _) if !lang.is_empty() => format!("{}{} ", theme::GOOD.paint("󰇙 "), theme::KEYWORD.paint(lang)),
+            (Some(_), _) => format!("{}{} ", theme::GOOD.paint("󰇙 "), theme::VALUE.paint("```")),
+            (None, true) => theme::GOOD.paint("󰇙 "), // Custom prompt for multi-line mode
+            (None, false) => session.get_user_prompt().to_string(),
         };
+        let prompt = output::styled(&prompt);
 
-        let rl_clone = Arc::clone(&rl);
-        let readline_result = tokio::select! {
-            result = spawn_blocking(move || {
-                let mut rl_guard = rl_clone.lock().unwrap();
-                rl_guard.readline(&prompt)
-            }) => Some(result),
-            Some(file_content) = rx.recv() => {
-                println!("\x1b[32mResponse from machine (based on mic.md):\x1b[0m");
-                write_ai_ack(&act_file_path_clone, &ai_ack_file_path_clone);
-                let mut stream = session.add_message(&file_content, client, &mut render).await?;
-                while let Some(event) = stream.recv().await {
-                    match event {
-                        SseEvent::Text(text) => {
-                            let lines: Vec<&str> = text.split('\n').collect();
-                            for line in lines {
-                                let output = render.render_line_mut(line);
-                                println!("{}", output);
-                            }
-                        }
-                        SseEvent::Done => break,
-                    }
+        let initial = std::mem::take(&mut state.typed_ahead);
+        let readline_result = if let Some(line) = state.queued.pop_front() {
+            // Typed while the previous answer was rendering
+            println!("{}{}", prompt, line);
+            if !state.queued.is_empty() {
+                println!("{}", output::styled(&theme::MUTED.paint(format!("󰒲 {} more queued", state.queued.len()))));
+            }
+            Some(Ok(line))
+        } else {
+            reader.request(prompt, initial, multi_line_mode, fence_lang);
+            tokio::select! {
+                result = reader.next() => Some(result),
+                Some(file_content) = rx.recv() => {
+                    dispatch_input(InputSource::MicWatch, &file_content, &mut session, client, &mut render, &env, &mut state)
+                        .await?;
+                    None
                 }
-                //session.add_message(&file_content, client).await?;
-                None
             }
         };
 
         if let Some(result) = readline_result {
             match result {
-                Ok(Ok(line)) => {
-                    let mut question = line.trim();
+                Ok(line) => {
+                    if let Some(model) = reader.take_model_switch() {
+                        // A model key ended the prompt; what was typed so far stays in it
+                        session.switch_model(&model);
+                        state.typed_ahead = line;
+                        if pinned {
+                            pin_status_line(&session);
+                        }
+                        continue;
+                    }
+                    let question = line.trim();
                     let mut message_content = question.to_string();
+                    let mut source = InputSource::Typed;
+                    // Pasted or Alt-Enter input spanning several lines is always a plain message
+                    let multi_line_input = question.contains('\n');
                     if question == ":::" {
                         if multi_line_mode {
                             // End multi-line mode
@@ -202,15 +520,14 @@ pub async fn interactive_mode(
                             let full_input = multi_line_buffer.join("\n");
                             multi_line_buffer.clear();
                             if !full_input.is_empty() {
-                                println!("\x1b[92m\r󰭻 Multi-line input:\x1b[0m\n{}", full_input);
-                                write_act(&act_file_path_clone);
-                                session.add_message(&full_input, client, &mut render).await?;
-                                write_ai_ack(&act_file_path_clone, &ai_ack_file_path_clone);
+                                let source = InputSource::MultiLine;
+                                dispatch_input(source, &full_input, &mut session, client, &mut render, &env, &mut state)
+                                    .await?;
                             }
                         } else {
                             // Start multi-line mode
                             multi_line_mode = true;
-                            println!("Entering multi-line mode. Type ':::' to finish.");
+                            println!("{}", i18n::t("multi_line.on"));
                         }
                         continue;
                     }
@@ -223,30 +540,15 @@ pub async fn interactive_mode(
 
                     // Handle regular commands outside multi-line mode
                     if question == "." {
-                        if last_input.is_empty() {
-                            println!("No previous input to repeat.");
+                        if state.last_input.is_empty() {
+                            println!("{}", i18n::t("repeat.nothing"));
                             continue;
                         }
-                        println!("\x1b[92m\r󰭻 \x1b[0m: {}", last_input);
-                        write_act(&act_file_path_clone);
-
-                        let mut stream = session.add_message(&last_input, client, &mut render).await?;
-                        while let Some(event) = stream.recv().await {
-                            match event {
-                                SseEvent::Text(text) => {
-                                    let lines: Vec<&str> = text.split('\n').collect();
-                                    for line in lines {
-                                        let output = render.render_line_mut(line);
-                                        println!("{}", output);
-                                    }
-                                }
-                                SseEvent::Done => break,
-                            }
-                    	}
-                        //write_ai_ack(&act_file_path_clone, &ai_ack_file_path_clone);
-			continue;
-
-	           }
+                        let last_input = state.last_input.clone();
+                        dispatch_input(InputSource::Repeat, &last_input, &mut session, client, &mut render, &env, &mut state)
+                            .await?;
+                        continue;
+                    }
                     if question == "?" {
                         if session.handle_command("?", client).await? {
                             continue;
@@ -261,85 +563,110 @@ pub async fn interactive_mode(
                         if session.handle_command("cls", client).await? {
                             continue;
                         }
+                        if pinned {
+                            pin_status_line(&session);
+                        }
                         continue;
                     }
                     if question == "jc" {
                         if !PathBuf::from(&mic_file_path).exists() {
-                            println!("Skip: mic.md does not exist");
+                            println!("{}", i18n::t("error.no_mic_file"));
                             continue;
                         }
-                        let file = OpenOptions::new()
-                            .read(true)
-                            .write(true)
-                            .open(&mic_file_path)?;
-                        file.lock_exclusive()?;
-                        let content = std::fs::read_to_string(&mic_file_path)?;
-                        file.unlock()?;
-                        let preview = content.lines().take(3).collect::<Vec<_>>().join("\n");
-                        println!(
-                            "\x1b[33mPreview:\x1b[0m --- load from {} ---\n{}",
-                            mic_file_path.to_string_lossy(),
-                            preview
-                        );
-                        println!("\x1b[32mMachine response:\x1b[0m");
-                        session.add_message(&content, client, &mut render).await?;
+                        let content = read_locked(&mic_file_path)?;
+                        let source = InputSource::MicCommand(mic_file_path.clone());
+                        dispatch_input(source, &content, &mut session, client, &mut render, &env, &mut state).await?;
                         continue;
                     }
 
-                    if question.starts_with(".file") {
-                        let parts: Vec<&str> = question.splitn(2, ' ').collect();
-                        if parts.len() > 1 {
-                            let filename = parts[1];
+                    if question.starts_with(".file") && !multi_line_input {
+                        let args = question.trim_start_matches(".file").trim();
+                        if !args.is_empty() {
+                            let (filename, file_question) = split_file_args(args);
                             let file_path = PathBuf::from(filename);
                             if !file_path.exists() {
-                                println!("Error: File '{}' does not exist.", filename);
+                                println!("{} {}", i18n::error_label(), i18n::tf("error.file_missing", &[&filename]));
                                 continue;
                             }
-                            let file = match OpenOptions::new()
-                                .read(true)
-                                .write(true)
-                                .open(&file_path)
-                            {
-                                Ok(file) => file,
-                                Err(_) => {
-                                    println!("Error: Failed to open file '{}'.", filename);
+                            // Directories are walked; single files may be shared, so read them locked
+                            let content = if file_path.is_dir() {
+                                ingest::read_dir(&file_path)
+                            } else {
+                                ingest::check_file(&file_path).and_then(|_| {
+                                    read_locked(&file_path)
+                                        .map_err(|e| i18n::tf("error.read_file", &[&filename, &e]))
+                                })
+                            };
+                            let content = match content {
+                                Ok(content) => content,
+                                Err(e) => {
+                                    println!("{} {}", i18n::error_label(), e);
                                     continue;
                                 }
                             };
-                            if file.lock_exclusive().is_err() {
-                                println!("Error: Failed to acquire lock on file '{}'.", filename);
+                            let trimmed_content = content.trim();
+                            if trimmed_content.is_empty() {
+                                println!("{} {}", i18n::error_label(), i18n::tf("error.file_empty", &[&filename]));
                                 continue;
                             }
-                            let content = match std::fs::read_to_string(&file_path) {
+                            // Too long for the model: condensed part by part instead of sent oversized
+                            let content = match condense::fit(client, session.model(), filename, trimmed_content.to_string(), file_question).await {
                                 Ok(content) => content,
-                                Err(_) => {
-                                    file.unlock().unwrap_or_else(|_| {
-                                        eprintln!("Failed to unlock file '{}'.", filename)
-                                    });
-                                    println!("Error: Failed to read file '{}'.", filename);
+                                Err(e) => {
+                                    println!("{} {}", i18n::error_label(), error_message(e.as_ref()));
                                     continue;
                                 }
                             };
-                            if file.unlock().is_err() {
-                                eprintln!("Failed to unlock file '{}'.", filename);
+                            source = InputSource::File {
+                                path: file_path,
+                                question: file_question.map(String::from),
+                            };
+                            message_content = content;
+                        } else {
+                            println!("{}", i18n::t("usage.file"));
+                            continue;
+                        }
+                    }
+
+                    if question == "/dictate" {
+                        let config = load_config()?;
+                        let transcriber = match Transcriber::from_config(&config, None) {
+                            Ok(t) => Arc::new(t),
+                            Err(e) => {
+                                println!("{} {}", i18n::error_label(), e);
+                                continue;
                             }
-                            let trimmed_content = content.trim();
-                            if trimmed_content.is_empty() {
-                                println!("Error: File '{}' is empty or contains only whitespace.", filename);
+                        };
+                        let options = MicOptions::from_config(&config);
+                        let chunk_secs = config.dictate_chunk_secs.unwrap_or(4).max(1);
+                        match dictate_main(&options, transcriber, chunk_secs) {
+                            Ok(Some(text)) => {
+                                message_content = text;
+                                source = InputSource::Dictation;
+                            }
+                            Ok(None) => {
+                                println!("{}", i18n::t("dictate.canceled"));
                                 continue;
                             }
-                            let preview = trimmed_content.lines().take(3).collect::<Vec<_>>().join("\n");
-                            println!(
-                                "\x1b[33mPreview:\x1b[0m --- load from {} ---
-                                    \r{}",
-                                filename, preview
-                            );
-                            println!("\x1b[32mMachine response:\x1b[0m");
-                            message_content = trimmed_content.to_string();
-                        } else {
-                            println!("Usage: .file <filename>");
-                            continue;
+                            Err(e) => {
+                                println!("{} {}", i18n::error_label(), e);
+                                continue;
+                            }
+                        }
+                    }
+
+                    if let Some(args) = question
+                        .strip_prefix("/snippet")
+                        .filter(|args| !multi_line_input && (args.is_empty() || args.starts_with(' ')))
+                    {
+                        if history.keeps(&line) {
+                            reader.add_history_entry(&line);
                         }
+                        let Some((name, prompt)) = snippet_command(args, &state.last_input) else {
+                            continue;
+                        };
+                        message_content = prompt;
+                        source = InputSource::Snippet(name);
                     }
 
                     if question == "mic" {
@@ -351,47 +678,59 @@ pub async fn interactive_mode(
                     if question.is_empty() {
                         continue;
                     }
-                    if let Some(stripped) = question.strip_prefix("/") {
-                        rl.lock().unwrap().add_history_entry(line.as_str());
+                    if let Some(stripped) = question.strip_prefix("/").filter(|_| !multi_line_input && matches!(source, InputSource::Typed)) {
+                        if history.keeps(&line) {
+                            reader.add_history_entry(&line);
+                        }
                         let command = stripped; // Remove the leading slash
+                        let mut cmd_parts = command.splitn(2, ' ');
+                        let cmd_name = cmd_parts.next().unwrap_or("");
+                        let cmd_args = cmd_parts.next().unwrap_or("");
+                        // Built-in commands come first; plugins can't take their names
+                        if let Some(plugin) = find_plugin(cmd_name).filter(|_| command_help(cmd_name).is_none()) {
+                            // Plugin stdout is fed back into the conversation as a user message
+                            let output = match run_plugin(&plugin, cmd_args) {
+                                Ok(output) => output,
+                                Err(e) => {
+                                    println!("{} {}", i18n::error_label(), e);
+                                    continue;
+                                }
+                            };
+                            if output.trim().is_empty() {
+                                println!("{}", i18n::tf("plugins.no_output", &[&plugin.name]));
+                                continue;
+                            }
+                            let source = InputSource::Plugin(plugin.name.clone());
+                            dispatch_input(source, &output, &mut session, client, &mut render, &env, &mut state).await?;
+                            continue;
+                        }
+                        let before = status_state(&session);
                         if session.handle_command(command, client).await? {
                             should_exit = true;
                             continue;
                         }
+                        if cmd_name == "cls" && pinned {
+                            pin_status_line(&session);
+                        } else if before != status_state(&session) {
+                            // /model, /ss, /system, /load: keep the title in line with what gets queried
+                            show_status_line(&session, pinned);
+                        }
                     } else {
                         if !message_content.trim().is_empty() {
-                            last_input = message_content.clone();
-                            write_act(&act_file_path_clone);
-                            let mut stream = session.add_message(&message_content, client, &mut render).await?;
-                            while let Some(event) = stream.recv().await {
-                                match event {
-                                    SseEvent::Text(text) => {
-                                        let lines: Vec<&str> = text.split('\n').collect();
-                                        for line in lines {
-                                            let output = render.render_line_mut(line);
-                                            println!("{}", output);
-                                        }
-                                    }
-                                    SseEvent::Done => break,
-                                }
-                            }
-                            write_ai_ack(&act_file_path_clone, &ai_ack_file_path_clone);
+                            dispatch_input(source, &message_content, &mut session, client, &mut render, &env, &mut state)
+                                .await?;
                         }
                     }
                 }
-                Ok(Err(ReadlineError::Interrupted)) => {
+                Err(ReadlineError::Interrupted) => {
                     continue;
                 }
-                Ok(Err(ReadlineError::Eof)) => {
-                    println!("CTRL-D Quitted");
+                Err(ReadlineError::Eof) => {
+                    println!("{}", i18n::t("quit.eof"));
                     should_exit = true;
                 }
-                Ok(Err(err)) => {
-                    println!("Error: {:?}", err);
-                    should_exit = true;
-                }
-                Err(join_err) => {
-                    eprintln!("Failed to start background task: {}", join_err);
+                Err(err) => {
+                    println!("{} {:?}", i18n::error_label(), err);
                     should_exit = true;
                 }
             }
@@ -399,7 +738,207 @@ pub async fn interactive_mode(
     }
 
     file_monitor_handle.abort();
-    std::process::exit(0);
-    #[allow(unreachable_code)]
+    if pinned {
+        println!("\x1b7\x1b[r\x1b8"); // release the scrolling region, keeping the cursor
+    }
+    session.remember_session(client).await;
+    if config.autosave_sessions.unwrap_or(true) {
+        session.autosave(client).await;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MicRoute;
+    use crate::test_support::{temp_dir, text, ScriptedBackend};
+
+    // One REPL's worth of state around dispatch_input, with signal files in a temp dir
+    struct Harness {
+        session: ChatSession,
+        render: MarkdownRender,
+        env: InputEnv,
+        state: InputState,
+    }
+
+    impl Harness {
+        fn new(name: &str, config: &Config) -> Self {
+            let dir = temp_dir(name);
+            Harness {
+                session: ChatSession::new("gpt-4o-mini".to_string(), false, ">".to_string()),
+                render: MarkdownRender::new(),
+                env: InputEnv {
+                    act_file_path: dir.join("act"),
+                    ai_ack_file_path: dir.join("ai_ack"),
+                    answer_file_path: dir.join("ans.md"),
+                    mic_router: MicRouter::from_config(config),
+                },
+                state: InputState::default(),
+            }
+        }
+
+        async fn input(&mut self, source: InputSource, content: &str, backend: &ScriptedBackend) {
+            dispatch_input(
+                source,
+                content,
+                &mut self.session,
+                backend,
+                &mut self.render,
+                &self.env,
+                &mut self.state,
+            )
+            .await
+            .unwrap();
+        }
+
+        fn ack(&self) -> String {
+            fs::read_to_string(&self.env.ai_ack_file_path).unwrap_or_default()
+        }
+    }
+
+    #[tokio::test]
+    async fn typed_input_is_sent_remembered_and_acknowledged() {
+        let backend = ScriptedBackend::new("pong");
+        let mut harness = Harness::new("typed", &Config::default());
+        harness.input(InputSource::Typed, "ping", &backend).await;
+        assert_eq!(backend.last_prompt(), "ping");
+        assert_eq!(harness.state.last_input, "ping");
+        assert_eq!(harness.ack(), "OK");
+        assert!(!harness.env.act_file_path.exists());
+        assert_eq!(harness.session.last_exchange().unwrap().response, "pong");
+    }
+
+    #[tokio::test]
+    async fn repeat_resends_the_last_input_with_the_history() {
+        let backend = ScriptedBackend::new("").then("first").then("second");
+        let mut harness = Harness::new("repeat", &Config::default());
+        harness.input(InputSource::Typed, "question", &backend).await;
+        let last = harness.state.last_input.clone();
+        harness.input(InputSource::Repeat, &last, &backend).await;
+        assert_eq!(backend.request_count(), 2);
+        assert_eq!(backend.last_prompt(), "question");
+        let requests = backend.requests.borrow();
+        // system, question, first, question
+        assert_eq!(requests[1].1.messages.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn file_with_a_question_is_sent_as_fenced_context() {
+        let backend = ScriptedBackend::new("ok");
+        let mut harness = Harness::new("file", &Config::default());
+        let source = InputSource::File {
+            path: PathBuf::from("notes.rs"),
+            question: Some("what does it do?".to_string()),
+        };
+        harness.input(source, "fn main() {}", &backend).await;
+        assert_eq!(backend.last_prompt(), "notes.rs:\n```rs\nfn main() {}\n```\n\nwhat does it do?");
+        assert_eq!(harness.state.last_input, backend.last_prompt());
+    }
+
+    #[tokio::test]
+    async fn files_over_the_context_budget_are_condensed_in_parts() {
+        let backend = ScriptedBackend::new("").then("first half").then("second half");
+        let small = condense::fit(&backend, "gpt-4o-mini", "small.md", "short".to_string(), None).await.unwrap();
+        assert_eq!(small, "short");
+        assert_eq!(backend.request_count(), 0);
+
+        // About 75k tokens against a 64k budget for a 128k model
+        let paragraph = format!("{}\n\n", "word ".repeat(200));
+        let large = paragraph.repeat(300);
+        let condensed = condense::fit(&backend, "gpt-4o-mini", "large.md", large, Some("who wrote it?"))
+            .await
+            .unwrap();
+        assert_eq!(backend.request_count(), 2);
+        assert!(backend.last_prompt().starts_with("Part 2 of 2 of 'large.md':"));
+        assert!(backend.last_prompt().contains("used to answer: who wrote it?"));
+        assert!(condensed.ends_with("first half\n\nsecond half"));
+    }
+
+    #[tokio::test]
+    async fn plugin_output_is_not_repeated_by_dot() {
+        let backend = ScriptedBackend::new("ok");
+        let mut harness = Harness::new("plugin", &Config::default());
+        harness.input(InputSource::Typed, "mine", &backend).await;
+        harness.input(InputSource::Plugin("weather".to_string()), "sunny", &backend).await;
+        assert_eq!(backend.last_prompt(), "sunny");
+        assert_eq!(harness.state.last_input, "mine");
+    }
+
+    #[tokio::test]
+    async fn mic_bridge_writes_the_answer_and_a_json_ack() {
+        let backend = ScriptedBackend::new("spoken answer");
+        let mut harness = Harness::new("mic", &Config::default());
+        harness.input(InputSource::MicWatch, "dictated", &backend).await;
+        assert_eq!(fs::read_to_string(&harness.env.answer_file_path).unwrap(), "spoken answer");
+        let ack: serde_json::Value = serde_json::from_str(&harness.ack()).unwrap();
+        assert_eq!(ack["status"], "OK");
+        assert_eq!(ack["model"], "gpt-4o-mini");
+    }
+
+    #[tokio::test]
+    async fn mic_bridge_failure_is_acknowledged_and_not_fatal() {
+        let backend = ScriptedBackend::new("").then_fail("provider down");
+        let mut harness = Harness::new("mic-error", &Config::default());
+        harness.input(InputSource::MicWatch, "dictated", &backend).await;
+        let ack: serde_json::Value = serde_json::from_str(&harness.ack()).unwrap();
+        assert_eq!(ack["status"], "error");
+        assert_eq!(ack["error"], "provider down");
+        assert!(!harness.env.answer_file_path.exists());
+    }
+
+    #[tokio::test]
+    async fn typed_failure_is_reported_and_the_session_goes_on() {
+        let backend = ScriptedBackend::new("later").then_fail("provider down");
+        let mut harness = Harness::new("typed-error", &Config::default());
+        harness.input(InputSource::Typed, "first", &backend).await;
+        assert!(harness.session.last_exchange().is_none());
+        harness.input(InputSource::Typed, "second", &backend).await;
+        assert_eq!(harness.session.last_exchange().unwrap().response, "later");
+    }
+
+    #[tokio::test]
+    async fn truncated_answers_are_continued_into_one_message() {
+        let backend = ScriptedBackend::new("ok").then("one two").then(" three");
+        let mut harness = Harness::new("continue", &Config::default());
+        harness.session.set_auto_continue(true);
+        harness.session.handle_command("maxtokens 2", &backend).await.unwrap();
+        harness.input(InputSource::Typed, "count", &backend).await;
+        assert_eq!(backend.request_count(), 2);
+        assert!(backend.last_prompt().contains("cut off"));
+        assert_eq!(harness.session.last_exchange().unwrap().response, "one two three");
+        assert!(!harness.session.truncated());
+        // The continue turn is gone; the pieces are one answer in the history
+        harness.input(InputSource::Typed, "next", &backend).await;
+        let requests = backend.requests.borrow();
+        let sent: Vec<&str> = requests[2].1.messages.iter().skip(1).map(text).collect();
+        assert_eq!(sent, ["count", "one two three", "next"]);
+    }
+
+    #[tokio::test]
+    async fn mic_routes_apply_to_the_bridge_only() {
+        let config = Config {
+            mic_routes: Some(vec![MicRoute {
+                pattern: "^code:".to_string(),
+                model: Some("strong-model".to_string()),
+                role: None,
+                template: None,
+            }]),
+            ..Config::default()
+        };
+        let backend = ScriptedBackend::new("ok");
+        let mut harness = Harness::new("routes", &config);
+        harness.input(InputSource::MicWatch, "code: sort a vec", &backend).await;
+        assert_eq!(backend.last_model(), "strong-model");
+        harness.input(InputSource::Typed, "code: sort a vec", &backend).await;
+        assert_eq!(backend.last_model(), "gpt-4o-mini");
+        assert_eq!(backend.last_prompt(), "code: sort a vec");
+    }
+
+    #[test]
+    fn file_arguments_split_into_path_and_question() {
+        assert_eq!(split_file_args("notes.md"), ("notes.md", None));
+        assert_eq!(split_file_args("notes.md explain it"), ("notes.md", Some("explain it")));
+        assert_eq!(split_file_args("\"my notes.md\" explain"), ("my notes.md", Some("explain")));
+    }
+}
@@ -0,0 +1,180 @@
+// latex.rs
+// Unicode approximations of common LaTeX math, good enough to read formulas in a terminal
+
+const SYMBOLS: &[(&str, &str)] = &[
+    // Greek
+    ("alpha", "α"), ("beta", "β"), ("gamma", "γ"), ("delta", "δ"), ("epsilon", "ε"),
+    ("varepsilon", "ε"), ("zeta", "ζ"), ("eta", "η"), ("theta", "θ"), ("vartheta", "ϑ"),
+    ("iota", "ι"), ("kappa", "κ"), ("lambda", "λ"), ("mu", "μ"), ("nu", "ν"), ("xi", "ξ"),
+    ("pi", "π"), ("rho", "ρ"), ("sigma", "σ"), ("tau", "τ"), ("upsilon", "υ"), ("phi", "φ"),
+    ("varphi", "φ"), ("chi", "χ"), ("psi", "ψ"), ("omega", "ω"), ("Gamma", "Γ"), ("Delta", "Δ"),
+    ("Theta", "Θ"), ("Lambda", "Λ"), ("Xi", "Ξ"), ("Pi", "Π"), ("Sigma", "Σ"), ("Phi", "Φ"),
+    ("Psi", "Ψ"), ("Omega", "Ω"),
+    // Operators and relations
+    ("times", "×"), ("cdot", "·"), ("div", "÷"), ("pm", "±"), ("mp", "∓"), ("leq", "≤"),
+    ("le", "≤"), ("geq", "≥"), ("ge", "≥"), ("neq", "≠"), ("ne", "≠"), ("approx", "≈"),
+    ("equiv", "≡"), ("sim", "∼"), ("propto", "∝"), ("ll", "≪"), ("gg", "≫"),
+    ("sum", "∑"), ("prod", "∏"), ("int", "∫"), ("oint", "∮"), ("partial", "∂"), ("nabla", "∇"),
+    ("infty", "∞"), ("sqrt", "√"), ("circ", "∘"), ("ast", "∗"), ("star", "⋆"),
+    // Arrows
+    ("to", "→"), ("rightarrow", "→"), ("leftarrow", "←"), ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"), ("Leftarrow", "⇐"), ("Leftrightarrow", "⇔"), ("implies", "⇒"),
+    ("iff", "⇔"), ("mapsto", "↦"),
+    // Sets and logic
+    ("in", "∈"), ("notin", "∉"), ("subset", "⊂"), ("subseteq", "⊆"), ("supset", "⊃"),
+    ("supseteq", "⊇"), ("cup", "∪"), ("cap", "∩"), ("emptyset", "∅"), ("varnothing", "∅"),
+    ("forall", "∀"), ("exists", "∃"), ("neg", "¬"), ("land", "∧"), ("lor", "∨"),
+    ("wedge", "∧"), ("vee", "∨"), ("mathbb", ""),
+    // Dots and spacing
+    ("ldots", "…"), ("cdots", "⋯"), ("dots", "…"), ("vdots", "⋮"), ("quad", "  "),
+    ("qquad", "    "), ("left", ""), ("right", ""), ("displaystyle", ""),
+    ("lfloor", "⌊"), ("rfloor", "⌋"), ("lceil", "⌈"), ("rceil", "⌉"), ("langle", "⟨"),
+    ("rangle", "⟩"), ("degree", "°"), ("prime", "′"), ("hbar", "ħ"), ("ell", "ℓ"),
+];
+
+// Commands whose argument is shown as-is
+const TEXT_COMMANDS: &[&str] = &[
+    "text", "mathrm", "mathbf", "mathit", "mathsf", "mathtt", "operatorname", "textbf", "textit", "boldsymbol",
+];
+
+const SUPERSCRIPTS: &[(char, char)] = &[
+    ('0', '⁰'), ('1', '¹'), ('2', '²'), ('3', '³'), ('4', '⁴'), ('5', '⁵'), ('6', '⁶'),
+    ('7', '⁷'), ('8', '⁸'), ('9', '⁹'), ('+', '⁺'), ('-', '⁻'), ('=', '⁼'), ('(', '⁽'),
+    (')', '⁾'), ('n', 'ⁿ'), ('i', 'ⁱ'), ('T', 'ᵀ'), ('x', 'ˣ'), ('k', 'ᵏ'), ('a', 'ᵃ'),
+    ('b', 'ᵇ'), ('c', 'ᶜ'), ('d', 'ᵈ'), ('e', 'ᵉ'), ('m', 'ᵐ'), ('t', 'ᵗ'), ('j', 'ʲ'),
+];
+
+const SUBSCRIPTS: &[(char, char)] = &[
+    ('0', '₀'), ('1', '₁'), ('2', '₂'), ('3', '₃'), ('4', '₄'), ('5', '₅'), ('6', '₆'),
+    ('7', '₇'), ('8', '₈'), ('9', '₉'), ('+', '₊'), ('-', '₋'), ('=', '₌'), ('(', '₍'),
+    (')', '₎'), ('i', 'ᵢ'), ('j', 'ⱼ'), ('k', 'ₖ'), ('n', 'ₙ'), ('m', 'ₘ'), ('x', 'ₓ'),
+    ('a', 'ₐ'), ('e', 'ₑ'), ('o', 'ₒ'), ('t', 'ₜ'), ('r', 'ᵣ'), ('s', 'ₛ'), ('p', 'ₚ'),
+];
+
+// Content of the `{...}` group starting at `start` (which must be '{'), and the index after it
+fn take_group(chars: &[char], start: usize) -> Option<(String, usize)> {
+    if chars.get(start) != Some(&'{') {
+        return None;
+    }
+    let mut depth = 0;
+    for (i, c) in chars.iter().enumerate().skip(start) {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((chars[start + 1..i].iter().collect(), i + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Argument of a command or script: a `{group}`, or a single character
+fn take_arg(chars: &[char], start: usize) -> Option<(String, usize)> {
+    take_group(chars, start).or_else(|| chars.get(start).map(|c| (c.to_string(), start + 1)))
+}
+
+fn map_script(text: &str, table: &[(char, char)]) -> Option<String> {
+    text.chars()
+        .map(|c| table.iter().find(|(from, _)| *from == c).map(|(_, to)| *to))
+        .collect()
+}
+
+fn wrap(text: &str) -> String {
+    if text.chars().count() <= 1 || text.chars().all(|c| c.is_alphanumeric()) {
+        text.to_string()
+    } else {
+        format!("({})", text)
+    }
+}
+
+pub fn latex_to_unicode(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                let name: String = chars[i + 1..].iter().take_while(|c| c.is_ascii_alphabetic()).collect();
+                let after = i + 1 + name.len();
+                if name.is_empty() {
+                    // \, \; \! \{ \} \\ and friends
+                    match chars.get(i + 1) {
+                        Some(',') | Some(';') | Some(':') => out.push(' '),
+                        Some('\\') => out.push_str("; "),
+                        Some(c @ ('{' | '}' | '$' | '%' | '#' | '&' | '_')) => out.push(*c),
+                        _ => {}
+                    }
+                    i += 2;
+                } else if name == "frac" || name == "dfrac" || name == "tfrac" {
+                    let parts = take_arg(&chars, after)
+                        .and_then(|(num, j)| take_arg(&chars, j).map(|(den, k)| (num, den, k)));
+                    match parts {
+                        Some((num, den, k)) => {
+                            let num = wrap(&latex_to_unicode(&num));
+                            let den = wrap(&latex_to_unicode(&den));
+                            out.push_str(&format!("{}/{}", num, den));
+                            i = k;
+                        }
+                        None => i = after,
+                    }
+                } else if name == "sqrt" {
+                    match take_arg(&chars, after) {
+                        Some((arg, k)) => {
+                            out.push_str(&format!("√{}", wrap(&latex_to_unicode(&arg))));
+                            i = k;
+                        }
+                        None => {
+                            out.push('√');
+                            i = after;
+                        }
+                    }
+                } else if TEXT_COMMANDS.contains(&name.as_str()) {
+                    match take_group(&chars, after) {
+                        Some((arg, k)) => {
+                            out.push_str(&latex_to_unicode(&arg));
+                            i = k;
+                        }
+                        None => i = after,
+                    }
+                } else {
+                    match SYMBOLS.iter().find(|(n, _)| *n == name) {
+                        Some((_, symbol)) => out.push_str(symbol),
+                        None => out.push_str(&name), // unknown command: keep its name readable
+                    }
+                    i = after;
+                }
+            }
+            c @ ('^' | '_') => {
+                let table = if c == '^' { SUPERSCRIPTS } else { SUBSCRIPTS };
+                match take_arg(&chars, i + 1) {
+                    Some((arg, k)) => {
+                        let arg = latex_to_unicode(&arg);
+                        match map_script(&arg, table) {
+                            Some(mapped) => out.push_str(&mapped),
+                            None if arg.chars().count() > 1 => out.push_str(&format!("{}({})", c, arg)),
+                            None => {
+                                out.push(c);
+                                out.push_str(&arg);
+                            }
+                        }
+                        i = k;
+                    }
+                    None => {
+                        out.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            '{' | '}' => i += 1,
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,151 @@
+// line_reader.rs
+// The rustyline editor lives on its own thread. The REPL asks for a line and awaits it on a
+// channel, so the mic.md watcher and answer rendering never wait on the editor. History entries
+// are appended to the file as they come; rustyline merges under a file lock, so several
+// instances don't overwrite each other's history. Without a file they are kept for this run only.
+// Keys bound to a model (Alt-0..Alt-3 by default) end the prompt like Enter; the REPL then finds
+// the model in `take_model_switch` and switches instead of sending the line.
+use crate::completion::CommandCompleter;
+use rustyline::error::ReadlineError;
+use rustyline::{Cmd, ConditionalEventHandler, Editor, Event, EventContext, EventHandler, KeyCode, KeyEvent, Modifiers, RepeatCount};
+use std::path::PathBuf;
+use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tokio::sync::mpsc;
+
+enum Request {
+    Read {
+        prompt: String,
+        initial: String,
+        multi_line: bool,
+        fence_lang: Option<String>,
+    },
+    History(String),
+}
+
+pub struct LineReader {
+    requests: std_mpsc::Sender<Request>,
+    lines: mpsc::Receiver<Result<String, ReadlineError>>,
+    pending: bool, // a prompt is showing and its line hasn't been taken yet
+    model_switch: Arc<Mutex<Option<String>>>, // set by a model key before its line is sent
+}
+
+struct ModelKey {
+    model: String,
+    switch: Arc<Mutex<Option<String>>>,
+}
+
+impl ConditionalEventHandler for ModelKey {
+    fn handle(&self, _: &Event, _: RepeatCount, _: bool, _: &EventContext) -> Option<Cmd> {
+        *self.switch.lock().unwrap() = Some(self.model.clone());
+        Some(Cmd::AcceptLine)
+    }
+}
+
+// "alt-0", "ctrl-k", "f5"
+pub fn parse_key(spec: &str) -> Option<KeyEvent> {
+    let spec = spec.trim().to_lowercase();
+    if let Some(n) = spec.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return (1..=12).contains(&n).then_some(KeyEvent(KeyCode::F(n), Modifiers::NONE));
+    }
+    let (modifiers, key) = if let Some(key) = spec.strip_prefix("alt-") {
+        (Modifiers::ALT, key)
+    } else if let Some(key) = spec.strip_prefix("ctrl-") {
+        (Modifiers::CTRL, key)
+    } else {
+        return None;
+    };
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_alphanumeric() => Some(KeyEvent(KeyCode::Char(c), modifiers)),
+        _ => None,
+    }
+}
+
+impl LineReader {
+    pub fn spawn(
+        mut editor: Editor<CommandCompleter>,
+        history_file: Option<PathBuf>,
+        model_keys: Vec<(KeyEvent, String)>,
+    ) -> Self {
+        let model_switch = Arc::new(Mutex::new(None));
+        for (key, model) in model_keys {
+            let handler = ModelKey {
+                model,
+                switch: model_switch.clone(),
+            };
+            editor.bind_sequence(key, EventHandler::Conditional(Box::new(handler)));
+        }
+        let (requests, request_rx) = std_mpsc::channel::<Request>();
+        let (line_tx, lines) = mpsc::channel(1);
+        // Ends when the LineReader is dropped; a readline still waiting then dies with the process
+        thread::spawn(move || {
+            while let Ok(request) = request_rx.recv() {
+                match request {
+                    Request::Read {
+                        prompt,
+                        initial,
+                        multi_line,
+                        fence_lang,
+                    } => {
+                        if let Some(helper) = editor.helper_mut() {
+                            helper.multi_line = multi_line;
+                            helper.fence_lang = fence_lang;
+                        }
+                        let line = editor.readline_with_initial(&prompt, (&initial, ""));
+                        if line_tx.blocking_send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Request::History(line) => {
+                        let added = editor.add_history_entry(line);
+                        if let Some(history_file) = history_file.as_ref().filter(|_| added) {
+                            if let Err(e) = editor.append_history(history_file) {
+                                eprintln!("Failed to save history to '{}': {}", history_file.display(), e);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+        LineReader {
+            requests,
+            lines,
+            pending: false,
+            model_switch,
+        }
+    }
+
+    // Show a prompt, unless the previous one is still waiting for its line (e.g. after a mic.md
+    // answer was printed underneath it)
+    pub fn request(&mut self, prompt: String, initial: String, multi_line: bool, fence_lang: Option<String>) {
+        if self.pending {
+            return;
+        }
+        let request = Request::Read {
+            prompt,
+            initial,
+            multi_line,
+            fence_lang,
+        };
+        self.pending = self.requests.send(request).is_ok();
+    }
+
+    // The line for the last prompt; cancel-safe, so it can sit in a select! arm.
+    // A gone input thread reads as end of input.
+    pub async fn next(&mut self) -> Result<String, ReadlineError> {
+        let line = self.lines.recv().await.unwrap_or(Err(ReadlineError::Eof));
+        self.pending = false;
+        line
+    }
+
+    // The model whose key ended the last line; that line is unfinished input, not a message
+    pub fn take_model_switch(&self) -> Option<String> {
+        self.model_switch.lock().unwrap().take()
+    }
+
+    pub fn add_history_entry(&self, line: &str) {
+        let _ = self.requests.send(Request::History(line.to_string()));
+    }
+}
@@ -0,0 +1,130 @@
+// local.rs
+// Offline inference for `local:<path.gguf>` models: a llama.cpp `llama-server` is started on
+// demand and reached through its OpenAI-compatible API.
+use crate::config::Config;
+use genai::adapter::AdapterKind;
+use genai::resolver::{AuthData, Endpoint};
+use genai::{ModelIden, ServiceTarget};
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+pub const MODEL_PREFIX: &str = "local:";
+
+// Loading a large model can take a while
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(180);
+
+struct LocalServer {
+    model_path: String,
+    port: u16,
+    child: Child,
+}
+
+static SERVER_COMMAND: OnceLock<(String, Vec<String>)> = OnceLock::new();
+static SERVER: Mutex<Option<LocalServer>> = Mutex::new(None);
+
+// `llama_server` / `llama_server_args` from config; call once at startup
+pub fn init(config: &Config) {
+    let command = config.llama_server.clone().unwrap_or_else(|| "llama-server".to_string());
+    let args = config
+        .llama_server_args
+        .as_deref()
+        .map(|a| a.split_whitespace().map(String::from).collect())
+        .unwrap_or_default();
+    let _ = SERVER_COMMAND.set((command, args));
+}
+
+fn free_port() -> std::io::Result<u16> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+// Start (or reuse) the server for `model` and wait until it reports healthy
+pub async fn ensure_server(model: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let model_path = model.strip_prefix(MODEL_PREFIX).unwrap_or(model);
+    let port = {
+        let mut server = SERVER.lock().unwrap();
+        if let Some(running) = server.as_mut() {
+            if running.model_path == model_path && running.child.try_wait()?.is_none() {
+                return Ok(());
+            }
+        }
+        if let Some(mut old) = server.take() {
+            let _ = old.child.kill();
+            let _ = old.child.wait();
+        }
+        if !Path::new(model_path).is_file() {
+            return Err(format!("Local model '{}' not found", model_path).into());
+        }
+        let (command, args) = SERVER_COMMAND
+            .get()
+            .cloned()
+            .unwrap_or_else(|| ("llama-server".to_string(), Vec::new()));
+        let port = free_port()?;
+        tracing::debug!(%command, model = model_path, port, ?args, "starting llama-server");
+        let child = Command::new(&command)
+            .args(["-m", model_path, "--host", "127.0.0.1", "--port", &port.to_string()])
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start '{}' (set llama_server in config): {}", command, e))?;
+        *server = Some(LocalServer {
+            model_path: model_path.to_string(),
+            port,
+            child,
+        });
+        port
+    };
+
+    eprintln!("Loading {} ...", model_path);
+    let health = format!("http://127.0.0.1:{}/health", port);
+    let http = reqwest::Client::new();
+    let started = Instant::now();
+    loop {
+        if http.get(&health).send().await.is_ok_and(|r| r.status().is_success()) {
+            return Ok(());
+        }
+        if let Some(server) = SERVER.lock().unwrap().as_mut() {
+            if let Some(status) = server.child.try_wait()? {
+                return Err(format!("llama-server exited while loading '{}' ({})", model_path, status).into());
+            }
+        }
+        if started.elapsed() > STARTUP_TIMEOUT {
+            shutdown();
+            return Err(format!("llama-server did not become ready within {}s", STARTUP_TIMEOUT.as_secs()).into());
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+}
+
+// Used by the client's ServiceTargetResolver once ensure_server() has run
+pub fn resolve(target: &ServiceTarget) -> Option<ServiceTarget> {
+    let model_path = target.model.model_name.strip_prefix(MODEL_PREFIX)?;
+    let port = SERVER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|s| s.model_path == model_path)?
+        .port;
+    let name = Path::new(model_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(model_path)
+        .to_string();
+    Some(ServiceTarget {
+        endpoint: Endpoint::from_owned(format!("http://127.0.0.1:{}/v1/", port)),
+        auth: AuthData::from_single("local"),
+        model: ModelIden::new(AdapterKind::OpenAI, name),
+    })
+}
+
+// Stop the server, if one was started
+pub fn shutdown() {
+    if let Some(mut server) = SERVER.lock().unwrap().take() {
+        let _ = server.child.kill();
+        let _ = server.child.wait();
+    }
+}
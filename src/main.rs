@@ -7,21 +7,60 @@ use genai::{Client, ServiceTarget};
 use std::env;
 use std::fs;
 use std::path::Path;
-use std::path::PathBuf;
 
+mod attachments;
+mod bedrock;
 mod chat_session;
 mod cli;
+mod command;
 mod completion;
+mod condense;
 mod config;
+mod config_schema;
+mod dry_run;
+mod endpoints;
+mod i18n;
+mod import;
+mod ingest;
+mod input_source;
 mod interactive;
+mod latex;
+mod line_reader;
+mod local;
+mod memory;
 mod mic;
+mod mirror;
+mod offline;
+mod ocr;
+mod output;
+mod plugins;
+mod preferences;
+mod rate_limit;
+mod redact;
+mod routing;
+mod sanitize;
+mod session_browser;
+mod snippets;
 mod tools;
+mod trace;
+mod transcribe;
+mod transcript;
+mod typeahead;
+mod ui;
+mod usage;
+mod word_diff;
 mod sse_event;
+#[cfg(test)]
+mod test_support;
 mod markdown_render;
 
-use cli::{execute_query, list_models, Cli, Commands, DEFAULT_MODEL};
-use config::{load_config, save_config, Config};
+use cli::{
+    error_message, execute_query, list_models, resolve_system_prompt, Cli, Commands, ConfigCommand, QueryError, SessionsCommand, SyncCommand,
+    UsageCommand, DEFAULT_MODEL,
+};
+use config::{load_config, Config};
 use interactive::interactive_mode;
+use ui::theme;
 
 const BANNER: &str = r#"                   _           
       ___ ___   __| | ___ _ __  2o25
@@ -30,10 +69,87 @@ const BANNER: &str = r#"                   _
      \___\___/ \__,_|\___|_|  󰘦  󰊠  ● ● ● 
 "#;
 
+// Exit codes for automation; anything not classified below exits with 1
+const EXIT_PROVIDER_ERROR: i32 = 2;
+const EXIT_AUTH_ERROR: i32 = 3;
+const EXIT_INPUT_ERROR: i32 = 4;
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // --help / --version are not failures
+        Err(e) if !e.use_stderr() => e.exit(),
+        Err(e) => {
+            let _ = e.print();
+            std::process::exit(EXIT_INPUT_ERROR);
+        }
+    };
+    let result = run(cli).await;
+    local::shutdown();
+    if let Err(e) = result {
+        // A dry run stops at the first request; printing it was the point
+        if matches!(e.downcast_ref::<QueryError>(), Some(QueryError::DryRun)) {
+            return;
+        }
+        let label = theme::BAD.paint_err(format!("{}:", i18n::t("error")));
+        eprintln!("{} {}", label, error_message(e.as_ref()));
+        std::process::exit(exit_code(e.as_ref()));
+    }
+}
+
+fn exit_code(err: &(dyn std::error::Error + 'static)) -> i32 {
+    if let Some(query_err) = err.downcast_ref::<QueryError>() {
+        return match query_err {
+            QueryError::NoAnswer | QueryError::Timeout(_) => EXIT_PROVIDER_ERROR,
+            QueryError::Input(_) => EXIT_INPUT_ERROR,
+            QueryError::DryRun => 0,
+        };
+    }
+    if let Some(genai_err) = err.downcast_ref::<genai::Error>() {
+        return match genai_err {
+            genai::Error::RequiresApiKey { .. }
+            | genai::Error::NoAuthResolver { .. }
+            | genai::Error::NoAuthData { .. }
+            | genai::Error::Resolver {
+                resolver_error: genai::resolver::Error::ApiKeyEnvNotFound { .. },
+                ..
+            } => EXIT_AUTH_ERROR,
+            genai::Error::WebModelCall { webc_error, .. } | genai::Error::WebAdapterCall { webc_error, .. } => {
+                match webc_error {
+                    genai::webc::Error::ResponseFailedStatus { status, .. }
+                        if status.as_u16() == 401 || status.as_u16() == 403 =>
+                    {
+                        EXIT_AUTH_ERROR
+                    }
+                    _ => EXIT_PROVIDER_ERROR,
+                }
+            }
+            _ => EXIT_PROVIDER_ERROR,
+        };
+    }
+    1
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    config::set_profile(cli.profile.clone());
+    // Before load_config, which refuses an invalid file
+    if let Some(Commands::Config {
+        action: ConfigCommand::Validate,
+    }) = &cli.command
+    {
+        return validate_config();
+    }
     let config = load_config()?;
-    let cli = Cli::parse();
+    config::load_env_file(&config)?;
+    output::set_quiet(cli.quiet);
+    output::set_raw(cli.raw);
+    output::set_accessible(cli.accessible || config.accessible.unwrap_or(false));
+    dry_run::set_enabled(cli.dry_run);
+    offline::set_enabled(cli.offline || config.offline.unwrap_or(false));
+    trace::init(cli.verbose);
+    ingest::set_include_hidden(cli.include_hidden);
+    cli::set_request_timeout(cli.timeout.or(config.request_timeout));
     let default_temp_dir = env::temp_dir();
 
     // Resolve global defaults
@@ -44,60 +160,60 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .map(|s| s.to_string())
         .unwrap_or_else(|| DEFAULT_MODEL.to_string());
     let global_stream = cli.stream.or(config.stream).unwrap_or(false);
-    let user_prompt = env::var("USER_PROMPT").unwrap_or("\x1b[93m>\x1b[0m".to_string());
-
-    // Define default alias models
-    let default_models = [
-        ("grok-2".to_string(), config.zero_alias.as_ref()),
-        ("gemini-2.0-flash".to_string(), config.one_alias.as_ref()),
-        ("phi4:14b".to_string(), config.two_alias.as_ref()),
-        ("gemma3:12b".to_string(), config.three_alias.as_ref()),
-    ];
-
-    // Resolve alias models (use config if set, otherwise default)
-    let alias_models: Vec<&String> = default_models
-        .iter()
-        .map(|(default, config_alias)| config_alias.unwrap_or(default))
-        .collect();
-
-    // Custom resolver for unsupported models
+    let user_prompt = env::var("USER_PROMPT").unwrap_or_else(|_| theme::ACCENT.paint(">"));
+
+    let alias_models = config::alias_models(&config);
+
+    endpoints::init(&config);
+    local::init(&config);
+    routing::init_auto_router(&config);
+    config::init_dirs(&config);
+    usage::init(&config);
+    rate_limit::init(&config);
+    redact::init(&config)?;
+    sanitize::init(&config)?;
+    i18n::init(&config)?;
+
+    // Custom resolver for unsupported models; in offline mode a cloud target is refused here, before
+    // any connection is made
     let target_resolver = ServiceTargetResolver::from_resolver_fn(
         |service_target: ServiceTarget| -> Result<ServiceTarget, genai::resolver::Error> {
-            if service_target.model.model_name.to_string() == "qwen-max" {
-                let endpoint =
-                    Endpoint::from_static("https://dashscope.aliyuncs.com/compatible-mode/v1/");
-                let auth = AuthData::from_env("QWEN_API_KEY");
-                let model = ModelIden::new(AdapterKind::OpenAI, "deepseek-r1-distill-qwen-32b");
-                Ok(ServiceTarget {
-                    endpoint,
-                    auth,
-                    model,
-                })
-            } else {
-                Ok(service_target)
+            let model = service_target.model.model_name.to_string();
+            let target = resolve_target(service_target)?;
+            if offline::is_enabled() && !offline::is_local(target.endpoint.base_url()) {
+                return Err(genai::resolver::Error::Custom(offline::refuse_model(&model, target.endpoint.base_url())));
             }
+            Ok(target)
         },
     );
 
-    // Build client with the custom resolver
+    // Build client with the custom resolver; other HTTP requests share its proxy and certificates
+    let http = build_http_client(&config, cli.no_proxy)?;
+    endpoints::set_http_client(http.clone());
     let client = Client::builder()
+        .with_reqwest(http.clone())
         .with_service_target_resolver(target_resolver)
         .build();
 
-    // Print the banner only if the `banner` flag is enabled
-    if cli.banner
-        && !matches!(cli.command, Some(Commands::Query { .. }))
-        && !matches!(cli.command, Some(Commands::BuildRelease { .. }))
-    {
+    if shows_banner(&cli) {
         println!("{}", BANNER);
     }
 
     // Handle the case where a direct query is provided without a subcommand
-    if cli.command.is_none() && cli.query.is_some() {
-        let question = cli.query.unwrap().join(" ");
-        let model = cli.model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    // (or just --ocr <image>, asking about the text in it)
+    if cli.command.is_none() && (cli.query.is_some() || cli.ocr.is_some()) {
+        let mut question = cli.query.as_deref().unwrap_or_default().join(" ");
+        if let Some(image) = &cli.ocr {
+            let text = ocr::read_image(&client, &config, Path::new(image)).await?;
+            question = ocr::with_image_text(image, &text, &question);
+        }
+        if cli.ctx {
+            question = tools::ctx::with_context(&tools::ctx::load_or_build()?, &question);
+        }
+        let model = cli.model.clone().unwrap_or_else(|| DEFAULT_MODEL.to_string());
         let stream = cli.stream.or(config.stream).unwrap_or(false);
-        execute_query(&client, &model, &question, stream, false).await?;
+        let system_prompt = resolve_system_prompt(cli.system.clone(), cli.role.clone())?;
+        execute_query(&client, &model, &question, stream, false, system_prompt.as_deref()).await?;
         return Ok(());
     }
 
@@ -111,22 +227,42 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             file,
             stream,
             model,
+            system,
+            role,
+            ctx,
+            ocr,
         }) => {
             let model = model.unwrap_or(global_model);
             let stream = stream.unwrap_or(global_stream);
-            let question = resolve_question(question, file)?;
-            println!("Using model: \x1b[93m{}\x1b[0m", model);
-            println!("Stream: \x1b[93m{}\x1b[0m", stream);
-            execute_query(&client, &model, &question, stream, false).await?;
+            let ocr = ocr.or(cli.ocr.clone());
+            let mut question = match (&ocr, &question, &file) {
+                (Some(_), None, None) => String::new(),
+                _ => resolve_question(&client, &model, question, file).await?,
+            };
+            if let Some(image) = &ocr {
+                let text = ocr::read_image(&client, &config, Path::new(image)).await?;
+                question = ocr::with_image_text(image, &text, &question);
+            }
+            if ctx || cli.ctx {
+                question = tools::ctx::with_context(&tools::ctx::load_or_build()?, &question);
+            }
+            // Flags on the subcommand win over the top-level ones
+            let system_prompt = match (system, role) {
+                (None, None) => resolve_system_prompt(cli.system.clone(), cli.role.clone())?,
+                (system, role) => resolve_system_prompt(system, role)?,
+            };
+            print_query_settings(&model, stream);
+            execute_query(&client, &model, &question, stream, false, system_prompt.as_deref()).await?;
         }
         Some(Commands::SetDefault { model }) => {
-            let new_config = Config {
-                default_model: Some(model.clone()),
-                stream: cli.stream,
-                ..config
-            };
-            save_config(&new_config)?;
-            println!("Default model set to {}", model);
+            config::save_settings(&[
+                ("default_model", Some(toml::Value::String(model.clone()))),
+                ("stream", cli.stream.map(toml::Value::Boolean)),
+            ])?;
+            match config::active_profile() {
+                Some(profile) => println!("Default model for profile '{}' set to {}", profile, model),
+                None => println!("Default model set to {}", model),
+            }
         }
         Some(Commands::Zero { question, stream }) => {
             handle_alias_command(&client, &alias_models[0], question, stream, global_stream, &user_prompt, &config, &default_temp_dir).await?;
@@ -140,17 +276,119 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(Commands::Three { question, stream }) => {
             handle_alias_command(&client, &alias_models[3], question, stream, global_stream, &user_prompt, &config, &default_temp_dir).await?;
         }
-        Some(Commands::BuildRelease { stream, question }) => {
+        Some(Commands::BuildRelease { stream, question, fix_loop }) => {
             // check if Cargo.toml is present
             if !Path::new("Cargo.toml").exists() {
-                return Err("Cargo build needs Cargo.toml file present".into());
+                return Err(QueryError::Input("Cargo build needs Cargo.toml file present".to_string()).into());
             }
             let stream = stream.unwrap_or(global_stream);
-            tools::build_release::handle_build_release(&client, &global_model, stream, question).await?;
+            tools::build_release::handle_build_release(&client, &global_model, stream, question, fix_loop).await?;
+        }
+        Some(Commands::Transcribe { file, model, ask, stream }) => {
+            let stream = stream.unwrap_or(global_stream);
+            transcribe::handle_transcribe(&client, &global_model, &config, &file, model, ask, stream).await?;
+        }
+        Some(Commands::Rewrite {
+            style,
+            file,
+            text,
+            diff,
+            model,
+        }) => {
+            let model = model.unwrap_or(global_model);
+            tools::rewrite::handle_rewrite(&client, &model, &style, file, text, diff).await?;
+        }
+        Some(Commands::Translate {
+            to,
+            file,
+            text,
+            model,
+            stream,
+        }) => {
+            let model = model.unwrap_or(global_model);
+            let stream = stream.unwrap_or(global_stream);
+            tools::translate::handle_translate(&client, &model, &to, file, text, stream).await?;
+        }
+        Some(Commands::Summarize {
+            file,
+            length,
+            output,
+            model,
+            stream,
+        }) => {
+            let model = model.unwrap_or(global_model);
+            let stream = stream.unwrap_or(global_stream);
+            tools::summarize::handle_summarize(&client, &http, &config, &model, file, &length, output, stream).await?;
+        }
+        Some(Commands::Explain {
+            question,
+            model,
+            stream,
+            command,
+        }) => {
+            let model = model.unwrap_or(global_model);
+            let stream = stream.unwrap_or(global_stream);
+            tools::explain::handle_explain(&client, &model, stream, command, question).await?;
+        }
+        Some(Commands::Ctx { globs, budget, print }) => tools::ctx::handle_ctx(globs, budget, print)?,
+        Some(Commands::Watch { file, model, stream }) => {
+            let model = model.unwrap_or(global_model);
+            tools::watch::handle_watch(&client, &model, &file, stream.unwrap_or(global_stream)).await?;
+        }
+        Some(Commands::Pipe { input, output }) => {
+            tools::pipe::handle_pipe(&client, &global_model, &config, Path::new(&input), Path::new(&output)).await?;
+        }
+        Some(Commands::Extract {
+            fields,
+            file,
+            format,
+            model,
+        }) => {
+            let model = model.unwrap_or(global_model);
+            tools::extract::handle_extract(&client, &model, &fields, file.as_deref(), &format).await?;
+        }
+        Some(Commands::Ping { models }) => {
+            tools::ping::handle_ping(&client, &http, &config, &global_model, models).await?
+        }
+        Some(Commands::Bench {
+            models,
+            prompt,
+            prompt_file,
+            runs,
+            json,
+        }) => tools::bench::handle_bench(&client, &models, prompt, prompt_file, runs, json).await?,
+        Some(Commands::Filter {
+            prompt,
+            jsonl,
+            concurrency,
+            model,
+        }) => {
+            let model = model.unwrap_or(global_model);
+            let system_prompt = resolve_system_prompt(cli.system.clone(), cli.role.clone())?;
+            tools::filter::handle_filter(&client, &model, &prompt, system_prompt.as_deref(), jsonl, concurrency).await?;
+        }
+        Some(Commands::Script { file, args }) => {
+            tools::script::handle_script(&client, &global_model, &file, args).await?;
         }
+        Some(Commands::Sessions { action }) => match action {
+            SessionsCommand::Prune {
+                older_than,
+                archive,
+                dry_run,
+            } => tools::sessions::handle_prune(&older_than, archive, dry_run)?,
+        },
+        Some(Commands::Replay { session, speed }) => tools::replay::handle_replay(&session, speed.as_deref()).await?,
+        Some(Commands::Sync { action }) => match action {
+            SyncCommand::Push => tools::sync::handle_push(&config).await?,
+            SyncCommand::Pull => tools::sync::handle_pull(&config).await?,
+        },
+        Some(Commands::Usage { action }) => match action {
+            UsageCommand::Ratings => usage::print_ratings()?,
+        },
+        Some(Commands::Config { .. }) => {} // handled before the config is loaded
         Some(Commands::Interactive) | None => {
             let temp_dir = resolve_temp_dir(&config, &default_temp_dir);
-            interactive_mode(&client, &global_model, global_stream, &user_prompt, temp_dir).await?;
+            interactive_mode(&client, &global_model, global_stream, &user_prompt, temp_dir, &config).await?;
         }
         Some(Commands::Quit) => {}
     }
@@ -158,54 +396,187 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+// The banner (`banner` flag) goes only in front of the REPL: no subcommand and no query,
+// `interactive` or a model alias; every other subcommand (watch re-runs included) prints output of its own
+fn shows_banner(cli: &Cli) -> bool {
+    let starts_repl = match &cli.command {
+        None => cli.query.is_none() && cli.ocr.is_none(),
+        Some(
+            Commands::Interactive
+            | Commands::Zero { .. }
+            | Commands::One { .. }
+            | Commands::Two { .. }
+            | Commands::Three { .. },
+        ) => true,
+        Some(_) => false,
+    };
+    cli.banner && !cli.quiet && !cli.raw && starts_repl
+}
+
+fn validate_config() -> Result<(), Box<dyn std::error::Error>> {
+    let path = config::get_config_file_path();
+    match fs::read_to_string(&path) {
+        Ok(text) => {
+            let parsed = config_schema::parse(&path, &text)?;
+            if let Some(profile) = config::active_profile() {
+                config_schema::apply_profile(parsed, profile, &config::get_profile_file_path(profile))?;
+            }
+            println!("'{}' is valid.", path.display());
+        }
+        Err(_) => println!("No config file at '{}'; defaults are used.", path.display()),
+    }
+    Ok(())
+}
+
+// Where a model's requests go: [[endpoints]], the local llama-server, qwen-max, or genai's default
+fn resolve_target(service_target: ServiceTarget) -> Result<ServiceTarget, genai::resolver::Error> {
+    // `<name>/<model>` for [[endpoints]] from config
+    if let Some(target) = endpoints::resolve(&service_target) {
+        return Ok(target);
+    }
+    // `local:<path.gguf>` through the llama-server started by local::ensure_server
+    if let Some(target) = local::resolve(&service_target) {
+        tracing::debug!(model = %service_target.model.model_name, "resolver: local llama-server");
+        return Ok(target);
+    }
+    if service_target.model.model_name.to_string() == "qwen-max" {
+        tracing::debug!("resolver: qwen-max via dashscope (QWEN_API_KEY)");
+        let endpoint =
+            Endpoint::from_static("https://dashscope.aliyuncs.com/compatible-mode/v1/");
+        let auth = AuthData::from_env("QWEN_API_KEY");
+        let model = ModelIden::new(AdapterKind::OpenAI, "deepseek-r1-distill-qwen-32b");
+        Ok(ServiceTarget {
+            endpoint,
+            auth,
+            model,
+        })
+    } else {
+        tracing::debug!(
+            model = %service_target.model.model_name,
+            adapter = ?service_target.model.adapter_kind,
+            "resolver: genai default for the adapter"
+        );
+        Ok(service_target)
+    }
+}
+
+// HTTP client shared by all providers: proxy and extra CA certificates from config
+fn build_http_client(config: &Config, no_proxy: bool) -> Result<reqwest::Client, Box<dyn std::error::Error>> {
+    let mut builder = reqwest::Client::builder();
+    if no_proxy {
+        builder = builder.no_proxy();
+    } else if let Some(url) = &config.proxy_url {
+        let proxy = reqwest::Proxy::all(url)
+            .map_err(|e| anyhow::anyhow!("Invalid proxy_url '{}': {}", url, e))?
+            // Local servers (Ollama) are always reached directly
+            .no_proxy(reqwest::NoProxy::from_string("localhost,127.0.0.1,::1"));
+        builder = builder.proxy(proxy);
+    }
+    if let Some(path) = &config.ca_cert {
+        let pem = fs::read(path).map_err(|e| anyhow::anyhow!("Failed to read ca_cert '{}': {}", path, e))?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| anyhow::anyhow!("Invalid ca_cert '{}': {}", path, e))?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    Ok(builder.build()?)
+}
+
 // Helper function to handle alias commands (Zero, One, Two)
+#[allow(clippy::too_many_arguments)]
 async fn handle_alias_command(
     client: &Client,
-    model: &String,
+    model: &str,
     question: Option<String>,
     stream: Option<bool>,
     global_stream: bool,
     user_prompt: &str,
     config: &Config,
-    default_temp_dir: &PathBuf,
+    default_temp_dir: &Path,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let stream = stream.unwrap_or(global_stream);
     let temp_dir = resolve_temp_dir(config, default_temp_dir);
     match question {
         Some(q) => {
-            println!("Using model: \x1b[93m{}\x1b[0m", model);
-            println!("Stream: \x1b[93m{}\x1b[0m", stream);
-            execute_query(client, model, &q, stream, false).await?;
+            print_query_settings(model, stream);
+            execute_query(client, model, &q, stream, false, None).await?;
         }
         None => {
-            interactive_mode(client, model, stream, user_prompt, temp_dir).await?;
+            interactive_mode(client, model, stream, user_prompt, temp_dir, config).await?;
         }
     }
     Ok(())
 }
 
-// Helper function to resolve question from either text or file
-fn resolve_question(question: Option<String>, file: Option<String>) -> Result<String, Box<dyn std::error::Error>> {
+fn print_query_settings(model: &str, stream: bool) {
+    if output::is_quiet() {
+        return;
+    }
+    println!("Using model: {}", theme::ACCENT.paint(model));
+    println!("Stream: {}", theme::ACCENT.paint(stream));
+}
+
+// Helper function to resolve question from either text or file; files too long for the model
+// are condensed first
+async fn resolve_question(
+    client: &Client,
+    model: &str,
+    question: Option<String>,
+    file: Option<String>,
+) -> Result<String, Box<dyn std::error::Error>> {
     match (question, file) {
         (Some(q), None) => {
-            println!("Question: \x1b[93m{}\x1b[0m", q);
+            if !output::is_quiet() && !output::is_raw() {
+                println!("Question: {}", theme::ACCENT.paint(&q));
+            }
             Ok(q)
         }
         (None, Some(file_path)) => {
-            let content = fs::read_to_string(&file_path)
-                .map_err(|e| anyhow::anyhow!("Failed to read file: {}", e))?;
-            let preview = content.lines().take(3).collect::<Vec<&str>>().join("\n");
-            println!("File preview (up to 3 lines):\n\x1b[93m{}\x1b[0m", preview);
-            Ok(content)
+            let content = ingest::read_path(Path::new(&file_path)).map_err(QueryError::Input)?;
+            if !output::is_quiet() && !output::is_raw() {
+                let preview = content.lines().take(3).collect::<Vec<&str>>().join("\n");
+                println!("File preview (up to 3 lines):\n{}", theme::ACCENT.paint(preview));
+            }
+            condense::fit(client, model, &file_path, content, None).await
+        }
+        (Some(q), Some(file_path)) => {
+            let content = ingest::read_path(Path::new(&file_path)).map_err(QueryError::Input)?;
+            if !output::is_quiet() && !output::is_raw() {
+                let preview = content.lines().take(3).collect::<Vec<&str>>().join("\n");
+                println!("File preview (up to 3 lines):\n{}", theme::ACCENT.paint(preview));
+                println!("Question: {}", theme::ACCENT.paint(&q));
+            }
+            let content = condense::fit(client, model, &file_path, content, Some(&q)).await?;
+            Ok(input_source::with_file_context(&file_path, &content, &q))
         }
-        _ => Err(anyhow::anyhow!("Missing input: Either a question or a file is required.").into()),
+        _ => Err(QueryError::Input("Missing input: Either a question or a file is required.".to_string()).into()),
     }
 }
 
 // Helper function to resolve temp directory
-fn resolve_temp_dir<'a>(config: &'a Config, default_temp_dir: &'a PathBuf) -> &'a str {    
+fn resolve_temp_dir<'a>(config: &'a Config, default_temp_dir: &'a Path) -> &'a str {    
     config
         .temp_dir
         .as_deref()
         .unwrap_or_else(|| default_temp_dir.to_str().unwrap_or("./"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn banner_for(args: &[&str]) -> bool {
+        shows_banner(&Cli::try_parse_from([&["llm", "--banner"], args].concat()).unwrap())
+    }
+
+    #[test]
+    fn banner_only_in_front_of_the_repl() {
+        assert!(banner_for(&[]));
+        assert!(banner_for(&["interactive"]));
+        assert!(!banner_for(&["what", "is", "rust"]));
+        assert!(!banner_for(&["watch", "-f", "prompt.md"]));
+        assert!(!banner_for(&["pipe", "--in", "a.fifo", "--out", "b.fifo"]));
+        assert!(!shows_banner(&Cli::try_parse_from(["llm"]).unwrap()));
+    }
+}
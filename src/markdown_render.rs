@@ -1,6 +1,14 @@
-use crossterm::{
-    style::{self, SetForegroundColor},
-};
+use crate::latex::latex_to_unicode;
+use crate::output;
+use crate::ui::theme;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    // $$..$$, \(..\), \[..\] and $..$ (no space just inside the dollars, so prices like "$5 or $10" are left alone)
+    static ref INLINE_MATH: Regex =
+        Regex::new(r"\$\$(.+?)\$\$|\\\((.+?)\\\)|\\\[(.+?)\\\]|\$([^\s$](?:[^$]*?[^\s$\\])?)\$").unwrap();
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LineType {
@@ -13,6 +21,8 @@ pub enum LineType {
 pub struct MarkdownRender {
     prev_line_type: LineType,
     code_active: bool,
+    latex: bool,       // render $...$ / $$...$$ math as Unicode
+    math_active: bool, // inside a multi-line $$ / \[ block
 }
 
 impl MarkdownRender {
@@ -20,14 +30,24 @@ impl MarkdownRender {
         Self {
             prev_line_type: LineType::Normal,
             code_active: false,
+            latex: true,
+            math_active: false,
         }
     }
 
+    pub fn set_latex(&mut self, enabled: bool) {
+        self.latex = enabled;
+        self.math_active = false;
+    }
+
     pub fn render_line_mut(&mut self, line: &str) -> String {
         let (line_type, is_code) = self.check_line(line);
-        let output = if is_code {
-            format!("{}", SetForegroundColor(style::Color::Yellow)) + line
-                + &format!("{}", SetForegroundColor(style::Color::Reset))
+        let output = if output::is_raw() {
+            line.to_string()
+        } else if is_code {
+            theme::VALUE.paint(line)
+        } else if self.latex && line_type == LineType::Normal {
+            self.render_math(line)
         } else {
             line.to_string()
         };
@@ -36,6 +56,33 @@ impl MarkdownRender {
         output
     }
 
+    fn render_math(&mut self, line: &str) -> String {
+        let trimmed = line.trim();
+        // Delimiter lines of a display block are dropped from the output
+        if !self.math_active && (trimmed == "$$" || trimmed == "\\[") {
+            self.math_active = true;
+            return String::new();
+        }
+        if self.math_active {
+            if trimmed == "$$" || trimmed == "\\]" {
+                self.math_active = false;
+                return String::new();
+            }
+            return format!("    {}", style_math(&latex_to_unicode(trimmed)));
+        }
+        INLINE_MATH
+            .replace_all(line, |caps: &Captures| {
+                let whole = caps.get(0).unwrap();
+                // "$5-$10": a digit right after the closing dollar means currency, not math
+                if caps.get(4).is_some() && line[whole.end()..].starts_with(|c: char| c.is_ascii_digit()) {
+                    return whole.as_str().to_string();
+                }
+                let math = (1..=4).find_map(|i| caps.get(i)).map(|m| m.as_str()).unwrap_or("");
+                style_math(&latex_to_unicode(math))
+            })
+            .into_owned()
+    }
+
     fn check_line(&self, line: &str) -> (LineType, bool) {
         let mut line_type = self.prev_line_type;
         let mut is_code = self.code_active;
@@ -70,3 +117,26 @@ impl MarkdownRender {
         (line_type, is_code)
     }
 }
+
+fn style_math(text: &str) -> String {
+    theme::INFO.paint(text)
+}
+
+// Reasoning/thinking traces are printed dimmed and italic so they read apart from the answer
+pub fn render_reasoning_line(line: &str) -> String {
+    theme::REASONING.paint(line)
+}
+
+// Language of the ``` fence still open at the end of `lines` (empty string for a bare fence)
+pub fn current_fence_lang(lines: &[String]) -> Option<String> {
+    let mut lang = None;
+    for line in lines {
+        if let Some(rest) = line.trim_start().strip_prefix("```") {
+            lang = match lang {
+                None => Some(rest.trim().to_string()),
+                Some(_) => None,
+            };
+        }
+    }
+    lang
+}
@@ -0,0 +1,216 @@
+// memory.rs
+// Opt-in long-term memory (`memory = true`): short facts about the user in memory.jsonl in the
+// data dir. Facts are extracted from a conversation when the session ends; the ones closest to
+// the first question of a new session are added to its system prompt. Embeddings come from the
+// OpenAI-compatible /embeddings endpoint in `memory_embedding_url`; without one, facts are matched
+// by shared words.
+use crate::config::{get_data_dir, write_atomic, Config};
+use crate::endpoints;
+use crate::offline;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+const MEMORY_FILE: &str = "memory.jsonl";
+const OPENAI_HOST: &str = "https://api.openai.com/";
+const DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+// Memories added to one session's system prompt
+pub const RECALL_LIMIT: usize = 5;
+
+pub const EXTRACT_PROMPT: &str = "From the conversation below, list facts worth remembering about the user for \
+future conversations: preferences, their projects, tools and the names they use. One short, self-contained fact \
+per line, each starting with \"- \". Leave out anything that only matters for this conversation and anything \
+already known. Answer NONE if there is nothing worth remembering.";
+
+/// One remembered fact, stored as a line of JSON.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Memory {
+    pub id: u32,
+    pub text: String,
+    pub created: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+}
+
+struct Embedder {
+    http: reqwest::Client,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+pub struct MemoryStore {
+    path: PathBuf,
+    embedder: Option<Embedder>,
+}
+
+impl MemoryStore {
+    // None unless `memory = true`; embeddings only when `memory_embedding_url` is set. The key comes
+    // from `memory_embedding_api_key_env`; OPENAI_API_KEY is only ever sent to api.openai.com
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if !config.memory.unwrap_or(false) {
+            return None;
+        }
+        let embedder = config.memory_embedding_url.clone().map(|url| {
+            let api_key = match &config.memory_embedding_api_key_env {
+                Some(env) => std::env::var(env).ok(),
+                None if url.starts_with(OPENAI_HOST) => std::env::var("OPENAI_API_KEY").ok(),
+                None => None,
+            };
+            Embedder {
+                http: endpoints::http_client(),
+                url,
+                model: config
+                    .memory_embedding_model
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_EMBEDDING_MODEL.to_string()),
+                api_key,
+            }
+        })
+        // Offline, a cloud embedder is dropped and recall falls back to word matching
+        .filter(|embedder| !offline::is_enabled() || offline::is_local(&embedder.url));
+        let mut store = MemoryStore::new(get_data_dir().join(MEMORY_FILE));
+        store.embedder = embedder;
+        Some(store)
+    }
+
+    // A store at `path` that matches facts by shared words only
+    pub fn new(path: PathBuf) -> Self {
+        MemoryStore { path, embedder: None }
+    }
+
+    // Unreadable lines are skipped
+    pub fn load(&self) -> Vec<Memory> {
+        let Ok(data) = std::fs::read_to_string(&self.path) else {
+            return Vec::new();
+        };
+        data.lines().filter_map(|l| serde_json::from_str(l).ok()).collect()
+    }
+
+    // None when the same fact (ignoring case) is already stored
+    pub async fn add(&self, text: &str) -> Result<Option<Memory>, Box<dyn std::error::Error>> {
+        let text = text.trim();
+        let memories = self.load();
+        if memories.iter().any(|m| m.text.eq_ignore_ascii_case(text)) {
+            return Ok(None);
+        }
+        let memory = Memory {
+            id: memories.iter().map(|m| m.id).max().unwrap_or(0) + 1,
+            text: text.to_string(),
+            created: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            embedding: self.embed(text).await,
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&memory)?)?;
+        Ok(Some(memory))
+    }
+
+    pub fn remove(&self, id: u32) -> std::io::Result<bool> {
+        let memories = self.load();
+        let kept: Vec<&Memory> = memories.iter().filter(|m| m.id != id).collect();
+        if kept.len() == memories.len() {
+            return Ok(false);
+        }
+        let mut data = String::new();
+        for memory in kept {
+            data.push_str(&serde_json::to_string(memory)?);
+            data.push('\n');
+        }
+        write_atomic(&self.path, data.as_bytes())?;
+        Ok(true)
+    }
+
+    // The stored facts most related to `query`, best first
+    pub async fn recall(&self, query: &str, limit: usize) -> Vec<String> {
+        let memories = self.load();
+        if memories.is_empty() {
+            return Vec::new();
+        }
+        let query_embedding = self.embed(query).await;
+        let mut scored: Vec<(f32, &Memory)> = memories
+            .iter()
+            .map(|m| {
+                let score = match (&query_embedding, &m.embedding) {
+                    (Some(q), Some(e)) if q.len() == e.len() => cosine(q, e),
+                    _ => word_overlap(query, &m.text),
+                };
+                (score, m)
+            })
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().take(limit).map(|(_, m)| m.text.clone()).collect()
+    }
+
+    // Failures only lose the embedding; the fact falls back to word matching
+    async fn embed(&self, text: &str) -> Option<Vec<f32>> {
+        let embedder = self.embedder.as_ref()?;
+        let mut request = embedder
+            .http
+            .post(&embedder.url)
+            .json(&serde_json::json!({ "model": embedder.model, "input": text }));
+        if let Some(key) = &embedder.api_key {
+            request = request.bearer_auth(key);
+        }
+        let result = async {
+            let body: serde_json::Value = request.send().await?.error_for_status()?.json().await?;
+            Ok::<_, reqwest::Error>(body)
+        }
+        .await;
+        match result {
+            Ok(body) => serde_json::from_value(body.pointer("/data/0/embedding")?.clone()).ok(),
+            Err(e) => {
+                tracing::debug!(url = embedder.url, error = %e, "embedding request failed");
+                None
+            }
+        }
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn word_overlap(a: &str, b: &str) -> f32 {
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f32 / ((a.len() * b.len()) as f32).sqrt()
+}
+
+// "- fact" lines of an extraction answer; NONE gives nothing
+pub fn parse_facts(answer: &str) -> Vec<String> {
+    answer
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("- ").or_else(|| l.trim().strip_prefix("* ")))
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+// Appended to the system prompt of a session that recalled `facts`
+pub fn system_block(facts: &[String]) -> String {
+    let lines: Vec<String> = facts.iter().map(|f| format!("- {}", f)).collect();
+    format!("What you remember about the user from earlier conversations:\n{}", lines.join("\n"))
+}
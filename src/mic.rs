@@ -1,130 +1,418 @@
-use console::{Style, Term};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::{cursor, execute, terminal};
+use crate::config::Config;
+use crate::output;
+use crate::transcribe::Transcriber;
+use crate::ui::text_width;
+use crate::ui::theme;
 use hound::{WavSpec, WavWriter};
 use std::io::{self, Write};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::{Duration, Instant};
 
-pub fn mic_main() -> Result<bool, Box<dyn std::error::Error>> {
-    // Initialize CPAL host
-    let host = cpal::default_host();
+// Set while the recording UI owns the terminal so background printers (mic.md monitor) hold off
+static RECORDING: AtomicBool = AtomicBool::new(false);
 
-    // Get default input device
-    let device = host
-        .default_input_device()
-        .expect("No input device available");
-    //println!("Using input device: {}", device.name()?);
+pub fn is_recording() -> bool {
+    RECORDING.load(Ordering::Relaxed)
+}
 
-    // Configure audio stream
-    let config = device.default_input_config()?;
-    //println!("Input config: {:?}", config);
+// Restores the terminal even if recording bails out early with `?`
+struct RecordingGuard;
 
-    // Shared state for stopping the stream
-    let stop_flag = Arc::new(Mutex::new(false));
+impl RecordingGuard {
+    fn start() -> io::Result<Self> {
+        RECORDING.store(true, Ordering::Relaxed);
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), cursor::Hide)?;
+        Ok(RecordingGuard)
+    }
+}
 
-    // Setup terminal for capturing keypresses
-    let term = Term::stdout();
-    let stop_flag_clone = Arc::clone(&stop_flag);
-    std::thread::spawn(move || {
-        let _ = term.read_char(); // Wait for any key press
-        let mut stop = stop_flag_clone.lock().unwrap();
-        *stop = true;
-    });
+impl Drop for RecordingGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(
+            io::stdout(),
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            cursor::MoveToColumn(0),
+            cursor::Show
+        );
+        RECORDING.store(false, Ordering::Relaxed);
+    }
+}
 
-    // Start time for timeout
-    let start_time = Instant::now();
+/// Recording limits, read from `mic_*` keys in config.toml.
+#[derive(Debug, Clone)]
+pub struct MicOptions {
+    pub max_secs: u64,
+    pub silence_stop_ms: Option<u64>, // stop after this much silence once speech was heard
+    pub silence_threshold: f32,       // peak level treated as silence
+    pub sample_rate: u32,             // output WAV rate (16kHz suits whisper-style backends)
+}
 
-    // Create a WAV writer and wrap it in an Arc<Mutex<Option<...>>>
+impl Default for MicOptions {
+    fn default() -> Self {
+        MicOptions {
+            max_secs: 30,
+            silence_stop_ms: None,
+            silence_threshold: 0.02,
+            sample_rate: 16_000,
+        }
+    }
+}
+
+impl MicOptions {
+    pub fn from_config(config: &Config) -> Self {
+        let defaults = MicOptions::default();
+        MicOptions {
+            max_secs: config.mic_max_secs.unwrap_or(defaults.max_secs),
+            silence_stop_ms: config.mic_silence_stop_ms.filter(|ms| *ms > 0),
+            silence_threshold: config.mic_silence_threshold.unwrap_or(defaults.silence_threshold),
+            sample_rate: config.mic_sample_rate.unwrap_or(defaults.sample_rate),
+        }
+    }
+}
+
+// Linear-interpolation resampler, good enough for speech going to a transcription backend
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = from_rate as f64 / to_rate as f64;
+    let out_len = (samples.len() as f64 / ratio) as usize;
+    (0..out_len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let idx = pos as usize;
+            let frac = (pos - idx as f64) as f32;
+            let a = samples[idx];
+            let b = *samples.get(idx + 1).unwrap_or(&a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+// Write mono 16-bit PCM, the format most STT tools expect
+pub fn write_wav(path: &str, samples: &[f32], sample_rate: u32) -> Result<(), Box<dyn std::error::Error>> {
     let spec = WavSpec {
-        channels: config.channels(),
-        sample_rate: config.sample_rate().0,
-        bits_per_sample: 32,
-        sample_format: hound::SampleFormat::Float,
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
     };
-    let writer = WavWriter::create("/tmp/output.wav", spec)?;
-    let writer = Arc::new(Mutex::new(Some(writer)));
-
-    // Clone the writer for use in the audio callback
-    let writer_clone = Arc::clone(&writer);
-
-    // Define the audio callback function
-    let err_fn = |err| eprintln!("An error occurred on the input audio stream: {}", err);
-    let vu_meter = Arc::new(Mutex::new(0.0_f32));
-    let vu_meter_clone = Arc::clone(&vu_meter);
-
-    let stream = device.build_input_stream(
-        &config.into(),
-        move |data: &[f32], _: &cpal::InputCallbackInfo| {
-            // Lock the writer to write audio data to the WAV file
-            let mut writer_guard = writer_clone.lock().unwrap();
-            if let Some(writer) = writer_guard.as_mut() {
-                for &sample in data {
-                    writer.write_sample(sample).unwrap();
+    let mut writer = WavWriter::create(path, spec)?;
+    for &sample in samples {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+// Live input stream collecting mono samples at the device rate plus a peak level for the meter
+struct Recorder {
+    stream: cpal::Stream,
+    samples: Arc<Mutex<Vec<f32>>>,
+    vu_meter: Arc<Mutex<f32>>,
+    device_rate: u32,
+}
+
+impl Recorder {
+    fn start() -> Result<Self, Box<dyn std::error::Error>> {
+        // Initialize CPAL host
+        let host = cpal::default_host();
+
+        // Get default input device
+        let device = host
+            .default_input_device()
+            .ok_or("No input device available")?;
+
+        // Configure audio stream
+        let config = device.default_input_config()?;
+        let channels = config.channels() as usize;
+        let device_rate = config.sample_rate().0;
+
+        let samples = Arc::new(Mutex::new(Vec::<f32>::new()));
+        let samples_clone = Arc::clone(&samples);
+
+        // Define the audio callback function
+        let err_fn = |err| eprintln!("An error occurred on the input audio stream: {}", err);
+        let vu_meter = Arc::new(Mutex::new(0.0_f32));
+        let vu_meter_clone = Arc::clone(&vu_meter);
+
+        let stream = device.build_input_stream(
+            &config.into(),
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                // Mix interleaved frames down to mono
+                let mut buffer = samples_clone.lock().unwrap();
+                for frame in data.chunks(channels.max(1)) {
+                    buffer.push(frame.iter().sum::<f32>() / frame.len() as f32);
                 }
-            }
 
-            // Update VU meter
-            let max_sample = data.iter().map(|s| s.abs()).fold(0.0_f32, |a, b| a.max(b));
-            let mut vu = vu_meter_clone.lock().unwrap();
-            *vu = max_sample;
-        },
-        err_fn,
-        None,
-    )?;
-
-    stream.play()?;
-    //println!("Recording... Press ENTER to stop or wait for 30 seconds.");
-
-    // Main loop to monitor VU meter and handle stop condition
-    loop {
-        let vu_level = {
-            let vu = vu_meter.lock().unwrap();
-            *vu
-        };
-
-        // Determine color and label based on VU level
-        let (style, label) = if vu_level < 0.3 {
-            (Style::new().green(), " Low  ")
-        } else if vu_level < 0.7 {
-            (Style::new().yellow(), "Medium")
-        } else {
-            (Style::new().red(), " High ")
-        };
-
-        // Display colored VU meter
-        let bar = "=".repeat((vu_level * 50.0) as usize);
-        print!(
-            "\rVU Meter: [{}] {:.2} ({})",
-            style.apply_to(format!("{:<50}", bar)),
-            vu_level,
-            style.apply_to(label)
-        );
-        io::stdout().lock().flush().unwrap();
+                // Update VU meter
+                let max_sample = data.iter().map(|s| s.abs()).fold(0.0_f32, |a, b| a.max(b));
+                let mut vu = vu_meter_clone.lock().unwrap();
+                *vu = max_sample;
+            },
+            err_fn,
+            None,
+        )?;
+        stream.play()?;
 
-        // Check if stop flag is set or timeout reached
+        Ok(Recorder {
+            stream,
+            samples,
+            vu_meter,
+            device_rate,
+        })
+    }
+
+    fn level(&self) -> f32 {
+        *self.vu_meter.lock().unwrap()
+    }
+
+    // Take everything recorded so far
+    fn drain(&self) -> Vec<f32> {
+        std::mem::take(&mut *self.samples.lock().unwrap())
+    }
+}
+
+// Colored bar for the current peak level
+fn vu_bar(vu_level: f32) -> String {
+    // Determine color and label based on VU level
+    let (style, label) = if vu_level < 0.3 {
+        (theme::GOOD, " Low  ")
+    } else if vu_level < 0.7 {
+        (theme::VALUE, "Medium")
+    } else {
+        (theme::BAD, " High ")
+    };
+    let bar = "=".repeat(((vu_level.min(1.0)) * 30.0) as usize);
+    format!(
+        "[{}] {:.2} ({})",
+        style.paint(format!("{:<30}", bar)),
+        vu_level,
+        style.paint(label)
+    )
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    format!("{:02}:{:02}", elapsed.as_secs() / 60, elapsed.as_secs() % 60)
+}
+
+pub fn mic_main(options: &MicOptions) -> Result<bool, Box<dyn std::error::Error>> {
+    let recorder = Recorder::start()?;
+
+    // Start time for timeout
+    let start_time = Instant::now();
+
+    // Main loop: redraw the meter on a single line and poll keys (no blocking reader thread,
+    // so no keypress is left behind for the readline prompt)
+    let max_duration = Duration::from_secs(options.max_secs);
+    let mut heard_speech = false;
+    let mut last_sound = Instant::now();
+    // Said once instead of a meter redrawn ten times a second
+    let accessible = output::is_accessible();
+    if accessible {
+        println!("Recording, up to {}. Any key stops, Esc cancels.", format_elapsed(max_duration));
+    }
+    let guard = RecordingGuard::start()?;
+    let canceled = loop {
+        let vu_level = recorder.level();
         let elapsed = start_time.elapsed();
-        let should_stop = {
-            let stop = stop_flag.lock().unwrap();
-            *stop || elapsed >= Duration::from_secs(30)
-        };
+        if !accessible {
+            print!(
+                "\r\x1b[2K{} {}/{} {}  {}",
+                theme::BAD.paint("● REC"),
+                format_elapsed(elapsed),
+                format_elapsed(max_duration),
+                vu_bar(vu_level),
+                theme::DIM.paint("any key: stop, Esc: cancel")
+            );
+            io::stdout().lock().flush()?;
+        }
 
-        if should_stop {
-            break;
+        if elapsed >= max_duration {
+            break false;
+        }
+        // Silence detection only kicks in after something above the threshold was heard
+        if vu_level >= options.silence_threshold {
+            heard_speech = true;
+            last_sound = Instant::now();
+        } else if let Some(silence_ms) = options.silence_stop_ms {
+            if heard_speech && last_sound.elapsed() >= Duration::from_millis(silence_ms) {
+                break false;
+            }
         }
+        // Waiting on input doubles as the refresh interval
+        if event::poll(Duration::from_millis(100))? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                break key.code == KeyCode::Esc || ctrl_c;
+            }
+        }
+    };
+    let recorded = start_time.elapsed();
+    drop(guard);
 
-        // Sleep briefly to prevent busy-waiting
-        std::thread::sleep(Duration::from_millis(100));
+    // Stop the stream
+    drop(recorder.stream);
+
+    if canceled {
+        return Ok(false);
     }
 
-    // Stop the stream
-    drop(stream);
+    // Resample and write the WAV file
+    let samples = std::mem::take(&mut *recorder.samples.lock().unwrap());
+    let samples = resample(&samples, recorder.device_rate, options.sample_rate);
+    write_wav("/tmp/output.wav", &samples, options.sample_rate)?;
+    println!("Recorded {:.1}s", recorded.as_secs_f32());
+    Ok(true)
+}
+
+// Continuous dictation: every `chunk_secs` (or at a pause) the new audio is transcribed in the
+// background and appended to a live transcript. Enter returns the text, Esc cancels (None).
+pub fn dictate_main(
+    options: &MicOptions,
+    transcriber: Arc<Transcriber>,
+    chunk_secs: u64,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let recorder = Recorder::start()?;
+    let start_time = Instant::now();
+    let (tx, rx) = mpsc::channel::<(usize, Result<String, String>)>();
+    let mut chunks: Vec<Option<String>> = Vec::new();
+    let mut chunk_start = Instant::now();
+    let mut last_sound = Instant::now();
+    let mut heard_speech = false;
 
-    // Finalize the WAV file
-    {
-        let mut writer_guard = writer.lock().unwrap();
-        if let Some(writer) = writer_guard.take() {
-            writer.finalize()?;
+    // Hand the audio recorded since the last chunk to a transcription thread
+    let send_chunk = |recorder: &Recorder, chunks: &mut Vec<Option<String>>| {
+        let samples = resample(&recorder.drain(), recorder.device_rate, options.sample_rate);
+        if samples.is_empty() {
+            return;
         }
+        let index = chunks.len();
+        chunks.push(None);
+        let (tx, transcriber, sample_rate) = (tx.clone(), Arc::clone(&transcriber), options.sample_rate);
+        std::thread::spawn(move || {
+            let path = std::env::temp_dir().join(format!("dictate_{}_{}.wav", std::process::id(), index));
+            let result = write_wav(&path.to_string_lossy(), &samples, sample_rate)
+                .and_then(|_| transcriber.transcribe_file(&path))
+                .map_err(|e| e.to_string());
+            let _ = std::fs::remove_file(&path);
+            let _ = tx.send((index, result));
+        });
+    };
+    let transcript = |chunks: &[Option<String>]| {
+        chunks
+            .iter()
+            .flatten()
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let accessible = output::is_accessible();
+    if accessible {
+        println!("Dictating. Enter sends, Esc cancels.");
     }
-    Ok(true)
+    let guard = RecordingGuard::start()?;
+    let width = terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
+    let mut last_error: Option<String> = None;
+    let accepted = loop {
+        while let Ok((index, result)) = rx.try_recv() {
+            match result {
+                Ok(text) => chunks[index] = Some(text),
+                Err(e) => {
+                    chunks[index] = Some(String::new());
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        let vu_level = recorder.level();
+        if vu_level >= options.silence_threshold {
+            heard_speech = true;
+            last_sound = Instant::now();
+        }
+        // Cut a chunk on the interval, or early at a short pause after speech
+        let pause = heard_speech && last_sound.elapsed() >= Duration::from_millis(700);
+        if chunk_start.elapsed() >= Duration::from_secs(chunk_secs) || (pause && chunk_start.elapsed() >= Duration::from_secs(1)) {
+            if heard_speech {
+                send_chunk(&recorder, &mut chunks);
+            } else {
+                recorder.drain(); // nothing but silence, drop it
+            }
+            chunk_start = Instant::now();
+            heard_speech = false;
+        }
+
+        // Live partial transcript: keep the tail that fits on one line
+        if !accessible {
+            let pending = chunks.iter().filter(|c| c.is_none()).count();
+            let text = transcript(&chunks);
+            let status = format!(
+                "{} {} {}{} ",
+                theme::BAD.paint("● DICTATE"),
+                format_elapsed(start_time.elapsed()),
+                if pending > 0 { "… " } else { "" },
+                if vu_level >= options.silence_threshold { "▮" } else { "▯" },
+            );
+            let room = width.saturating_sub(30);
+            let tail = text_width::tail(&text, room);
+            print!("\r\x1b[2K{}{}", status, tail);
+            io::stdout().lock().flush()?;
+        }
+
+        if start_time.elapsed() >= Duration::from_secs(options.max_secs.max(chunk_secs) * 20) {
+            break true; // safety cap for a forgotten dictation
+        }
+        if event::poll(Duration::from_millis(100))? {
+            if let Event::Key(key) = event::read()? {
+                match key.code {
+                    KeyCode::Enter => break true,
+                    KeyCode::Esc => break false,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break false,
+                    _ => {}
+                }
+            }
+        }
+    };
+
+    if !accepted {
+        drop(guard);
+        return Ok(None);
+    }
+
+    // Transcribe the tail and wait for outstanding chunks
+    if heard_speech {
+        send_chunk(&recorder, &mut chunks);
+    }
+    drop(recorder.stream);
+    if accessible {
+        print!("Finishing transcription.\r\n");
+    } else {
+        print!("\r\x1b[2K{} finishing transcription…", theme::VALUE.paint("●"));
+    }
+    io::stdout().lock().flush()?;
+    while chunks.iter().any(|c| c.is_none()) {
+        match rx.recv_timeout(Duration::from_secs(60)) {
+            Ok((index, Ok(text))) => chunks[index] = Some(text),
+            Ok((index, Err(e))) => {
+                chunks[index] = Some(String::new());
+                last_error = Some(e);
+            }
+            Err(_) => break,
+        }
+    }
+    drop(guard);
+
+    if let Some(e) = last_error {
+        eprintln!("Transcription error: {}", e);
+    }
+    let text = transcript(&chunks);
+    Ok(if text.is_empty() { None } else { Some(text) })
 }
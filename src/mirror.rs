@@ -0,0 +1,62 @@
+// mirror.rs
+use crate::transcript::expand_home;
+use chrono::Local;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// `/mirror <path>`: answers are written to a file while they stream, so an editor or
+/// `tail -f` can watch them grow. Each exchange truncates the file unless `append` is set.
+#[derive(Clone)]
+pub struct ResponseMirror {
+    path: PathBuf,
+    append: bool,
+}
+
+impl ResponseMirror {
+    pub fn open(path: &Path, append: bool) -> std::io::Result<Self> {
+        let mirror = ResponseMirror {
+            path: expand_home(path),
+            append,
+        };
+        if let Some(parent) = mirror.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Fail now rather than on the first answer
+        OpenOptions::new().create(true).append(true).open(&mirror.path)?;
+        Ok(mirror)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_append(&self) -> bool {
+        self.append
+    }
+
+    // Start a new exchange: an empty file, or a new section at the end in append mode
+    pub fn begin(&self, model: &str, prompt: &str) {
+        let header = format!(
+            "## {} — {}\n\n> {}\n\n",
+            model,
+            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            prompt.lines().next().unwrap_or("")
+        );
+        let result = if self.append {
+            self.write(&format!("\n{}", header))
+        } else {
+            std::fs::write(&self.path, header)
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to write mirror {}: {}", self.path.display(), e);
+        }
+    }
+
+    // Open/append/close per chunk so watchers see every piece as soon as it arrives
+    pub fn write(&self, text: &str) -> std::io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(text.as_bytes())?;
+        file.flush()
+    }
+}
@@ -0,0 +1,116 @@
+// ocr.rs
+// `--ocr <image>`: the text in a screenshot or scan is read first and goes into the prompt as
+// text, so models without vision can answer about it too. A local OCR command (tesseract by
+// default) does the reading, or, with `ocr_backend = "vision"`, a vision model transcribes it.
+use crate::cli::{request_deadline, with_deadline, QueryError};
+use crate::config::Config;
+use crate::endpoints::ChatBackend;
+use crate::output;
+use crate::ui::spinner::Spinner;
+use crate::usage::{self, ExchangeStats};
+use base64::Engine;
+use genai::chat::{ChatMessage, ChatRequest, ContentPart, MessageContent};
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+
+const DEFAULT_COMMAND: &str = "tesseract {file} stdout";
+const DEFAULT_VISION_MODEL: &str = "gpt-4o-mini";
+
+const VISION_PROMPT: &str = "Transcribe all text in this image exactly as written, keeping line breaks, \
+indentation and code. Reply with the text only, without comments; reply with nothing if there is no text.";
+
+fn content_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn run_command(template: &str, path: &Path) -> Result<String, QueryError> {
+    // The file goes in as a positional argument, so the shell never parses its name
+    let cmdline = template.replace("{file}", "\"$1\"");
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(&cmdline)
+        .arg("sh")
+        .arg(path)
+        .output()
+        .map_err(|e| QueryError::Input(format!("Failed to run the OCR command: {}", e)))?;
+    if output.status.code() == Some(127) {
+        return Err(QueryError::Input(
+            "OCR command not found: install tesseract, set `ocr_command`, or use `ocr_backend = \"vision\"`".to_string(),
+        ));
+    }
+    if !output.status.success() {
+        return Err(QueryError::Input(format!(
+            "OCR command failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+async fn ask_vision_model(client: &impl ChatBackend, model: &str, path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let content_type = content_type(path).ok_or_else(|| {
+        QueryError::Input(format!("'{}' is not a PNG, JPEG, GIF or WebP image", path.display()))
+    })?;
+    let bytes = std::fs::read(path).map_err(|e| QueryError::Input(format!("Failed to read '{}': {}", path.display(), e)))?;
+    let image = base64::engine::general_purpose::STANDARD.encode(bytes);
+    let content = MessageContent::from_parts(vec![
+        ContentPart::from_text(VISION_PROMPT),
+        ContentPart::from_image_base64(content_type, image),
+    ]);
+    let started = Instant::now();
+    let chat_res = with_deadline(request_deadline(), client.chat(model, ChatRequest::new(vec![ChatMessage::user(content)]), None)).await?;
+    let elapsed = started.elapsed();
+    usage::record(&ExchangeStats::new(model, &chat_res.usage, None, elapsed));
+    Ok(chat_res.content_text_as_str().unwrap_or_default().trim().to_string())
+}
+
+// The text in the image at `path`, read as configured by `ocr_backend` / `ocr_command` / `ocr_model`
+pub async fn read_image(client: &impl ChatBackend, config: &Config, path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    if !path.is_file() {
+        return Err(QueryError::Input(format!("Image '{}' does not exist", path.display())).into());
+    }
+    let text = match config.ocr_backend.as_deref() {
+        Some("vision") => {
+            let model = config.ocr_model.as_deref().unwrap_or(DEFAULT_VISION_MODEL);
+            let mut spinner = Spinner::start(&format!("Reading {} with {}", path.display(), model));
+            let text = ask_vision_model(client, model, path).await;
+            spinner.stop();
+            text?
+        }
+        Some("command") | None => {
+            let template = config.ocr_command.clone().unwrap_or_else(|| DEFAULT_COMMAND.to_string());
+            let image = path.to_path_buf();
+            let mut spinner = Spinner::start(&format!("Reading {}", path.display()));
+            let text = tokio::task::spawn_blocking(move || run_command(&template, &image)).await;
+            spinner.stop();
+            text??
+        }
+        Some(other) => return Err(QueryError::Input(format!("Unknown ocr_backend '{}'", other)).into()),
+    };
+    if text.is_empty() {
+        return Err(QueryError::Input(format!("No text found in '{}'", path.display())).into());
+    }
+    if !output::is_quiet() {
+        eprintln!("Read {} lines of text from {}", text.lines().count(), path.display());
+    }
+    Ok(text)
+}
+
+// The prompt with the image's text in front of `question` (which may be empty)
+pub fn with_image_text(name: &str, text: &str, question: &str) -> String {
+    let fence = if text.contains("```") { "````" } else { "```" };
+    let block = format!("Text read from the image '{}':\n{}\n{}\n{}", name, fence, text, fence);
+    match question.trim() {
+        "" => block,
+        question => format!("{}\n\n{}", block, question),
+    }
+}
@@ -0,0 +1,101 @@
+// output.rs
+use crate::i18n;
+use crate::markdown_render::{render_reasoning_line, MarkdownRender};
+use crate::sse_event::{print_stream_line, SseEvent, StreamSpeed};
+use crate::ui::text_width;
+use crate::ui::theme;
+use crate::usage;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::mpsc;
+
+// Process-wide output switches from --quiet / --raw / --accessible
+static QUIET: AtomicBool = AtomicBool::new(false);
+static RAW: AtomicBool = AtomicBool::new(false);
+static ACCESSIBLE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn set_raw(raw: bool) {
+    RAW.store(raw, Ordering::Relaxed);
+}
+
+// Everything --raw leaves out, plus icon glyphs, live meters and redrawn status lines; responses
+// are announced where they start and end, for screen readers
+pub fn set_accessible(accessible: bool) {
+    ACCESSIBLE.store(accessible, Ordering::Relaxed);
+    if accessible {
+        set_raw(true);
+    }
+}
+
+pub fn is_accessible() -> bool {
+    ACCESSIBLE.load(Ordering::Relaxed)
+}
+
+// A line of its own in accessible mode only, e.g. "End of response."
+pub fn announce(text: &str) {
+    if is_accessible() {
+        println!("{}", text);
+    }
+}
+
+// `text` as it should be printed: without colors and icon glyphs in accessible mode
+pub fn styled(text: &str) -> String {
+    if is_accessible() {
+        text_width::plain(text)
+    } else {
+        text.to_string()
+    }
+}
+
+// Suppress informational lines (model/stream banners, previews, answer headers)
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+// Plain content only: no ANSI styling and no markdown rendering
+pub fn is_raw() -> bool {
+    RAW.load(Ordering::Relaxed)
+}
+
+// Render an answer from ChatSession::add_message until it is done: reasoning, tool activity,
+// errors and the stats footer inline with the text.
+// `after_chunk` runs after each chunk (e.g. to collect type-ahead input).
+pub async fn render_stream(
+    mut rx: mpsc::Receiver<SseEvent>,
+    render: &mut MarkdownRender,
+    speed: &StreamSpeed,
+    mut after_chunk: impl FnMut(),
+) {
+    while let Some(event) = rx.recv().await {
+        match event {
+            SseEvent::Text(text) => {
+                for line in text.split('\n') {
+                    let output = render.render_line_mut(line);
+                    print_stream_line(&output, speed);
+                }
+                after_chunk();
+            }
+            SseEvent::ReasoningText(line) => println!("{}", render_reasoning_line(&line)),
+            SseEvent::ToolCallStart { name, arguments } if is_accessible() => {
+                println!("{}", i18n::tf("tool.calling", &[&name, &arguments]))
+            }
+            SseEvent::ToolCallStart { name, arguments } => {
+                println!("{}{} {}", theme::INFO.paint(" ⚙ "), name, theme::DIM.paint(arguments))
+            }
+            SseEvent::ToolCallEnd { name, error: None } if is_accessible() => println!("{}", i18n::tf("tool.done", &[&name])),
+            SseEvent::ToolCallEnd { name, error: None } => println!("{}{}", theme::GOOD.paint(" ✓ "), name),
+            SseEvent::ToolCallEnd { name, error: Some(e) } if is_accessible() => println!("{}", i18n::tf("tool.failed", &[&name, &e])),
+            SseEvent::ToolCallEnd { name, error: Some(e) } => println!("{}{}: {}", theme::BAD.paint(" ✗ "), name, e),
+            SseEvent::Usage(stats) => {
+                if usage::show_stats() {
+                    println!("{}", theme::DIM.paint(stats.footer()));
+                }
+            }
+            SseEvent::Error(message) => println!("{} {}", i18n::error_label(), message),
+            SseEvent::Done => break,
+        }
+    }
+}
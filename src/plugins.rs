@@ -0,0 +1,102 @@
+// plugins.rs
+// Manifests are read once when the REPL starts and again on /plugins reload, so completion and
+// dispatch work from the cached list and problems are reported only then, not over the prompt.
+use crate::command::command_help;
+use crate::config::get_config_dir;
+use crate::sanitize;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::RwLock;
+
+static LOADED: RwLock<Vec<PluginManifest>> = RwLock::new(Vec::new());
+
+/// Manifest describing a user plugin, stored as `<name>.json` in the plugins dir.
+///
+/// ```json
+/// { "name": "weather", "exec": "weather.sh", "description": "Current weather",
+///   "usage": "/weather <city>", "completions": ["tokyo", "taipei"] }
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+pub struct PluginManifest {
+    pub name: String,
+    pub exec: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub usage: Option<String>,
+    #[serde(default)]
+    pub completions: Vec<String>,
+}
+
+pub fn get_plugins_dir() -> PathBuf {
+    get_config_dir().join("plugins")
+}
+
+// Scan the plugins dir for *.json manifests, skipping broken ones and those named after a built-in
+// command, with a warning for each
+fn discover_plugins() -> Vec<PluginManifest> {
+    let mut plugins = Vec::new();
+    let entries = match fs::read_dir(get_plugins_dir()) {
+        Ok(entries) => entries,
+        Err(_) => return plugins,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::from_str::<PluginManifest>(&data).map_err(|e| e.to_string()))
+        {
+            Ok(manifest) if command_help(&manifest.name).is_some() => eprintln!(
+                "Skipping plugin manifest {}: /{} is a built-in command",
+                path.display(),
+                manifest.name
+            ),
+            Ok(manifest) => plugins.push(manifest),
+            Err(e) => eprintln!("Skipping plugin manifest {}: {}", path.display(), e),
+        }
+    }
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+// Read the manifests again, replacing the cached list; returns how many plugins there are
+pub fn load() -> usize {
+    let plugins = discover_plugins();
+    let count = plugins.len();
+    *LOADED.write().unwrap() = plugins;
+    count
+}
+
+// The plugins found by the last `load`, sorted by name
+pub fn loaded() -> Vec<PluginManifest> {
+    LOADED.read().unwrap().clone()
+}
+
+pub fn find_plugin(name: &str) -> Option<PluginManifest> {
+    LOADED.read().unwrap().iter().find(|p| p.name == name).cloned()
+}
+
+// Run the plugin executable with the remaining command line as arguments and return its stdout,
+// scrubbed of local paths and names
+pub fn run_plugin(plugin: &PluginManifest, args: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let exec_path = PathBuf::from(&plugin.exec);
+    let exec_path = if exec_path.is_absolute() {
+        exec_path
+    } else {
+        get_plugins_dir().join(exec_path)
+    };
+    let output = Command::new(&exec_path)
+        .args(args.split_whitespace())
+        .output()
+        .map_err(|e| format!("Failed to run plugin '{}' ({}): {}", plugin.name, exec_path.display(), e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Plugin '{}' failed ({}): {}", plugin.name, output.status, stderr.trim()).into());
+    }
+    Ok(sanitize::scrub(&String::from_utf8_lossy(&output.stdout)))
+}
@@ -0,0 +1,63 @@
+// preferences.rs
+use crate::config::{get_data_dir, Config};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const PREFERENCE_FILE: &str = "preferences.jsonl";
+
+/// One /compare vote, stored as a line of JSON.
+#[derive(Serialize, Deserialize)]
+pub struct Preference {
+    pub timestamp: String,
+    pub prompt: String,
+    pub models: Vec<String>,
+    pub winner: Option<String>, // None when the vote was skipped
+}
+
+// `preference_log` from config, or preferences.jsonl in the data dir
+pub fn preference_log_path(config: &Config) -> PathBuf {
+    config
+        .preference_log
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| get_data_dir().join(PREFERENCE_FILE))
+}
+
+pub fn record_preference(path: &Path, prompt: &str, models: &[String], winner: Option<&str>) -> std::io::Result<()> {
+    let entry = Preference {
+        timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        prompt: prompt.to_string(),
+        models: models.to_vec(),
+        winner: winner.map(String::from),
+    };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)
+}
+
+// Wins and appearances per model; unreadable lines are ignored
+pub fn tally_preferences(path: &Path) -> BTreeMap<String, (usize, usize)> {
+    let mut tally: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    let Ok(data) = std::fs::read_to_string(path) else {
+        return tally;
+    };
+    for entry in data.lines().filter_map(|l| serde_json::from_str::<Preference>(l).ok()) {
+        if entry.winner.is_none() {
+            continue;
+        }
+        for model in &entry.models {
+            let counts = tally.entry(model.clone()).or_default();
+            counts.1 += 1;
+            if entry.winner.as_ref() == Some(model) {
+                counts.0 += 1;
+            }
+        }
+    }
+    tally
+}
@@ -0,0 +1,123 @@
+// rate_limit.rs
+// `[rate_limits]`: requests and tokens per minute for each provider, enforced before every model
+// call so `script`, `filter`, `bench` and long chats wait their turn instead of running into the
+// provider's quota. The last minute of requests is kept per provider; a request counts with its
+// estimated prompt tokens when it starts and with the reported usage once it is answered.
+use crate::config::{Config, RateLimit};
+use crate::dry_run;
+use crate::endpoints;
+use crate::output;
+use crate::ui::theme;
+use genai::chat::{ChatRequest, Usage};
+use std::collections::{HashMap, VecDeque};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+struct Sent {
+    at: Instant,
+    id: u64,
+    tokens: u64,
+}
+
+struct Gate {
+    limits: HashMap<String, RateLimit>,
+    sent: Mutex<HashMap<String, VecDeque<Sent>>>,
+}
+
+static GATE: OnceLock<Gate> = OnceLock::new();
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+// `[rate_limits]` from config; call once at startup
+pub fn init(config: &Config) {
+    if let Some(limits) = config.rate_limits.clone().filter(|limits| !limits.is_empty()) {
+        let _ = GATE.set(Gate { limits, sent: Mutex::new(HashMap::new()) });
+    }
+}
+
+/// A request let through the gate; `settle` replaces its estimate with the tokens it really used.
+pub struct Permit {
+    provider: String,
+    id: u64,
+}
+
+impl Permit {
+    pub fn settle(self, usage: &Usage) {
+        let tokens = usage
+            .total_tokens
+            .or_else(|| Some(usage.prompt_tokens? + usage.completion_tokens.unwrap_or(0)));
+        let (Some(gate), Some(tokens)) = (GATE.get(), tokens) else {
+            return;
+        };
+        let mut sent = gate.sent.lock().unwrap();
+        if let Some(entry) = sent.get_mut(&self.provider).and_then(|w| w.iter_mut().find(|s| s.id == self.id)) {
+            entry.tokens = tokens.max(0) as u64;
+        }
+    }
+}
+
+// How long until a request of `tokens` fits in the window, and which limit it waits for
+fn wait_needed(limit: &RateLimit, window: &VecDeque<Sent>, tokens: u64, now: Instant) -> Option<(Duration, String)> {
+    if let Some(rpm) = limit.requests_per_minute.filter(|&rpm| rpm > 0) {
+        let rpm = rpm as usize;
+        if window.len() >= rpm {
+            let frees_at = window[window.len() - rpm].at + WINDOW;
+            return Some((frees_at - now, format!("{} requests/min", rpm)));
+        }
+    }
+    if let Some(tpm) = limit.tokens_per_minute.filter(|&tpm| tpm > 0) {
+        let mut used: u64 = window.iter().map(|s| s.tokens).sum();
+        // A request bigger than the whole budget goes alone once the window is empty
+        if used + tokens > tpm && !window.is_empty() {
+            let reason = format!("{} tokens/min", tpm);
+            for sent in window {
+                used -= sent.tokens;
+                if used + tokens <= tpm || used == 0 {
+                    return Some((sent.at + WINDOW - now, reason));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn notice(message: &str) {
+    if output::is_quiet() {
+        return;
+    }
+    // Starts on a clean line when a spinner is drawing
+    let clear = if std::io::stderr().is_terminal() && theme::colors() { "\r\x1b[2K" } else { "" };
+    eprintln!("{}{}", clear, theme::DIM.paint_err(message));
+}
+
+// Waits until `model`'s provider has room for `chat_req`; None when it has no limits
+pub async fn acquire(model: &str, chat_req: &ChatRequest) -> Option<Permit> {
+    let gate = GATE.get()?;
+    let provider = endpoints::provider(model);
+    let limit = gate.limits.get(&provider)?;
+    let tokens = dry_run::estimate_request(chat_req) as u64;
+    loop {
+        let (wait, reason) = {
+            let mut sent = gate.sent.lock().unwrap();
+            let window = sent.entry(provider.clone()).or_default();
+            let now = Instant::now();
+            while window.front().is_some_and(|s| now.duration_since(s.at) >= WINDOW) {
+                window.pop_front();
+            }
+            match wait_needed(limit, window, tokens, now) {
+                Some(wait) => wait,
+                None => {
+                    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+                    window.push_back(Sent { at: now, id, tokens });
+                    return Some(Permit { provider, id });
+                }
+            }
+        };
+        notice(&format!("Rate limit for {} ({}): waiting {}s", provider, reason, wait.as_secs() + 1));
+        tracing::debug!(provider, ?wait, "rate limit");
+        tokio::time::sleep(wait + Duration::from_millis(50)).await;
+    }
+}
@@ -0,0 +1,148 @@
+// routing.rs
+use crate::chat_session::ChatSession;
+use crate::config::{Config, MicRoute};
+use crate::output;
+use crate::ui::theme;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// A mic.md message after applying the first matching route.
+pub struct RoutedMessage {
+    pub pattern: String,
+    pub model: Option<String>,
+    pub system_prompt: Option<String>,
+    pub content: String,
+}
+
+pub struct MicRouter {
+    routes: Vec<(Regex, MicRoute)>,
+}
+
+impl MicRouter {
+    pub fn from_config(config: &Config) -> Self {
+        let mut routes = Vec::new();
+        for route in config.mic_routes.clone().unwrap_or_default() {
+            match Regex::new(&route.pattern) {
+                Ok(re) => routes.push((re, route)),
+                Err(e) => eprintln!("Ignoring mic route '{}': {}", route.pattern, e),
+            }
+        }
+        MicRouter { routes }
+    }
+
+    // First route whose pattern matches; the matched text is stripped from the content
+    pub fn route(&self, content: &str) -> Option<RoutedMessage> {
+        let trimmed = content.trim_start();
+        self.routes.iter().find_map(|(re, route)| {
+            let m = re.find(trimmed)?;
+            let input = trimmed[m.end()..].trim();
+            let message = match &route.template {
+                Some(template) => template.replace("{input}", input),
+                None => input.to_string(),
+            };
+            let system_prompt = route.role.as_ref().map(|role| {
+                ChatSession::role_prompt(role).unwrap_or_else(|| role.clone())
+            });
+            Some(RoutedMessage {
+                pattern: route.pattern.clone(),
+                model: route.model.clone(),
+                system_prompt,
+                content: message,
+            })
+        })
+    }
+}
+
+/// Model name that picks a model per prompt (`-m auto`).
+pub const AUTO_MODEL: &str = "auto";
+
+const DEFAULT_FAST_MODEL: &str = "gemini-2.0-flash";
+const DEFAULT_STRONG_MODEL: &str = "deepseek-chat";
+const DEFAULT_MAX_FAST_CHARS: usize = 400;
+const DEFAULT_STRONG_KEYWORDS: &[&str] = &[
+    "code", "function", "implement", "debug", "error", "refactor", "compile", "algorithm", "regex", "sql",
+    "script", "bug", "stack trace",
+];
+
+/// Heuristics behind `-m auto`: short factual prompts go to the fast model, prompts with code
+/// fences, strong keywords or more than `max_fast_chars` characters go to the strong one.
+pub struct AutoRouter {
+    fast_model: String,
+    strong_model: String,
+    max_fast_chars: usize,
+    keywords: Vec<Regex>,
+}
+
+static AUTO_ROUTER: OnceLock<AutoRouter> = OnceLock::new();
+
+impl AutoRouter {
+    pub fn from_config(config: &Config) -> Self {
+        let keywords = match &config.auto_strong_keywords {
+            Some(words) => words.clone(),
+            None => DEFAULT_STRONG_KEYWORDS.iter().map(|w| w.to_string()).collect(),
+        };
+        AutoRouter {
+            fast_model: config.auto_fast_model.clone().unwrap_or_else(|| DEFAULT_FAST_MODEL.to_string()),
+            strong_model: config
+                .auto_strong_model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_STRONG_MODEL.to_string()),
+            max_fast_chars: config.auto_max_fast_chars.unwrap_or(DEFAULT_MAX_FAST_CHARS),
+            keywords: keywords
+                .iter()
+                .filter_map(|w| Regex::new(&format!(r"(?i)\b{}\b", regex::escape(w))).ok())
+                .collect(),
+        }
+    }
+
+    // The chosen model and the rule that picked it
+    pub fn choose(&self, prompt: &str) -> (&str, &'static str) {
+        if prompt.contains("```") {
+            (&self.strong_model, "code block")
+        } else if prompt.chars().count() > self.max_fast_chars {
+            (&self.strong_model, "long prompt")
+        } else if self.keywords.iter().any(|re| re.is_match(prompt)) {
+            (&self.strong_model, "keyword")
+        } else {
+            (&self.fast_model, "short question")
+        }
+    }
+}
+
+pub fn init_auto_router(config: &Config) {
+    let _ = AUTO_ROUTER.set(AutoRouter::from_config(config));
+}
+
+fn auto_router() -> &'static AutoRouter {
+    AUTO_ROUTER.get_or_init(|| AutoRouter::from_config(&Config::default()))
+}
+
+// Resolve `auto` to a concrete model for this prompt; other names pass through
+pub fn choose_model(model: &str, prompt: &str) -> String {
+    if model != AUTO_MODEL {
+        return model.to_string();
+    }
+    let (chosen, reason) = auto_router().choose(prompt);
+    tracing::debug!(model = chosen, reason, "auto routing");
+    chosen.to_string()
+}
+
+// `choose_model`, printing an `auto` choice
+pub fn resolve_model(model: &str, prompt: &str) -> String {
+    let chosen = choose_model(model, prompt);
+    if model != AUTO_MODEL || output::is_quiet() {
+        return chosen;
+    }
+    let (_, reason) = auto_router().choose(prompt);
+    if output::is_raw() {
+        eprintln!("auto -> {} ({})", chosen, reason);
+    } else {
+        eprintln!(
+            "{}{}{}",
+            theme::MUTED.paint_err("auto → "),
+            theme::VALUE.paint_err(&chosen),
+            theme::MUTED.paint_err(format!(" ({})", reason))
+        );
+    }
+    chosen
+}
@@ -0,0 +1,75 @@
+// sanitize.rs
+// Scrubs details about the local machine from text sent to a model: home directories (and $HOME
+// wherever it lives) become "~", and the regexes in `sanitize_patterns` (hostnames, usernames,
+// internal domains) become [scrubbed]. Applied to all tool output (build-release, explain,
+// plugins), and to every outgoing prompt when `sanitize_prompts = true`.
+use crate::config::Config;
+use regex::Regex;
+use std::sync::OnceLock;
+
+const HOME_PATTERN: &str = r"(?:/home|/Users)/[a-zA-Z0-9_.-]+";
+
+struct Sanitizer {
+    prompts: bool,
+    home: Regex,
+    patterns: Vec<Regex>,
+}
+
+static SANITIZER: OnceLock<Sanitizer> = OnceLock::new();
+
+// `sanitize_prompts` and `sanitize_patterns` from config; call once at startup
+pub fn init(config: &Config) -> Result<(), String> {
+    let mut patterns = Vec::new();
+    for pattern in config.sanitize_patterns.iter().flatten() {
+        let regex = Regex::new(pattern).map_err(|e| format!("Invalid sanitize_patterns entry '{}': {}", pattern, e))?;
+        patterns.push(regex);
+    }
+    let _ = SANITIZER.set(Sanitizer {
+        prompts: config.sanitize_prompts.unwrap_or(false),
+        home: home_regex(),
+        patterns,
+    });
+    Ok(())
+}
+
+// Home directories under /home and /Users, plus $HOME when it is elsewhere (/root, /var/lib/...)
+fn home_regex() -> Regex {
+    let home = std::env::var("HOME")
+        .ok()
+        .map(|h| h.trim_end_matches('/').to_string())
+        .filter(|h| h.len() > 1);
+    match home {
+        Some(home) => Regex::new(&format!(r"{}|{}\b", HOME_PATTERN, regex::escape(&home))).unwrap(),
+        None => Regex::new(HOME_PATTERN).unwrap(),
+    }
+}
+
+// Home directories only until init runs (tests, early errors)
+fn sanitizer() -> &'static Sanitizer {
+    SANITIZER.get_or_init(|| Sanitizer {
+        prompts: false,
+        home: home_regex(),
+        patterns: Vec::new(),
+    })
+}
+
+// `text` without home directories and configured patterns
+pub fn scrub(text: &str) -> String {
+    let sanitizer = sanitizer();
+    let mut scrubbed = sanitizer.home.replace_all(text, "~").into_owned();
+    for regex in &sanitizer.patterns {
+        if regex.is_match(&scrubbed) {
+            scrubbed = regex.replace_all(&scrubbed, "[scrubbed]").into_owned();
+        }
+    }
+    scrubbed
+}
+
+// A prompt as it should be sent: scrubbed when `sanitize_prompts` is on
+pub fn outgoing(text: &str) -> String {
+    if sanitizer().prompts {
+        scrub(text)
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,241 @@
+// session_browser.rs
+// Full-screen picker for /load: fuzzy search over saved sessions with a preview of each one
+use chrono::{DateTime, Local};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::{cursor, execute, queue, terminal};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use crate::ui::text_width;
+use crate::ui::theme;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+// Messages shown from each end of a conversation in the preview
+const PREVIEW_MESSAGES: usize = 2;
+
+/// A saved session file, summarized for listing.
+pub struct SessionEntry {
+    pub filename: String,
+    pub title: Option<String>,
+    pub model: String,
+    pub modified: DateTime<Local>,
+    pub messages: Vec<(String, String)>, // (role, text), system prompt excluded
+}
+
+impl SessionEntry {
+    pub fn read(path: &Path) -> Option<SessionEntry> {
+        let filename = path.file_name()?.to_str()?.to_string();
+        let modified: DateTime<Local> = fs::metadata(path).ok()?.modified().ok()?.into();
+        let json: serde_json::Value = fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        let messages = json
+            .get("messages")
+            .and_then(|m| m.as_array())
+            .map(|messages| {
+                messages
+                    .iter()
+                    .filter_map(|m| {
+                        let role = m.get("role")?.as_str()?;
+                        let text = m.pointer("/content/Text")?.as_str()?;
+                        (role != "System").then(|| (role.to_string(), text.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(SessionEntry {
+            filename,
+            title: json.get("title").and_then(|t| t.as_str()).map(String::from),
+            model: json
+                .get("model")
+                .and_then(|m| m.as_str())
+                .unwrap_or("Unknown")
+                .to_string(),
+            modified,
+            messages,
+        })
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "{}  {}  {}",
+            self.modified.format("%Y-%m-%d %H:%M"),
+            self.title.as_deref().unwrap_or(&self.filename),
+            self.model
+        )
+    }
+
+    // First and last messages, one line per wrapped chunk
+    fn preview_lines(&self, width: usize) -> Vec<String> {
+        let count = self.messages.len();
+        let mut lines = vec![format!("{} — {} messages", self.filename, count)];
+        for (i, (role, text)) in self.messages.iter().enumerate() {
+            if i == PREVIEW_MESSAGES && count > PREVIEW_MESSAGES * 2 {
+                lines.push(String::new());
+                lines.push(format!("  … {} more messages …", count - PREVIEW_MESSAGES * 2));
+            }
+            if i >= PREVIEW_MESSAGES && i + PREVIEW_MESSAGES < count {
+                continue;
+            }
+            lines.push(String::new());
+            lines.push(format!("{}:", role));
+            for line in text.lines() {
+                lines.extend(text_width::wrap(line, width));
+            }
+        }
+        lines
+    }
+}
+
+// Sessions in `dir`, most recently modified first
+pub fn list_sessions(dir: &Path) -> io::Result<Vec<SessionEntry>> {
+    let mut entries: Vec<SessionEntry> = fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter_map(|p| SessionEntry::read(&p))
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.modified));
+    Ok(entries)
+}
+
+// Restores the terminal even if drawing fails part way
+struct BrowserGuard;
+
+impl BrowserGuard {
+    fn start() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(BrowserGuard)
+    }
+}
+
+impl Drop for BrowserGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Indices of the entries matching `query`, best match first (all of them, by date, when empty)
+fn filter(entries: &[SessionEntry], query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return (0..entries.len()).collect();
+    }
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(i64, usize)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| matcher.fuzzy_match(&e.label(), query).map(|score| (score, i)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+fn draw(
+    entries: &[SessionEntry],
+    matches: &[usize],
+    query: &str,
+    selected: usize,
+    preview_scroll: usize,
+) -> io::Result<()> {
+    let (width, height) = terminal::size()?;
+    let (width, height) = (width as usize, height as usize);
+    let list_height = (height / 2).saturating_sub(2).max(1);
+    let preview_height = height.saturating_sub(list_height + 3);
+    let mut out = io::stdout();
+    queue!(out, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))?;
+    queue!(
+        out,
+        Print(theme::GOOD.paint("Search: ")),
+        Print(text_width::truncate(query, width.saturating_sub(8))),
+    )?;
+
+    // Keep the selection visible
+    let first = selected.saturating_sub(list_height - 1);
+    for (row, &index) in matches.iter().enumerate().skip(first).take(list_height) {
+        queue!(out, cursor::MoveTo(0, (row - first + 1) as u16))?;
+        let label = text_width::truncate(&entries[index].label(), width.saturating_sub(2));
+        if row == selected {
+            queue!(out, Print(theme::SELECTED.paint(format!("> {}", label))))?;
+        } else {
+            queue!(out, Print(format!("  {}", label)))?;
+        }
+    }
+
+    let separator_row = list_height + 1;
+    queue!(
+        out,
+        cursor::MoveTo(0, separator_row as u16),
+        Print(theme::MUTED.paint(text_width::truncate(
+            &format!(
+                "── {}/{} ── ↑↓ select  PgUp/PgDn scroll preview  Enter load  Esc cancel {}",
+                matches.len(),
+                entries.len(),
+                "─".repeat(width)
+            ),
+            width
+        ))),
+    )?;
+    if let Some(&index) = matches.get(selected) {
+        let lines = entries[index].preview_lines(width);
+        for (row, line) in lines.iter().skip(preview_scroll).take(preview_height).enumerate() {
+            queue!(out, cursor::MoveTo(0, (separator_row + 1 + row) as u16))?;
+            if matches!(line.as_str(), "User:" | "Assistant:" | "Tool:") {
+                queue!(out, Print(theme::VALUE.paint(line)))?;
+            } else {
+                queue!(out, Print(line))?;
+            }
+        }
+    }
+    out.flush()
+}
+
+// Interactive picker; returns the chosen filename, or None when cancelled
+pub fn browse_sessions(entries: &[SessionEntry]) -> io::Result<Option<String>> {
+    let _guard = BrowserGuard::start()?;
+    let mut query = String::new();
+    let mut selected = 0;
+    let mut preview_scroll = 0;
+    loop {
+        let matches = filter(entries, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+        let width = terminal::size()?.0 as usize;
+        let preview_len = matches.get(selected).map_or(0, |&i| entries[i].preview_lines(width).len());
+        preview_scroll = preview_scroll.min(preview_len.saturating_sub(1));
+        draw(entries, &matches, &query, selected, preview_scroll)?;
+
+        let Event::Key(key) = event::read()? else {
+            continue; // resize: just redraw
+        };
+        let page = (terminal::size()?.1 as usize / 2).max(1);
+        match key.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Enter => return Ok(matches.get(selected).map(|&i| entries[i].filename.clone())),
+            KeyCode::Up => {
+                selected = selected.saturating_sub(1);
+                preview_scroll = 0;
+            }
+            KeyCode::Down => {
+                selected = (selected + 1).min(matches.len().saturating_sub(1));
+                preview_scroll = 0;
+            }
+            KeyCode::PageUp => preview_scroll = preview_scroll.saturating_sub(page),
+            KeyCode::PageDown => preview_scroll += page,
+            KeyCode::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+                preview_scroll = 0;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// snippets.rs
+// Saved prompts for `/snippet`: name = prompt in snippets.toml in the config dir, read on every
+// use so a snippet saved in one window can be sent from another.
+use crate::config::get_config_dir;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+const SNIPPETS_FILE: &str = "snippets.toml";
+
+fn path() -> PathBuf {
+    get_config_dir().join(SNIPPETS_FILE)
+}
+
+// All snippets by name; a missing or unreadable file has none
+pub fn load() -> BTreeMap<String, String> {
+    fs::read_to_string(path())
+        .ok()
+        .and_then(|data| toml::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn write(snippets: &BTreeMap<String, String>) -> Result<(), String> {
+    let data = toml::to_string(snippets).map_err(|e| e.to_string())?;
+    fs::write(path(), data).map_err(|e| format!("Failed to write {}: {}", SNIPPETS_FILE, e))
+}
+
+pub fn get(name: &str) -> Option<String> {
+    load().remove(name)
+}
+
+pub fn names() -> Vec<String> {
+    load().into_keys().collect()
+}
+
+// Store `prompt` under `name`, replacing an earlier one; returns whether it replaced one
+pub fn save(name: &str, prompt: &str) -> Result<bool, String> {
+    if name.is_empty() || name.contains(char::is_whitespace) || matches!(name, "save" | "rm") {
+        return Err(format!("'{}' can't be a snippet name (one word, not save or rm)", name));
+    }
+    let mut snippets = load();
+    let replaced = snippets.insert(name.to_string(), prompt.to_string()).is_some();
+    write(&snippets)?;
+    Ok(replaced)
+}
+
+// Returns whether there was one to remove
+pub fn remove(name: &str) -> Result<bool, String> {
+    let mut snippets = load();
+    if snippets.remove(name).is_none() {
+        return Ok(false);
+    }
+    write(&snippets)?;
+    Ok(true)
+}
@@ -1,5 +1,105 @@
+use crate::config::Config;
+use crate::usage::ExchangeStats;
+use std::io::Write;
+use std::time::Duration;
+
+/// One piece of an answer on its way to `output::render_stream`, in the order it is shown.
 #[derive(Debug)]
 pub enum SseEvent {
+    ReasoningText(String), // one line of the reasoning trace, before the answer
     Text(String),
+    ToolCallStart { name: String, arguments: String },
+    ToolCallEnd { name: String, error: Option<String> }, // None = the tool ran
+    Usage(ExchangeStats), // timing and tokens for the footer
+    Error(String),        // a failure while the answer is shown; the stream goes on
     Done,
 }
+
+// Pause between lines when no `stream_char_delay` is configured
+const DEFAULT_LINE_DELAY_MS: u64 = 50;
+// Pause between characters for `stream_flush = "token"` without an explicit delay
+const DEFAULT_CHAR_DELAY_MS: u64 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFlush {
+    Line,  // print whole lines (markdown rendering sees complete lines)
+    Token, // print as soon as text arrives, typing effect when a delay is set
+}
+
+/// How streamed output is paced, from `stream_char_delay` / `stream_flush` or `/speed`.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamSpeed {
+    pub char_delay_ms: Option<u64>, // Some(0) = raw, fastest output
+    pub flush: StreamFlush,
+}
+
+impl Default for StreamSpeed {
+    fn default() -> Self {
+        StreamSpeed {
+            char_delay_ms: None,
+            flush: StreamFlush::Line,
+        }
+    }
+}
+
+impl StreamSpeed {
+    pub fn from_config(config: &Config) -> Self {
+        StreamSpeed {
+            char_delay_ms: config.stream_char_delay,
+            flush: match config.stream_flush.as_deref() {
+                Some("token") => StreamFlush::Token,
+                _ => StreamFlush::Line,
+            },
+        }
+    }
+
+    // Delay after sending a line of the simulated stream
+    pub fn line_delay(&self, line: &str) -> Duration {
+        match (self.flush, self.char_delay_ms) {
+            (StreamFlush::Token, _) => Duration::ZERO, // the printer does the pacing
+            (StreamFlush::Line, None) => Duration::from_millis(DEFAULT_LINE_DELAY_MS),
+            (StreamFlush::Line, Some(ms)) => Duration::from_millis(ms * line.chars().count() as u64),
+        }
+    }
+
+    fn char_delay(&self) -> Duration {
+        match self.flush {
+            StreamFlush::Token => Duration::from_millis(self.char_delay_ms.unwrap_or(DEFAULT_CHAR_DELAY_MS)),
+            StreamFlush::Line => Duration::ZERO,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match (self.flush, self.char_delay_ms) {
+            (_, Some(0)) => "raw (no delay)".to_string(),
+            (StreamFlush::Line, None) => format!("line, {}ms per line", DEFAULT_LINE_DELAY_MS),
+            (StreamFlush::Line, Some(ms)) => format!("line, {}ms per char", ms),
+            (StreamFlush::Token, _) => format!("token, {}ms per char", self.char_delay().as_millis()),
+        }
+    }
+}
+
+// Print one rendered line, typing it out character by character in token mode.
+// ANSI escape sequences are written without pauses.
+pub fn print_stream_line(output: &str, speed: &StreamSpeed) {
+    let delay = speed.char_delay();
+    if delay.is_zero() {
+        println!("{}", output);
+        return;
+    }
+    let mut stdout = std::io::stdout();
+    let mut in_escape = false;
+    for c in output.chars() {
+        let _ = write!(stdout, "{}", c);
+        if c == '\x1b' {
+            in_escape = true;
+        } else if in_escape {
+            in_escape = !c.is_ascii_alphabetic();
+        } else {
+            let _ = stdout.flush();
+            std::thread::sleep(delay);
+        }
+    }
+    let _ = writeln!(stdout);
+    let _ = stdout.flush();
+}
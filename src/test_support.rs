@@ -0,0 +1,95 @@
+// test_support.rs
+// A deterministic ChatBackend for tests: scripted answers (or failures) in order, then a
+// fallback answer; every request is kept for inspection.
+use crate::endpoints::ChatBackend;
+use futures::future::LocalBoxFuture;
+use genai::adapter::AdapterKind;
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, ChatResponse, MessageContent, Usage};
+use genai::ModelIden;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+pub struct ScriptedBackend {
+    script: RefCell<VecDeque<Result<String, String>>>,
+    fallback: String,
+    pub requests: RefCell<Vec<(String, ChatRequest)>>,
+}
+
+impl ScriptedBackend {
+    // Answers everything with `fallback` unless something was scripted
+    pub fn new(fallback: &str) -> Self {
+        ScriptedBackend {
+            script: RefCell::new(VecDeque::new()),
+            fallback: fallback.to_string(),
+            requests: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn then(self, answer: &str) -> Self {
+        self.script.borrow_mut().push_back(Ok(answer.to_string()));
+        self
+    }
+
+    pub fn then_fail(self, error: &str) -> Self {
+        self.script.borrow_mut().push_back(Err(error.to_string()));
+        self
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.borrow().len()
+    }
+
+    // Model of the last request
+    pub fn last_model(&self) -> String {
+        self.requests.borrow().last().map(|(model, _)| model.clone()).unwrap_or_default()
+    }
+
+    // Text of the last message of the last request
+    pub fn last_prompt(&self) -> String {
+        self.requests
+            .borrow()
+            .last()
+            .and_then(|(_, req)| req.messages.last().map(text).map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+impl ChatBackend for ScriptedBackend {
+    fn chat<'a>(
+        &'a self,
+        model: &'a str,
+        chat_req: ChatRequest,
+        _options: Option<&'a ChatOptions>,
+    ) -> LocalBoxFuture<'a, Result<ChatResponse, Box<dyn std::error::Error>>> {
+        self.requests.borrow_mut().push((model.to_string(), chat_req));
+        let next = self.script.borrow_mut().pop_front().unwrap_or_else(|| Ok(self.fallback.clone()));
+        let model_iden = ModelIden::new(AdapterKind::OpenAI, model);
+        Box::pin(async move {
+            let answer = next.map_err(|e| -> Box<dyn std::error::Error> { e.into() })?;
+            Ok(ChatResponse {
+                content: Some(MessageContent::from(answer.as_str())),
+                reasoning_content: None,
+                model_iden: model_iden.clone(),
+                provider_model_iden: model_iden,
+                // One token per word, enough for max_tokens checks
+                usage: Usage {
+                    completion_tokens: Some(answer.split_whitespace().count() as i32),
+                    ..Usage::default()
+                },
+            })
+        })
+    }
+}
+
+pub fn text(message: &ChatMessage) -> &str {
+    message.content.text_as_str().unwrap_or("")
+}
+
+// A fresh, empty directory under the system temp dir, unique per test name and process
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("llm-test-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create test dir");
+    dir
+}
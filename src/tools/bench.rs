@@ -0,0 +1,218 @@
+// tools/bench.rs
+// `llm bench -m llama3.2 -m gpt-4o-mini --prompt-file p.txt --runs 5`: the same prompt is sent
+// to every model, one run of each in turn so a slow minute hurts them all alike, and time to
+// first token, total time and output tokens/sec are compared as medians. Local servers are
+// started before the clock runs. --json prints every run instead of the table.
+use crate::cli::{error_message, request_deadline, with_deadline, QueryError};
+use crate::dry_run;
+use crate::endpoints::{self, ChatBackend};
+use crate::ingest;
+use crate::output;
+use crate::rate_limit;
+use crate::redact;
+use crate::ui::spinner::Spinner;
+use crate::ui::text_width;
+use crate::ui::theme;
+use crate::usage::{self, ExchangeStats};
+use futures::StreamExt;
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, ChatStreamEvent};
+use genai::Client;
+use serde_json::json;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// One request, streamed when the model allows it so the first token can be timed
+async fn run_once(client: &Client, model: &str, prompt: &str) -> Result<ExchangeStats, Box<dyn std::error::Error>> {
+    endpoints::prepare(model).await?;
+    let chat_req = ChatRequest::new(vec![ChatMessage::user(prompt)]);
+    let deadline = request_deadline();
+    if !endpoints::supports_stream(model) {
+        let started = Instant::now();
+        let chat_res = with_deadline(deadline, client.chat(model, chat_req, None)).await?;
+        let total = started.elapsed();
+        return Ok(ExchangeStats::new(model, &chat_res.usage, None, total));
+    }
+    // A wait for the rate limit is not part of the measurement
+    let permit = rate_limit::acquire(model, &chat_req).await;
+    let started = Instant::now();
+    let options = ChatOptions::default().with_capture_usage(true);
+    let mut chat_res = with_deadline(deadline, client.exec_chat_stream(model, chat_req, Some(&options))).await?;
+    let mut first_token = None;
+    let mut captured_usage = None;
+    loop {
+        let next = async { Ok::<_, genai::Error>(chat_res.stream.next().await) };
+        match with_deadline(deadline, next).await? {
+            Some(event) => match event? {
+                ChatStreamEvent::Chunk(chunk) if !chunk.content.is_empty() => {
+                    first_token.get_or_insert_with(|| started.elapsed());
+                }
+                ChatStreamEvent::End(end) => captured_usage = end.captured_usage,
+                _ => {}
+            },
+            None => break,
+        }
+    }
+    let total = started.elapsed();
+    let first_token = first_token.ok_or(QueryError::NoAnswer)?;
+    let usage = captured_usage.unwrap_or_default();
+    if let Some(permit) = permit {
+        permit.settle(&usage);
+    }
+    Ok(ExchangeStats::new(model, &usage, Some(first_token), total))
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] })
+}
+
+// Results of one model across the runs
+struct ModelRuns {
+    model: String,
+    runs: Vec<ExchangeStats>,
+    errors: Vec<String>,
+}
+
+impl ModelRuns {
+    fn median_of(&self, value: impl Fn(&ExchangeStats) -> Option<f64>) -> Option<f64> {
+        median(self.runs.iter().filter_map(value).collect())
+    }
+
+    fn ttft(&self) -> Option<f64> {
+        self.median_of(|run| run.first_token.map(|d| d.as_secs_f64()))
+    }
+
+    fn total(&self) -> Option<f64> {
+        self.median_of(|run| Some(run.total.as_secs_f64()))
+    }
+
+    fn tokens_per_sec(&self) -> Option<f64> {
+        self.median_of(ExchangeStats::tokens_per_sec)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let runs: Vec<serde_json::Value> = self
+            .runs
+            .iter()
+            .map(|run| {
+                json!({
+                    "ttft_secs": run.first_token.map(|d| d.as_secs_f64()),
+                    "total_secs": run.total.as_secs_f64(),
+                    "prompt_tokens": run.prompt_tokens,
+                    "completion_tokens": run.completion_tokens,
+                    "tokens_per_sec": run.tokens_per_sec(),
+                })
+            })
+            .collect();
+        json!({
+            "model": self.model,
+            "median_ttft_secs": self.ttft(),
+            "median_total_secs": self.total(),
+            "median_tokens_per_sec": self.tokens_per_sec(),
+            "runs": runs,
+            "errors": self.errors,
+        })
+    }
+}
+
+fn cell(value: Option<f64>, unit: &str) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.2}{}", v, unit))
+}
+
+fn print_table(results: &[ModelRuns]) {
+    let width = results.iter().map(|r| text_width::display_width(&r.model)).max().unwrap_or(0).max(5);
+    let header = format!(
+        "{}  {:>6}  {:>9}  {:>9}  {:>8}",
+        text_width::pad("model", width),
+        "ok",
+        "ttft",
+        "total",
+        "tok/s"
+    );
+    println!("{}", theme::HEADING.paint(header));
+    for result in results {
+        let ok = format!("{}/{}", result.runs.len(), result.runs.len() + result.errors.len());
+        println!(
+            "{}  {:>6}  {:>9}  {:>9}  {:>8}",
+            theme::ACCENT.paint(text_width::pad(&result.model, width)),
+            ok,
+            cell(result.ttft(), "s"),
+            cell(result.total(), "s"),
+            cell(result.tokens_per_sec(), "")
+        );
+    }
+    println!("{}", theme::DIM.paint("(medians over the successful runs)"));
+}
+
+pub async fn handle_bench(
+    client: &Client,
+    models: &[String],
+    prompt: Option<String>,
+    prompt_file: Option<String>,
+    runs: usize,
+    as_json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let prompt = match (prompt, prompt_file) {
+        (Some(prompt), _) => prompt,
+        (None, Some(path)) => ingest::read_file(Path::new(&path)).map_err(QueryError::Input)?,
+        (None, None) => return Err(QueryError::Input("Give the prompt with --prompt or --prompt-file".to_string()).into()),
+    };
+    if prompt.trim().is_empty() {
+        return Err(QueryError::Input("The prompt is empty".to_string()).into());
+    }
+    let prompt = redact::check_outgoing(&prompt)?;
+    if dry_run::is_enabled() {
+        for model in models {
+            dry_run::print_request(model, &ChatRequest::new(vec![ChatMessage::user(prompt.as_str())]), None);
+        }
+        return Ok(());
+    }
+
+    let runs = runs.max(1);
+    let mut results: Vec<ModelRuns> = models
+        .iter()
+        .map(|model| ModelRuns { model: model.clone(), runs: Vec::new(), errors: Vec::new() })
+        .collect();
+    for run in 1..=runs {
+        for result in results.iter_mut() {
+            let mut spinner = Spinner::start(&format!("Run {}/{}: {}", run, runs, result.model));
+            let outcome = run_once(client, &result.model, &prompt).await;
+            spinner.stop();
+            match outcome {
+                Ok(stats) => {
+                    usage::record(&stats);
+                    if !output::is_quiet() && !as_json {
+                        eprintln!("{}", theme::DIM.paint_err(format!("run {}/{} {}: {}", run, runs, result.model, stats.footer())));
+                    }
+                    result.runs.push(stats);
+                }
+                Err(e) => {
+                    let message = error_message(e.as_ref());
+                    eprintln!("run {}/{} {}: {}", run, runs, result.model, message);
+                    result.errors.push(message);
+                }
+            }
+            // Let a provider's rate limiter breathe between back-to-back requests
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+
+    if as_json {
+        let report = json!({
+            "runs": runs,
+            "prompt_chars": prompt.chars().count(),
+            "models": results.iter().map(ModelRuns::to_json).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_table(&results);
+    }
+    if results.iter().all(|result| result.runs.is_empty()) {
+        return Err(QueryError::Input("Every run failed".to_string()).into());
+    }
+    Ok(())
+}
@@ -2,120 +2,229 @@
 use bat::{Input, PrettyPrinter};
 use regex::Regex;
 use std::io::{self, Write};
-use std::process::Command;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::thread;
-use std::time::Duration;
+use std::path::Path;
+use std::process::{Command, Stdio};
 
 use crate::cli::execute_query;
+use crate::sanitize;
+use crate::tools::cargo_diagnostics::{describe_groups, parse_build_output, BuildReport};
+use crate::ui::spinner::Spinner;
+use crate::ui::theme;
 use genai::Client;
 use std::io::stdout;
 use std::io::stdin;
 
-pub async fn handle_build_release(
-    client: &Client,
-    model: &str,
-    stream: bool,
-    question: Option<String>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Cargo build release");
+// Rounds of the --loop appended here, next to /tmp/q.log
+const LOOP_LOG: &str = "/tmp/build_loop.log";
 
-    // Spinner animation in a separate thread
-    let spinner = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-    let mut spinner_idx = 0;
-    let building = Arc::new(AtomicBool::new(true));
-    let building_clone = building.clone();
-
-    let spinner_thread = thread::spawn(move || {
-        while building_clone.load(Ordering::Relaxed) {
-            print!("\r{} Building...", spinner[spinner_idx]);
-            spinner_idx = (spinner_idx + 1) % spinner.len();
-            io::stdout().flush().unwrap();
-            thread::sleep(Duration::from_millis(100));
-        }
-        println!("\rBuild complete!    ");
-    });
+const FIX_INSTRUCTIONS: &str = "Fix the errors. Reply with a short explanation and one unified diff \
+(paths relative to the workspace root, a/ and b/ prefixes) in a single ```diff block that can be applied with `git apply`.";
 
-    // Run cargo build --release and capture output
-    let build_result = Command::new("cargo").args(["build", "--release"]).output();
+struct BuildOutcome {
+    success: bool,
+    report: BuildReport,
+    stderr: String,
+}
 
-    // Stop spinner
-    building.store(false, Ordering::Relaxed);
-    spinner_thread.join().unwrap();
+// cargo build --release; diagnostics arrive as JSON lines on stdout
+fn run_build() -> io::Result<BuildOutcome> {
+    let mut spinner = Spinner::start("Building...");
+    let result = Command::new("cargo")
+        .args(["build", "--release", "--message-format=json"])
+        .output();
+    spinner.stop();
+    let output = result?;
+    let report = parse_build_output(&String::from_utf8_lossy(&output.stdout));
+    Ok(BuildOutcome {
+        success: output.status.success() && report.success,
+        report,
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    })
+}
 
-    fn filter_output(output: &str) -> String {
-        let home_re = Regex::new(r"(/home/[a-zA-Z0-9_.-]+|/Users/[a-zA-Z0-9_.-]+)").unwrap();
-        home_re.replace_all(output, "  ").to_string()
+fn failure_prompt(outcome: &BuildOutcome) -> String {
+    let report = &outcome.report;
+    let root = Path::new(".");
+    let errors = describe_groups(report, "error", root);
+    let warnings = describe_groups(report, "warning", root);
+    let mut q = format!(
+        "Build failed or incomplete ({} errors, {} warnings).\n\n",
+        report.count("error"),
+        report.count("warning")
+    );
+    if errors.is_empty() {
+        // Failures outside the compiler (manifest, linker, ...) only show up on stderr
+        q.push_str(&format!("No compiler errors found.\n\nStderr:\n{}\n", outcome.stderr));
+    } else {
+        q.push_str(&format!("## Errors\n\n{}", errors));
+    }
+    if !warnings.is_empty() {
+        q.push_str(&format!("## Warnings\n\n{}", warnings));
     }
+    sanitize::scrub(&q)
+}
 
-    fn extract_error_sessions(compiler_output: &str) -> Vec<String> {
-        // Define regex patterns
-        let error_start_pattern = Regex::new(r"^error\[.*\]:").unwrap();
-        let mut sessions = Vec::new();
-        let mut current_session = Vec::new();
-
-        // Split the compiler output into lines
-        for line in compiler_output.lines() {
-            if error_start_pattern.is_match(line) {
-                // If a new error session starts, save the previous session (if any)
-                if !current_session.is_empty() {
-                    sessions.push(current_session.join("\n"));
-                    current_session.clear();
-                }
-            }
+fn log_question(q: &str) -> io::Result<()> {
+    append_log("/tmp/q.log", q)
+}
 
-            // Add the current line to the ongoing session if it's part of an error
-            if !current_session.is_empty() || error_start_pattern.is_match(line) {
-                current_session.push(line);
-            }
+fn append_log(path: &str, text: &str) -> io::Result<()> {
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", text)?;
+    file.flush()?;
+    Ok(())
+}
+
+fn bat_printer(text: &str, language: &str) {
+    let mut printer = PrettyPrinter::new();
+    if printer
+        .language(language)
+        .grid(true)
+        .line_numbers(false)
+        .theme("TwoDark")
+        .input(Input::from_bytes(text.as_bytes()))
+        .print()
+        .is_err()
+    {
+        eprintln!("Failed to print with bat, fallback: {}", text);
+    }
+}
+
+fn confirm(prompt: &str) -> io::Result<bool> {
+    print!("{} (y/N): ", prompt);
+    stdout().flush()?;
+    let mut input = String::new();
+    stdin().read_line(&mut input)?;
+    Ok(input.trim().to_lowercase().starts_with('y'))
+}
+
+// The first ```diff (or ```patch) block of an answer
+fn extract_patch(answer: &str) -> Option<String> {
+    let re = Regex::new(r"(?s)```(?:diff|patch)[^\n]*\n(.*?)\n```").unwrap();
+    re.captures(answer)
+        .map(|c| format!("{}\n", c[1].trim_end()))
+        .filter(|patch| patch.contains("@@"))
+}
+
+// git apply from stdin; --check first so a bad patch leaves the tree untouched
+fn apply_patch(patch: &str) -> Result<(), String> {
+    for check in [true, false] {
+        let mut cmd = Command::new("git");
+        cmd.args(["apply", "--recount", "--whitespace=nowarn"]);
+        if check {
+            cmd.arg("--check");
+        }
+        let mut child = cmd
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run git apply: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(patch.as_bytes())
+                .map_err(|e| format!("Failed to write patch: {}", e))?;
         }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("git apply failed: {}", e))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+    }
+    Ok(())
+}
 
-        // Save the last session if it exists
-        if !current_session.is_empty() {
-            sessions.push(current_session.join("\n"));
+// --loop: ask for a patch, apply it (after confirmation), rebuild; until the build passes or
+// max_rounds patches were tried
+async fn fix_loop(
+    client: &Client,
+    model: &str,
+    stream: bool,
+    question: Option<String>,
+    max_rounds: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let _ = append_log(LOOP_LOG, &format!("=== build-release --loop ({} rounds max)", max_rounds));
+    let mut outcome = run_build()?;
+    for round in 1..=max_rounds {
+        if outcome.success {
+            break;
         }
+        println!(
+            "{}: {} errors, {} warnings",
+            theme::ACCENT.paint(format!("Round {}/{}", round, max_rounds)),
+            outcome.report.count("error"),
+            outcome.report.count("warning")
+        );
+        let mut q = failure_prompt(&outcome);
+        q.push_str(&format!("\n{}", question.as_deref().unwrap_or(FIX_INSTRUCTIONS)));
+        log_question(&q).unwrap_or_else(|e| eprintln!("Failed to log question: {}", e));
+        let _ = append_log(LOOP_LOG, &format!("--- round {}\n{}", round, q));
 
-        sessions
+        let answer = execute_query(client, model, &q, stream, true, None).await?;
+        let _ = append_log(LOOP_LOG, &answer);
+        let Some(patch) = extract_patch(&answer) else {
+            println!("No patch in the answer; stopping.");
+            let _ = append_log(LOOP_LOG, "no patch, stopped");
+            return Ok(());
+        };
+        bat_printer(&patch, "diff");
+        if !confirm("\nApply this patch and rebuild?")? {
+            println!("Stopped by user");
+            let _ = append_log(LOOP_LOG, "declined, stopped");
+            return Ok(());
+        }
+        if let Err(e) = apply_patch(&patch) {
+            eprintln!("Patch did not apply: {}", e);
+            let _ = append_log(LOOP_LOG, &format!("patch did not apply: {}", e));
+            return Ok(());
+        }
+        let _ = append_log(LOOP_LOG, "patch applied");
+        outcome = run_build()?;
     }
-
-    fn log_question(q: &str) -> io::Result<()> {
-        let mut file = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("/tmp/q.log")?;
-        writeln!(file, "{}", q)?;
-        file.flush()?;
-        Ok(())
+    if outcome.success {
+        let _ = std::fs::remove_file("/tmp/q.log");
+        let _ = append_log(LOOP_LOG, "build succeeded");
+        println!("Build succeeded. Done!");
+    } else {
+        let _ = append_log(LOOP_LOG, "still failing, giving up");
+        println!(
+            "Still failing after {} rounds ({} errors). Log: {}",
+            max_rounds,
+            outcome.report.count("error"),
+            LOOP_LOG
+        );
     }
+    Ok(())
+}
 
-    fn bat_printer(text: &str) {
-        let mut printer = PrettyPrinter::new();
-        if printer
-            .language("markdown")
-            .grid(true)
-            .line_numbers(false)
-            .theme("TwoDark")
-            .input(Input::from_bytes(text.as_bytes()))
-            .print()
-            .is_err()
-        {
-            eprintln!("Failed to print with bat, fallback: {}", text);
-        }
+pub async fn handle_build_release(
+    client: &Client,
+    model: &str,
+    stream: bool,
+    question: Option<String>,
+    max_rounds: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Cargo build release");
+
+    if let Some(max_rounds) = max_rounds {
+        return fix_loop(client, model, stream, question, max_rounds).await;
     }
 
+    let build_result = run_build();
+    println!("Build complete!");
+
     match build_result {
-        Ok(output) => {
-            let stdout_str = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr_str = String::from_utf8_lossy(&output.stderr).to_string();
-
-            if output.status.success()
-                && (stdout_str.contains("Finished `release`")
-                    || stderr_str.contains("Finished `release`"))
-            {
+        Ok(outcome) => {
+            if outcome.success {
+                let warnings = outcome.report.count("warning");
+                if warnings > 0 {
+                    println!("{} warnings", warnings);
+                }
                 if let Some(q) = question {
                     log_question(&q).unwrap_or_else(|e| eprintln!("Failed to log question: {}", e));
-                    bat_printer(&q);
+                    bat_printer(&q, "markdown");
                     //execute_query(client, model, &q, stream, true).await?;
                 } else {
                     // remove q.log if it exists
@@ -123,37 +232,20 @@ pub async fn handle_build_release(
                     println!("Build succeeded. Done!");
                 }
             } else {
-                let filtered_stdout = filter_output(&stdout_str);
-                let filtered_stderr = filter_output(&stderr_str);
+                let q = question.unwrap_or_else(|| failure_prompt(&outcome));
 
-                let error_sessions = extract_error_sessions(&filtered_stderr);
-                let error_message = if error_sessions.is_empty() {
-                    "No specific error sessions found.".to_string()
-                } else {
-                    error_sessions.join("\n\n---\n\n") // Join multiple error sessions with a separator
-                };
-                let q = question.unwrap_or_else(|| {
-                    format!(
-                        "Build failed or incomplete.\n\nError Sessions:\n{}\n\nStdout: {}\nStderr: {}",
-                        error_message, filtered_stdout, filtered_stderr
-                    )
-                });
-
-                println!("Using model: \x1b[93m{}\x1b[0m", model);
-                bat_printer(&q);
+                println!("Using model: {}", theme::ACCENT.paint(model));
+                bat_printer(&q, "markdown");
                 log_question(&q).unwrap_or_else(|e| eprintln!("Failed to log question: {}", e));
 
-                print!("\nExecute this query? (y/N): ");
-                stdout().flush()?;
-                let mut input = String::new();
-                stdin().read_line(&mut input)?;
-                if input.trim().to_lowercase().starts_with('y') {
+                if confirm("\nExecute this query?")? {
                     execute_query(
                         client,
                         model,
                         &q,
                         stream,
-                        true
+                        true,
+                        None
                     ).await?;
                 } else {
                     println!("Query execution cancelled by user");
@@ -163,7 +255,7 @@ pub async fn handle_build_release(
         }
         Err(e) => {
             let q = question.unwrap_or_else(|| format!("Failed to execute build: {}", e));
-            bat_printer(&q);
+            bat_printer(&q, "markdown");
             log_question(&q).unwrap_or_else(|e| eprintln!("Failed to log question: {}", e));
             //execute_query(client, model, &q, stream, true).await?;
         }
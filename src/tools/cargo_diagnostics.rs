@@ -0,0 +1,169 @@
+// tools/cargo_diagnostics.rs
+// Compiler messages from `cargo build --message-format=json`, grouped per crate, error code and
+// file, with the source lines they point at. Only the fields build_release needs are modelled.
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+// Lines of context shown around the primary span
+const SNIPPET_CONTEXT: usize = 3;
+
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    package_id: Option<String>,
+    message: Option<Diagnostic>,
+    success: Option<bool>, // build-finished
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Diagnostic {
+    pub message: String,
+    pub level: String, // "error", "warning", "note", ...
+    pub code: Option<DiagnosticCode>,
+    #[serde(default)]
+    pub spans: Vec<DiagnosticSpan>,
+    pub rendered: Option<String>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct DiagnosticCode {
+    pub code: String,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct DiagnosticSpan {
+    pub file_name: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub is_primary: bool,
+}
+
+impl Diagnostic {
+    fn primary_span(&self) -> Option<&DiagnosticSpan> {
+        self.spans.iter().find(|s| s.is_primary).or(self.spans.first())
+    }
+}
+
+/// Diagnostics sharing crate, level, code and file.
+pub struct DiagnosticGroup {
+    pub krate: String,
+    pub level: String,
+    pub code: Option<String>,
+    pub file: Option<String>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+pub struct BuildReport {
+    pub success: bool,
+    pub groups: Vec<DiagnosticGroup>, // errors first
+}
+
+impl BuildReport {
+    pub fn count(&self, level: &str) -> usize {
+        self.groups
+            .iter()
+            .filter(|g| g.level == level)
+            .map(|g| g.diagnostics.len())
+            .sum()
+    }
+}
+
+// "path+file:///src/foo#bar@0.1.0", "registry+...#serde@1.0.1" or the older "foo 0.1.0 (path+file://...)"
+fn crate_name(package_id: &str) -> String {
+    let name = match package_id.rsplit_once('#') {
+        Some((path, spec)) => match spec.split_once('@') {
+            Some((name, _)) => name,
+            None => path.rsplit('/').next().unwrap_or(spec),
+        },
+        None => package_id.split(' ').next().unwrap_or(package_id),
+    };
+    name.to_string()
+}
+
+// Errors before warnings, then crate, level, code and file
+type GroupKey = (u8, String, String, Option<String>, Option<String>);
+
+// Parse cargo's JSON lines (stdout); lines that aren't JSON messages are skipped
+pub fn parse_build_output(stdout: &str) -> BuildReport {
+    let mut success = false;
+    let mut grouped: BTreeMap<GroupKey, Vec<Diagnostic>> = BTreeMap::new();
+    for line in stdout.lines().filter(|l| l.starts_with('{')) {
+        let Ok(msg) = serde_json::from_str::<CargoMessage>(line) else {
+            continue;
+        };
+        match msg.reason.as_str() {
+            "build-finished" => success = msg.success.unwrap_or(false),
+            "compiler-message" => {
+                let Some(diagnostic) = msg.message else { continue };
+                // Summaries such as "aborting due to 2 previous errors" carry no location
+                if diagnostic.spans.is_empty() && diagnostic.code.is_none() {
+                    continue;
+                }
+                let rank = if diagnostic.level == "error" { 0 } else { 1 };
+                let key = (
+                    rank,
+                    msg.package_id.as_deref().map(crate_name).unwrap_or_default(),
+                    diagnostic.level.clone(),
+                    diagnostic.code.as_ref().map(|c| c.code.clone()),
+                    diagnostic.primary_span().map(|s| s.file_name.clone()),
+                );
+                grouped.entry(key).or_default().push(diagnostic);
+            }
+            _ => {}
+        }
+    }
+    let groups = grouped
+        .into_iter()
+        .map(|((_, krate, level, code, file), diagnostics)| DiagnosticGroup {
+            krate,
+            level,
+            code,
+            file,
+            diagnostics,
+        })
+        .collect();
+    BuildReport { success, groups }
+}
+
+// Numbered source lines around a span, relative to the workspace root
+fn source_snippet(root: &Path, span: &DiagnosticSpan) -> Option<String> {
+    let source = std::fs::read_to_string(root.join(&span.file_name)).ok()?;
+    let lines: Vec<&str> = source.lines().collect();
+    let first = span.line_start.saturating_sub(SNIPPET_CONTEXT + 1);
+    let last = (span.line_end + SNIPPET_CONTEXT).min(lines.len());
+    if first >= last {
+        return None;
+    }
+    Some(
+        (first..last)
+            .map(|i| format!("{:>5} | {}", i + 1, lines[i]))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+// Prompt section for the groups of `level` ("error" or "warning")
+pub fn describe_groups(report: &BuildReport, level: &str, root: &Path) -> String {
+    let mut out = String::new();
+    for group in report.groups.iter().filter(|g| g.level == level) {
+        out.push_str(&format!(
+            "### {} {} in {} ({})\n\n",
+            group.level,
+            group.code.as_deref().unwrap_or("(no code)"),
+            group.file.as_deref().unwrap_or("(no file)"),
+            if group.krate.is_empty() { "unknown crate" } else { &group.krate },
+        ));
+        for diagnostic in &group.diagnostics {
+            match &diagnostic.rendered {
+                Some(rendered) => out.push_str(&format!("```\n{}\n```\n", rendered.trim_end())),
+                None => out.push_str(&format!("{}\n", diagnostic.message)),
+            }
+            if let Some(snippet) = diagnostic.primary_span().and_then(|span| source_snippet(root, span)) {
+                out.push_str(&format!("Source:\n```rust\n{}\n```\n", snippet));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
@@ -0,0 +1,113 @@
+// tools/ctx.rs
+// `llm ctx [globs...]`: a markdown bundle of the current repo (file tree, Cargo.toml, matching
+// files) saved for `--ctx` and `/ctx load`. Files are listed by ingest, so .gitignore applies.
+use crate::config::get_cache_dir;
+use crate::ingest::{self, fenced};
+use crate::output;
+use crate::ui::theme;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_BUDGET: usize = 100_000; // bytes of file content
+const BUNDLE_FILE: &str = "ctx.md";
+// Keep the tree of a large repo from eating the budget
+const MAX_TREE_ENTRIES: usize = 500;
+
+pub struct Bundle {
+    pub text: String,
+    pub included: Vec<String>,
+    pub omitted: Vec<String>, // matched but over the budget, or not text
+}
+
+pub fn bundle_path() -> PathBuf {
+    get_cache_dir().join(BUNDLE_FILE)
+}
+
+// Indented tree from sorted paths
+fn file_tree(files: &[String]) -> String {
+    let mut out = String::new();
+    let mut previous: Vec<&str> = Vec::new();
+    for file in files.iter().take(MAX_TREE_ENTRIES) {
+        let parts: Vec<&str> = file.split('/').collect();
+        let (dirs, name) = parts.split_at(parts.len() - 1);
+        let common = dirs.iter().zip(&previous).take_while(|(a, b)| a == b).count();
+        for (depth, dir) in dirs.iter().enumerate().skip(common) {
+            out.push_str(&format!("{}{}/\n", "  ".repeat(depth), dir));
+        }
+        out.push_str(&format!("{}{}\n", "  ".repeat(dirs.len()), name[0]));
+        previous = dirs.to_vec();
+    }
+    if files.len() > MAX_TREE_ENTRIES {
+        out.push_str(&format!("... {} more files\n", files.len() - MAX_TREE_ENTRIES));
+    }
+    out
+}
+
+// Cargo.toml first, then the matching files in path order until the budget runs out
+pub fn build(globs: &[String], budget: usize) -> Result<Bundle, String> {
+    let all = ingest::list_files(Path::new("."), &[])?;
+    let selected = if globs.is_empty() { Vec::new() } else { ingest::list_files(Path::new("."), globs)? };
+    let root = std::env::current_dir().map_err(|e| e.to_string())?;
+    let name = root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+    let mut text = format!("# Project context: {}\n\n## File tree\n```\n{}```\n\n", name, file_tree(&all));
+    let mut included = Vec::new();
+    let mut omitted = Vec::new();
+    let mut used = 0;
+    let manifest = all.iter().filter(|f| f.as_str() == "Cargo.toml");
+    for path in manifest.chain(selected.iter().filter(|f| f.as_str() != "Cargo.toml")) {
+        let Ok(content) = ingest::read_file(Path::new(path)) else {
+            omitted.push(path.clone());
+            continue;
+        };
+        if used + content.len() > budget {
+            omitted.push(path.clone());
+            continue;
+        }
+        used += content.len();
+        text.push_str(&fenced(path, &content));
+        included.push(path.clone());
+    }
+    if !omitted.is_empty() {
+        text.push_str(&format!("Omitted (size budget or binary): {}\n", omitted.join(", ")));
+    }
+    Ok(Bundle { text, included, omitted })
+}
+
+// The bundle saved by `llm ctx`, or a tree + Cargo.toml one for the current directory
+pub fn load_or_build() -> Result<String, String> {
+    match std::fs::read_to_string(bundle_path()) {
+        Ok(text) => Ok(text),
+        Err(_) => build(&[], DEFAULT_BUDGET).map(|b| b.text),
+    }
+}
+
+// The question with the bundle in front of it
+pub fn with_context(bundle: &str, question: &str) -> String {
+    format!("{}\n---\n\n{}", bundle.trim_end(), question.trim())
+}
+
+pub fn handle_ctx(globs: Vec<String>, budget: usize, print: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let bundle = build(&globs, budget)?;
+    let path = bundle_path();
+    std::fs::write(&path, &bundle.text)?;
+    if print {
+        print!("{}", bundle.text);
+        return Ok(());
+    }
+    if !output::is_quiet() {
+        println!(
+            "Context saved to '{}': {} files, {} bytes",
+            path.display(),
+            bundle.included.len(),
+            bundle.text.len()
+        );
+        for file in &bundle.included {
+            println!("  {} {}", theme::GOOD.paint("+"), file);
+        }
+        for file in &bundle.omitted {
+            println!("  {} {} (omitted)", theme::VALUE.paint("-"), file);
+        }
+        println!("Attach it with `llm --ctx ...`, `llm query --ctx ...` or `/ctx load`.");
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+// tools/explain.rs
+// `llm explain -- <command>`: run a command and ask the model about its output, the general
+// form of the build_release error flow
+use crate::cli::{execute_query, QueryError};
+use crate::output;
+use crate::sanitize;
+use crate::ui::spinner::Spinner;
+use genai::Client;
+use std::process::Command;
+
+const EXPLAIN_SYSTEM_PROMPT: &str = "You explain command line output to a developer. For failures, name the \
+cause first, then the fix. Point out anything surprising. Be concise.";
+
+// Errors are usually at the end; keep the prompt a reasonable size
+const MAX_LINES: usize = 200;
+
+fn tail(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() <= MAX_LINES {
+        return text.trim_end().to_string();
+    }
+    format!(
+        "[... {} earlier lines omitted ...]\n{}",
+        lines.len() - MAX_LINES,
+        lines[lines.len() - MAX_LINES..].join("\n")
+    )
+}
+
+fn explain_prompt(command: &str, status: &str, success: bool, stdout: &str, stderr: &str, question: Option<&str>) -> String {
+    let mut prompt = format!("I ran `{}`. It {}.\n", command, status);
+    for (name, text) in [("stdout", stdout), ("stderr", stderr)] {
+        if !text.trim().is_empty() {
+            prompt.push_str(&format!("\n{}:\n```\n{}\n```\n", name, tail(&sanitize::scrub(text))));
+        }
+    }
+    prompt.push('\n');
+    prompt.push_str(match question {
+        Some(question) => question,
+        None if success => "Explain this output and point out anything unexpected.",
+        None => "Explain what went wrong and how to fix it.",
+    });
+    prompt
+}
+
+pub async fn handle_explain(
+    client: &Client,
+    model: &str,
+    stream: bool,
+    command: Vec<String>,
+    question: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(QueryError::Input("Usage: llm explain -- <command> [args...]".to_string()).into());
+    };
+    let command_line = command.join(" ");
+    let mut spinner = Spinner::start(&format!("Running {}", command_line));
+    let result = Command::new(program).args(args).output();
+    spinner.stop();
+    let output = result.map_err(|e| QueryError::Input(format!("Failed to run '{}': {}", program, e)))?;
+
+    let status = match output.status.code() {
+        Some(0) => "exited successfully".to_string(),
+        Some(code) => format!("exited with code {}", code),
+        None => "was terminated by a signal".to_string(),
+    };
+    if !output::is_quiet() {
+        println!("`{}` {}; asking {}", command_line, status, model);
+    }
+    let prompt = explain_prompt(
+        &command_line,
+        &status,
+        output.status.success(),
+        &String::from_utf8_lossy(&output.stdout),
+        &String::from_utf8_lossy(&output.stderr),
+        question.as_deref(),
+    );
+    execute_query(client, model, &prompt, stream, false, Some(EXPLAIN_SYSTEM_PROMPT)).await?;
+    Ok(())
+}
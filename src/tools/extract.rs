@@ -0,0 +1,189 @@
+// tools/extract.rs
+// `llm extract --fields name,email,amount -f invoice.txt --format csv`: the model is asked for a
+// JSON array with one object per record, the answer is checked (an array of flat objects with
+// the requested keys), a malformed one is sent back with what was wrong, and the rows are printed
+// as CSV or JSON with the fields in the order given.
+use crate::cli::{request_deadline, with_deadline, QueryError};
+use crate::endpoints::ChatBackend;
+use crate::ingest;
+use crate::output;
+use crate::redact;
+use crate::routing;
+use crate::ui::spinner::Spinner;
+use crate::usage::{self, ExchangeStats};
+use genai::chat::{ChatMessage, ChatRequest};
+use serde_json::Value;
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+use std::time::Instant;
+
+// Answers checked before giving up
+const MAX_ATTEMPTS: usize = 3;
+
+fn system_prompt(fields: &[String]) -> String {
+    format!(
+        "You extract structured data from documents. Reply with a JSON array containing one object per record \
+found in the document, each with exactly these keys: {}. Use strings or numbers as values and null when a value \
+is missing. Reply with the JSON array only: no prose, no code fences.",
+        fields.join(", ")
+    )
+}
+
+// The answer without a ``` fence around it
+fn strip_fence(answer: &str) -> &str {
+    let trimmed = answer.trim();
+    match trimmed.strip_prefix("```") {
+        Some(rest) => {
+            let body = rest.split_once('\n').map_or("", |(_, body)| body);
+            body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+        }
+        None => trimmed,
+    }
+}
+
+// The rows in `answer`, values in field order, or what is wrong with it
+fn parse_rows(answer: &str, fields: &[String]) -> Result<Vec<Vec<Value>>, String> {
+    let value: Value = serde_json::from_str(strip_fence(answer)).map_err(|e| format!("not valid JSON ({})", e))?;
+    let records = match value {
+        Value::Array(records) => records,
+        // A single record is fine too
+        record @ Value::Object(_) => vec![record],
+        _ => return Err("not a JSON array of objects".to_string()),
+    };
+    let mut rows = Vec::with_capacity(records.len());
+    for (i, record) in records.into_iter().enumerate() {
+        let Value::Object(mut object) = record else {
+            return Err(format!("item {} is not an object", i + 1));
+        };
+        if let Some(unknown) = object.keys().find(|key| !fields.contains(key)) {
+            return Err(format!("item {} has the key '{}', which was not asked for", i + 1, unknown));
+        }
+        let mut row = Vec::with_capacity(fields.len());
+        for field in fields {
+            let value = object.remove(field).unwrap_or(Value::Null);
+            if value.is_array() || value.is_object() {
+                return Err(format!("'{}' of item {} is not a plain value", field, i + 1));
+            }
+            row.push(value);
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn csv_cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn to_csv(fields: &[String], rows: &[Vec<Value>]) -> String {
+    let header: Vec<String> = fields.iter().map(|f| csv_cell(&Value::String(f.clone()))).collect();
+    let mut out = header.join(",") + "\n";
+    for row in rows {
+        out.push_str(&row.iter().map(csv_cell).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+// A JSON array with one object per line, keys in field order
+fn to_json(fields: &[String], rows: &[Vec<Value>]) -> String {
+    let objects: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let pairs: Vec<String> = fields
+                .iter()
+                .zip(row)
+                .map(|(field, value)| format!("{}: {}", Value::String(field.clone()), value))
+                .collect();
+            format!("  {{{}}}", pairs.join(", "))
+        })
+        .collect();
+    if objects.is_empty() {
+        return "[]\n".to_string();
+    }
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
+
+fn read_document(file: Option<&str>) -> Result<String, QueryError> {
+    let text = match file {
+        Some(path) if path != "-" => ingest::read_path(Path::new(path)).map_err(QueryError::Input)?,
+        _ if std::io::stdin().is_terminal() => {
+            return Err(QueryError::Input("Nothing to extract from: give -f <file> or pipe the text in".to_string()));
+        }
+        _ => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| QueryError::Input(format!("Failed to read stdin: {}", e)))?;
+            text
+        }
+    };
+    if text.trim().is_empty() {
+        return Err(QueryError::Input("The document is empty".to_string()));
+    }
+    Ok(text)
+}
+
+pub async fn handle_extract(
+    client: &impl ChatBackend,
+    model: &str,
+    fields: &[String],
+    file: Option<&str>,
+    format: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let fields: Vec<String> = fields.iter().map(|f| f.trim().to_string()).filter(|f| !f.is_empty()).collect();
+    if fields.is_empty() {
+        return Err(QueryError::Input("--fields needs at least one name".to_string()).into());
+    }
+    let document = redact::check_outgoing(&read_document(file)?)?;
+    let model = routing::resolve_model(model, &document);
+    let mut messages = vec![ChatMessage::system(system_prompt(&fields)), ChatMessage::user(document)];
+    let mut attempt = 1;
+    loop {
+        let mut spinner = Spinner::start(&format!("Extracting {} with {}", fields.join(", "), model));
+        let started = Instant::now();
+        let request = client.chat(&model, ChatRequest::new(messages.clone()), None);
+        let chat_res = with_deadline(request_deadline(), request).await;
+        spinner.stop();
+        let chat_res = chat_res?;
+        let elapsed = started.elapsed();
+        usage::record(&ExchangeStats::new(&model, &chat_res.usage, None, elapsed));
+        let answer = chat_res.content_text_as_str().unwrap_or_default().to_string();
+        match parse_rows(&answer, &fields) {
+            Ok(rows) => {
+                let text = if format == "csv" { to_csv(&fields, &rows) } else { to_json(&fields, &rows) };
+                print!("{}", text);
+                return Ok(());
+            }
+            Err(problem) if attempt < MAX_ATTEMPTS => {
+                attempt += 1;
+                if !output::is_quiet() {
+                    eprintln!("Malformed answer: {}; asking again ({}/{})", problem, attempt, MAX_ATTEMPTS);
+                }
+                messages.push(ChatMessage::assistant(answer));
+                messages.push(ChatMessage::user(format!(
+                    "That answer is malformed: {}. Reply again with only the JSON array of objects with the keys {}.",
+                    problem,
+                    fields.join(", ")
+                )));
+            }
+            Err(problem) => {
+                tracing::debug!(answer, "malformed extraction");
+                return Err(QueryError::Input(format!(
+                    "No usable answer after {} attempts, the last one: {}",
+                    MAX_ATTEMPTS, problem
+                ))
+                .into());
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+// tools/filter.rs
+// `llm filter --prompt "translate to English: {}"`: the prompt is applied to every line of stdin
+// and the answers come out one line each, in input order, so the tool fits in a pipeline. With
+// --jsonl every line is a JSON object: `{field}` in the prompt takes that field, `{}` the whole
+// record, and each record is printed back with an "output" (or "error") field. Up to
+// --concurrency requests are in flight at once.
+use crate::cli::{error_message, request_deadline, with_deadline, QueryError};
+use crate::endpoints::ChatBackend;
+use crate::redact;
+use crate::routing;
+use crate::usage::{self, ExchangeStats};
+use futures::stream::{self, StreamExt};
+use genai::chat::{ChatMessage, ChatRequest};
+use serde_json::Value;
+use std::io::Write;
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+const FILTER_SYSTEM_PROMPT: &str = "You are a text filter in a shell pipeline. Answer with the result only: \
+no introduction, no explanation, no quotes or code fences around it.";
+
+// The prompt for one record; without a `{}` the record is appended
+fn fill(template: &str, line: &str, record: Option<&Value>) -> String {
+    let mut prompt = if template.contains("{}") {
+        template.replace("{}", line)
+    } else {
+        format!("{}\n\n{}", template, line)
+    };
+    if let Some(Value::Object(fields)) = record {
+        for (key, value) in fields {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            prompt = prompt.replace(&format!("{{{}}}", key), &value);
+        }
+    }
+    prompt
+}
+
+// One answer, folded onto a single line
+async fn apply(client: &impl ChatBackend, model: &str, system: &str, prompt: String) -> Result<String, Box<dyn std::error::Error>> {
+    let prompt = redact::check_outgoing(&prompt)?;
+    let model = routing::resolve_model(model, &prompt);
+    let chat_req = ChatRequest::new(vec![ChatMessage::system(system), ChatMessage::user(prompt)]);
+    let started = Instant::now();
+    let chat_res = with_deadline(request_deadline(), client.chat(&model, chat_req, None)).await?;
+    let elapsed = started.elapsed();
+    usage::record(&ExchangeStats::new(&model, &chat_res.usage, None, elapsed));
+    let answer = chat_res
+        .content_text_as_str()
+        .filter(|t| !t.trim().is_empty())
+        .ok_or(QueryError::NoAnswer)?;
+    Ok(answer.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+// The output line for one input line, or the error to report for it
+async fn process(
+    client: &impl ChatBackend,
+    model: &str,
+    system: &str,
+    template: &str,
+    jsonl: bool,
+    line: String,
+) -> Result<(String, Option<String>), Box<dyn std::error::Error>> {
+    if line.trim().is_empty() {
+        return Ok((line, None));
+    }
+    if !jsonl {
+        return match apply(client, model, system, fill(template, &line, None)).await {
+            Ok(answer) => Ok((answer, None)),
+            Err(e) if matches!(e.downcast_ref::<QueryError>(), Some(QueryError::DryRun)) => Err(e),
+            Err(e) => Ok((String::new(), Some(error_message(e.as_ref())))),
+        };
+    }
+    let mut record: Value = match serde_json::from_str(&line) {
+        Ok(record @ Value::Object(_)) => record,
+        _ => return Ok((line, Some("not a JSON object".to_string()))),
+    };
+    let result = apply(client, model, system, fill(template, &line, Some(&record))).await;
+    let error = match result {
+        Ok(answer) => {
+            record["output"] = Value::String(answer);
+            None
+        }
+        Err(e) if matches!(e.downcast_ref::<QueryError>(), Some(QueryError::DryRun)) => return Err(e),
+        Err(e) => {
+            let message = error_message(e.as_ref());
+            record["error"] = Value::String(message.clone());
+            Some(message)
+        }
+    };
+    Ok((record.to_string(), error))
+}
+
+pub async fn handle_filter(
+    client: &impl ChatBackend,
+    model: &str,
+    prompt: &str,
+    system_prompt: Option<&str>,
+    jsonl: bool,
+    concurrency: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let system = system_prompt.unwrap_or(FILTER_SYSTEM_PROMPT);
+    let lines = BufReader::new(tokio::io::stdin()).lines();
+    // Lines are read as the workers free up, so huge or endless inputs stream through
+    let records = stream::unfold(lines, |mut lines| async move {
+        match lines.next_line().await {
+            Ok(Some(line)) => Some((Ok(line), lines)),
+            Ok(None) => None,
+            Err(e) => Some((Err(e), lines)),
+        }
+    });
+    let results = records
+        .map(|line| async move {
+            match line {
+                Ok(line) => process(client, model, system, prompt, jsonl, line).await,
+                Err(e) => Err(QueryError::Input(format!("Failed to read stdin: {}", e)).into()),
+            }
+        })
+        .buffered(concurrency.max(1))
+        .enumerate();
+    let mut results = std::pin::pin!(results);
+    let mut stdout = std::io::stdout();
+    let (mut total, mut failed) = (0, 0);
+    while let Some((index, result)) = results.next().await {
+        let (output, error) = result?;
+        total += 1;
+        if let Some(error) = error {
+            failed += 1;
+            eprintln!("line {}: {}", index + 1, error);
+        }
+        writeln!(stdout, "{}", output)?;
+        // Downstream commands see each result as soon as it is ready
+        stdout.flush()?;
+    }
+    if failed > 0 {
+        return Err(QueryError::Input(format!("{} of {} lines failed", failed, total)).into());
+    }
+    Ok(())
+}
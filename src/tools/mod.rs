@@ -1 +1,17 @@
+pub mod bench;
 pub mod build_release;
+pub mod cargo_diagnostics;
+pub mod ctx;
+pub mod explain;
+pub mod extract;
+pub mod filter;
+pub mod ping;
+pub mod pipe;
+pub mod replay;
+pub mod rewrite;
+pub mod script;
+pub mod sessions;
+pub mod summarize;
+pub mod sync;
+pub mod translate;
+pub mod watch;
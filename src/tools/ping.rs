@@ -0,0 +1,212 @@
+// tools/ping.rs
+// `llm ping [-m model]`: a one-token request to the default and alias models (or the -m ones),
+// and GET /models against every [[endpoints]] entry, all at once. Each row says whether the
+// target answered, refused the key, could not be reached or timed out, with the round-trip time.
+use crate::cli::{error_message, request_deadline, with_deadline, QueryError};
+use crate::config::{alias_models, Config, EndpointConfig};
+use crate::dry_run;
+use crate::endpoints::{self, ChatBackend};
+use crate::offline;
+use crate::ui::spinner::Spinner;
+use crate::ui::text_width;
+use crate::ui::theme;
+use futures::future::join_all;
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest};
+use genai::Client;
+use std::time::{Duration, Instant};
+use tokio::time::Instant as Deadline;
+
+// Used when --timeout is not given
+const PING_TIMEOUT: Duration = Duration::from_secs(15);
+
+enum Target {
+    Model { model: String, role: String },
+    Endpoint(&'static EndpointConfig),
+}
+
+impl Target {
+    fn name(&self) -> &str {
+        match self {
+            Target::Model { model, .. } => model,
+            Target::Endpoint(endpoint) => &endpoint.name,
+        }
+    }
+
+    fn role(&self) -> &str {
+        match self {
+            Target::Model { role, .. } => role,
+            Target::Endpoint(_) => "endpoint",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Auth,
+    Unreachable,
+    Timeout,
+    Failed,
+}
+
+impl Status {
+    fn text(self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Auth => "auth",
+            Status::Unreachable => "unreachable",
+            Status::Timeout => "timeout",
+            Status::Failed => "error",
+        }
+    }
+}
+
+// What kind of failure `err` is
+fn diagnose(err: &(dyn std::error::Error + 'static)) -> Status {
+    use genai::webc;
+    if matches!(err.downcast_ref::<QueryError>(), Some(QueryError::Timeout(_))) {
+        return Status::Timeout;
+    }
+    match err.downcast_ref::<genai::Error>() {
+        Some(
+            genai::Error::RequiresApiKey { .. }
+            | genai::Error::NoAuthResolver { .. }
+            | genai::Error::NoAuthData { .. }
+            | genai::Error::Resolver {
+                resolver_error: genai::resolver::Error::ApiKeyEnvNotFound { .. },
+                ..
+            },
+        ) => Status::Auth,
+        Some(genai::Error::WebModelCall { webc_error, .. } | genai::Error::WebAdapterCall { webc_error, .. }) => {
+            match webc_error {
+                webc::Error::ResponseFailedStatus { status, .. } if matches!(status.as_u16(), 401 | 403) => Status::Auth,
+                webc::Error::Reqwest(_) => Status::Unreachable,
+                _ => Status::Failed,
+            }
+        }
+        Some(genai::Error::WebStream { .. } | genai::Error::ReqwestEventSource(_)) => Status::Unreachable,
+        _ => Status::Failed,
+    }
+}
+
+fn deadline() -> Deadline {
+    request_deadline().unwrap_or_else(|| Deadline::now() + PING_TIMEOUT)
+}
+
+async fn ping_model(client: &Client, model: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let chat_req = ChatRequest::new(vec![ChatMessage::user("ping")]);
+    let options = ChatOptions::default().with_max_tokens(1);
+    with_deadline(Some(deadline()), client.chat(model, chat_req, Some(&options))).await?;
+    Ok(())
+}
+
+async fn ping_endpoint(http: &reqwest::Client, endpoint: &EndpointConfig) -> (Status, String) {
+    if let Err(e) = offline::check_url(&endpoint.url, "Checking the endpoint") {
+        return (Status::Failed, e.to_string());
+    }
+    let request = endpoints::models_request(http, endpoint).send();
+    match with_deadline(Some(deadline()), request).await {
+        Ok(res) if res.status().is_success() => (Status::Ok, String::new()),
+        Ok(res) if matches!(res.status().as_u16(), 401 | 403) => (Status::Auth, format!("HTTP {}", res.status())),
+        // Up, but without a model list to check the key against
+        Ok(res) if res.status().as_u16() == 404 => (Status::Ok, "no /models to check the key".to_string()),
+        Ok(res) => (Status::Failed, format!("HTTP {}", res.status())),
+        Err(e) => {
+            let status = match e.downcast_ref::<QueryError>() {
+                Some(QueryError::Timeout(_)) => Status::Timeout,
+                _ => Status::Unreachable,
+            };
+            (status, error_message(e.as_ref()))
+        }
+    }
+}
+
+async fn ping(client: &Client, http: &reqwest::Client, target: &Target) -> (Status, Duration, String) {
+    let started = Instant::now();
+    let (status, detail) = match target {
+        Target::Model { model, .. } => match ping_model(client, model).await {
+            Ok(()) => (Status::Ok, String::new()),
+            Err(e) => (diagnose(e.as_ref()), error_message(e.as_ref())),
+        },
+        Target::Endpoint(endpoint) => ping_endpoint(http, endpoint).await,
+    };
+    (status, started.elapsed(), detail)
+}
+
+// The -m models, or the default model, the aliases and the endpoints, each once
+fn targets(config: &Config, default_model: &str, models: Vec<String>) -> Vec<Target> {
+    let mut targets: Vec<Target> = Vec::new();
+    let mut add = |model: String, role: String| {
+        if !targets.iter().any(|t| matches!(t, Target::Model { model: m, .. } if *m == model)) {
+            targets.push(Target::Model { model, role });
+        }
+    };
+    if !models.is_empty() {
+        for model in models {
+            add(model, "-m".to_string());
+        }
+        return targets;
+    }
+    add(default_model.to_string(), "default".to_string());
+    for (i, model) in alias_models(config).into_iter().enumerate() {
+        add(model, format!("alias {}", i));
+    }
+    targets.extend(endpoints::configured().iter().map(Target::Endpoint));
+    targets
+}
+
+pub async fn handle_ping(
+    client: &Client,
+    http: &reqwest::Client,
+    config: &Config,
+    default_model: &str,
+    models: Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let targets = targets(config, default_model, models);
+    if dry_run::is_enabled() {
+        // Show the probe once; nothing is sent
+        let model = targets.iter().find_map(|t| match t {
+            Target::Model { model, .. } => Some(model.as_str()),
+            Target::Endpoint(_) => None,
+        });
+        return match model {
+            Some(model) => ping_model(client, model).await,
+            None => Ok(()),
+        };
+    }
+    let mut spinner = Spinner::start(&format!("Pinging {} targets", targets.len()));
+    let results = join_all(targets.iter().map(|target| ping(client, http, target))).await;
+    spinner.stop();
+
+    let name_width = targets.iter().map(|t| text_width::display_width(t.name())).max().unwrap_or(0).max(6);
+    let role_width = targets.iter().map(|t| t.role().len()).max().unwrap_or(0).max(4);
+    println!(
+        "{}",
+        theme::HEADING.paint(format!(
+            "{}  {}  {}  {:>7}  detail",
+            text_width::pad("target", name_width),
+            text_width::pad("kind", role_width),
+            text_width::pad("status", 11),
+            "time"
+        ))
+    );
+    let mut failed = 0;
+    for (target, (status, elapsed, detail)) in targets.iter().zip(&results) {
+        if *status != Status::Ok {
+            failed += 1;
+        }
+        let style = if *status == Status::Ok { theme::GOOD } else { theme::BAD };
+        println!(
+            "{}  {}  {}  {:>6.2}s  {}",
+            theme::ACCENT.paint(text_width::pad(target.name(), name_width)),
+            text_width::pad(target.role(), role_width),
+            style.paint(text_width::pad(status.text(), 11)),
+            elapsed.as_secs_f64(),
+            theme::DIM.paint(text_width::truncate(detail, 80))
+        );
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} targets failed", failed, targets.len()).into());
+    }
+    Ok(())
+}
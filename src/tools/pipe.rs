@@ -0,0 +1,97 @@
+// tools/pipe.rs
+// `llm pipe --in /tmp/llm.in --out /tmp/llm.out`: one persistent chat session over two FIFOs, for
+// tmux and editor integrations. Everything written to the input FIFO until the writer closes it
+// is one prompt; the answer is written to the output FIFO line by line, and closing it afterwards
+// ends the reader (`cat /tmp/llm.out`). Slash commands (/clear, /model <name>, /quit) work as in
+// interactive mode and print to the terminal running `llm pipe`, not to the output FIFO.
+use crate::chat_session::ChatSession;
+use crate::cli::{error_message, QueryError};
+use crate::config::Config;
+use crate::endpoints::ChatBackend;
+use crate::sse_event::SseEvent;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+
+// Create `path` as a FIFO unless it already is one
+fn ensure_fifo(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.file_type().is_fifo() => Ok(()),
+        Ok(_) => Err(QueryError::Input(format!("'{}' exists and is not a FIFO", path.display())).into()),
+        Err(_) => {
+            let c_path = CString::new(path.as_os_str().as_bytes())?;
+            // SAFETY: c_path is a valid NUL-terminated path
+            if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+                let e = std::io::Error::last_os_error();
+                return Err(format!("Failed to create FIFO '{}': {}", path.display(), e).into());
+            }
+            Ok(())
+        }
+    }
+}
+
+// The answer to `prompt`, line by line; write errors (the reader went away) end it early
+async fn answer(
+    session: &mut ChatSession,
+    client: &impl ChatBackend,
+    prompt: &str,
+    out: &mut tokio::fs::File,
+) -> std::io::Result<()> {
+    let mut response = match session.send(prompt, client).await {
+        Ok(response) => response,
+        Err(e) => return out.write_all(format!("Error: {}\n", error_message(e.as_ref())).as_bytes()).await,
+    };
+    while let Some(event) = response.events.recv().await {
+        match event {
+            SseEvent::Text(line) => out.write_all(format!("{}\n", line).as_bytes()).await?,
+            SseEvent::Error(message) => out.write_all(format!("Error: {}\n", message).as_bytes()).await?,
+            SseEvent::Done => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+pub async fn handle_pipe(
+    client: &impl ChatBackend,
+    model: &str,
+    config: &Config,
+    input: &Path,
+    output: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    ensure_fifo(input)?;
+    ensure_fifo(output)?;
+    let mut session = ChatSession::new(model.to_string(), false, String::new());
+    eprintln!(
+        "Reading prompts from {}, answers go to {} (write /quit to stop)",
+        input.display(),
+        output.display()
+    );
+    loop {
+        // Waits for a writer; the prompt is complete when it closes the FIFO
+        let prompt = tokio::fs::read_to_string(input).await?;
+        let prompt = prompt.trim();
+        if prompt.is_empty() {
+            continue;
+        }
+        if let Some(command) = prompt.strip_prefix('/') {
+            if session.handle_command(command, client).await? {
+                break;
+            }
+            continue;
+        }
+        // Waits for a reader
+        let mut out = tokio::fs::OpenOptions::new().write(true).open(output).await?;
+        if let Err(e) = answer(&mut session, client, prompt, &mut out).await {
+            eprintln!("Failed to write {}: {}", output.display(), e);
+        }
+        // Closing the FIFO ends the answer for the reader
+        drop(out);
+    }
+    if config.autosave_sessions.unwrap_or(true) {
+        session.autosave(client).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+// tools/replay.rs
+// `llm replay <session>`: a saved conversation is played back one message at a time, answers
+// through the markdown renderer as they looked live. Nothing is loaded into a chat session and
+// nothing is sent. A key advances (q or Esc stops), or --speed advances on its own; without a
+// terminal everything is printed at once.
+use crate::cli::QueryError;
+use crate::config::get_sessions_dir;
+use crate::markdown_render::MarkdownRender;
+use crate::output;
+use crate::session_browser::SessionEntry;
+use crate::ui::theme;
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::{cursor, execute, terminal};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Raw mode only while waiting for the key
+struct KeyGuard;
+
+impl KeyGuard {
+    fn start() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(KeyGuard)
+    }
+}
+
+impl Drop for KeyGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), terminal::Clear(terminal::ClearType::CurrentLine), cursor::MoveToColumn(0));
+    }
+}
+
+// Whether to go on to the next message
+fn wait_for_key(shown: usize, total: usize) -> io::Result<bool> {
+    print!("{}", theme::DIM.paint(format!("[{}/{}] any key: next, q: quit", shown, total)));
+    io::stdout().flush()?;
+    let _guard = KeyGuard::start()?;
+    loop {
+        if let Event::Key(key) = event::read()? {
+            let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            return Ok(!(ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)));
+        }
+    }
+}
+
+// A file in the sessions dir, or a path
+fn session_path(name: &str) -> Result<PathBuf, QueryError> {
+    let in_sessions = get_sessions_dir().join(name);
+    if in_sessions.is_file() {
+        return Ok(in_sessions);
+    }
+    let path = PathBuf::from(name);
+    if path.is_file() {
+        return Ok(path);
+    }
+    Err(QueryError::Input(format!("No saved session '{}' (see `/load --list`)", name)))
+}
+
+fn print_message(role: &str, text: &str, model: &str) {
+    println!();
+    match role {
+        "User" => {
+            println!("{}", theme::PROMPT.paint("You:"));
+            for line in text.trim_end().lines() {
+                println!("{}", line);
+            }
+        }
+        _ => {
+            println!("{}", theme::ACCENT.paint(format!("{}:", model)));
+            let mut render = MarkdownRender::new();
+            for line in text.trim_end().split('\n') {
+                println!("{}", output::styled(&render.render_line_mut(line)));
+            }
+        }
+    }
+}
+
+// `speed` is the pause between messages in seconds
+pub async fn handle_replay(session: &str, speed: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let speed = match speed {
+        Some(text) => match text.parse::<f64>() {
+            Ok(secs) if secs.is_finite() && secs >= 0.0 => Some(secs),
+            _ => return Err(QueryError::Input(format!("Invalid --speed '{}' (seconds, e.g. 2 or 0.5)", text)).into()),
+        },
+        None => None,
+    };
+    let path = session_path(session)?;
+    let entry = SessionEntry::read(&path)
+        .filter(|entry| !entry.messages.is_empty())
+        .ok_or_else(|| QueryError::Input(format!("'{}' has no messages to replay", path.display())))?;
+    let interactive = speed.is_none() && io::stdin().is_terminal() && io::stdout().is_terminal();
+
+    println!(
+        "{} {} {}",
+        theme::HEADING.paint(entry.title.as_deref().unwrap_or(&entry.filename)),
+        theme::MUTED.paint(entry.modified.format("%Y-%m-%d %H:%M")),
+        theme::ACCENT.paint(&entry.model)
+    );
+    let total = entry.messages.len();
+    for (i, (role, text)) in entry.messages.iter().enumerate() {
+        print_message(role, text, &entry.model);
+        if i + 1 == total {
+            break;
+        }
+        if interactive {
+            if !wait_for_key(i + 1, total)? {
+                return Ok(());
+            }
+        } else if let Some(secs) = speed {
+            io::stdout().flush()?;
+            tokio::time::sleep(Duration::from_secs_f64(secs)).await;
+        }
+    }
+    Ok(())
+}
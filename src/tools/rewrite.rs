@@ -0,0 +1,93 @@
+// tools/rewrite.rs
+// `llm rewrite --style concise -f mail.txt`: the text comes back corrected and rewritten in the
+// chosen style, and nothing else is printed to stdout (no header, spinner line or footer), so the
+// output can replace the input. --diff shows a word diff against the original instead.
+use crate::cli::{request_deadline, with_deadline, QueryError};
+use crate::endpoints::ChatBackend;
+use crate::ingest;
+use crate::output;
+use crate::redact;
+use crate::routing;
+use crate::ui::spinner::Spinner;
+use crate::ui::theme;
+use crate::usage::{self, ExchangeStats};
+use crate::word_diff;
+use genai::chat::{ChatMessage, ChatRequest};
+use std::io::{self, IsTerminal, Read};
+use std::path::Path;
+use std::time::Instant;
+
+const REWRITE_SYSTEM_PROMPT: &str = "You are an editor. Rewrite the text you are given as instructed and fix \
+grammar, spelling and punctuation. Keep its language, meaning, markdown, code, URLs and names. Reply with the \
+rewritten text only: no introduction, no comments, no quotes or code fences around it.";
+
+const STYLES: &[(&str, &str)] = &[
+    ("correct", "Only fix grammar, spelling and punctuation; keep the wording and tone otherwise."),
+    ("formal", "Make it formal and professional, suitable for business correspondence."),
+    ("concise", "Make it concise: cut filler and repetition, keep every point."),
+    ("friendly", "Make it warm and friendly while staying clear."),
+];
+
+// Text from -f, the command line, or piped stdin
+fn read_source(file: Option<String>, text: Vec<String>) -> Result<String, QueryError> {
+    let source = match file {
+        Some(path) => ingest::read_file(Path::new(&path)).map_err(QueryError::Input)?,
+        None if !text.is_empty() => text.join(" "),
+        None if !io::stdin().is_terminal() => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(|e| QueryError::Input(format!("Failed to read stdin: {}", e)))?;
+            buf
+        }
+        None => return Err(QueryError::Input("Nothing to rewrite: give text, -f <file> or pipe stdin".to_string())),
+    };
+    if source.trim().is_empty() {
+        return Err(QueryError::Input("Nothing to rewrite: the input is empty".to_string()));
+    }
+    Ok(source)
+}
+
+pub async fn handle_rewrite(
+    client: &impl ChatBackend,
+    model: &str,
+    style: &str,
+    file: Option<String>,
+    text: Vec<String>,
+    diff: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let instruction = STYLES
+        .iter()
+        .find(|(name, _)| *name == style)
+        .map(|(_, instruction)| *instruction)
+        .ok_or_else(|| QueryError::Input(format!("Unknown style '{}'", style)))?;
+    let source = read_source(file, text)?;
+    let prompt = redact::check_outgoing(&format!("{}\n\n{}", instruction, source.trim_end()))?;
+    let model = routing::resolve_model(model, &prompt);
+    let chat_req = ChatRequest::new(vec![ChatMessage::system(REWRITE_SYSTEM_PROMPT), ChatMessage::user(prompt)]);
+
+    let mut spinner = Spinner::start(&format!("Rewriting ({}) with {}", style, model));
+    let started = Instant::now();
+    let chat_res = with_deadline(request_deadline(), client.chat(&model, chat_req, None)).await;
+    spinner.stop();
+    let chat_res = chat_res?;
+    let elapsed = started.elapsed();
+    usage::record(&ExchangeStats::new(&model, &chat_res.usage, None, elapsed));
+    let rewritten = chat_res
+        .content_text_as_str()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .ok_or(QueryError::NoAnswer)?;
+
+    if !diff {
+        println!("{}", rewritten);
+        return Ok(());
+    }
+    let changes = word_diff::diff(source.trim_end(), rewritten);
+    let (removed, added) = word_diff::counts(&changes);
+    println!("{}", word_diff::render(&changes));
+    if !output::is_quiet() {
+        eprintln!("{}", theme::DIM.paint_err(format!("({} words removed, {} added)", removed, added)));
+    }
+    Ok(())
+}
@@ -0,0 +1,151 @@
+// tools/script.rs
+use crate::chat_session::{ChatSession, SessionState};
+use crate::cli::{error_message, request_deadline, with_deadline, QueryError};
+use crate::config::get_sessions_dir;
+use crate::endpoints;
+use crate::redact;
+use crate::routing;
+use crate::usage::{self, ExchangeStats};
+use genai::chat::{ChatMessage, ChatRequest, MessageContent};
+use genai::Client;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::time::Instant;
+
+/// Conversation handle exposed to scripts via `session(model)`.
+#[derive(Clone)]
+struct ScriptSession {
+    model: String,
+    messages: Vec<ChatMessage>,
+}
+
+// Run a blocking chat call from inside the (synchronous) script engine. The last message is
+// checked for secrets first and replaced by what was actually sent.
+fn exec_chat_blocking(
+    client: &Client,
+    model: &str,
+    messages: &mut [ChatMessage],
+) -> Result<String, Box<EvalAltResult>> {
+    let mut prompt = String::new();
+    if let Some(MessageContent::Text(text)) = messages.last_mut().map(|m| &mut m.content) {
+        *text = redact::check_outgoing(text).map_err(|e| format!("ask({}) failed: {}", model, e))?;
+        prompt = text.clone();
+    }
+    let model = &routing::resolve_model(model, &prompt);
+    let chat_req = ChatRequest::new(messages.to_vec());
+    // handle_script runs the engine inside block_in_place, so blocking on the runtime is allowed here
+    let started = Instant::now();
+    let request = endpoints::exec_chat(client, model, chat_req, None);
+    let chat_res = tokio::runtime::Handle::current()
+        .block_on(with_deadline(request_deadline(), request))
+        .map_err(|e| format!("ask({}) failed: {}", model, error_message(e.as_ref())))?;
+    let elapsed = started.elapsed();
+    usage::record(&ExchangeStats::new(model, &chat_res.usage, None, elapsed));
+    match chat_res.content_text_as_str().filter(|t| !t.trim().is_empty()) {
+        Some(text) => Ok(text.to_string()),
+        None => Err(format!("ask({}) failed: {}", model, QueryError::NoAnswer).into()),
+    }
+}
+
+fn build_engine(client: &Client, default_model: &str) -> Engine {
+    let mut engine = Engine::new();
+
+    // ask(prompt) / ask(model, prompt): one-shot question, returns the answer text
+    let (c, m) = (client.clone(), default_model.to_string());
+    engine.register_fn("ask", move |prompt: &str| -> Result<String, Box<EvalAltResult>> {
+        exec_chat_blocking(
+            &c,
+            &m,
+            &mut [
+                ChatMessage::system("Answer concisely and clearly"),
+                ChatMessage::user(prompt),
+            ],
+        )
+    });
+    let c = client.clone();
+    engine.register_fn(
+        "ask",
+        move |model: &str, prompt: &str| -> Result<String, Box<EvalAltResult>> {
+            exec_chat_blocking(
+                &c,
+                model,
+                &mut [
+                    ChatMessage::system("Answer concisely and clearly"),
+                    ChatMessage::user(prompt),
+                ],
+            )
+        },
+    );
+
+    // session() / session(model): multi-turn conversation kept across calls
+    engine.register_type_with_name::<ScriptSession>("Session");
+    let m = default_model.to_string();
+    engine.register_fn("session", move || ScriptSession {
+        model: m.clone(),
+        messages: vec![ChatMessage::system(
+            "You are a helpful AI assistant. Answer concisely and clearly.",
+        )],
+    });
+    engine.register_fn("session", |model: &str| ScriptSession {
+        model: model.to_string(),
+        messages: vec![ChatMessage::system(
+            "You are a helpful AI assistant. Answer concisely and clearly.",
+        )],
+    });
+    engine.register_fn("system", |s: &mut ScriptSession, prompt: &str| {
+        s.messages[0] = ChatMessage::system(prompt);
+    });
+    engine.register_get("model", |s: &mut ScriptSession| s.model.clone());
+    engine.register_set("model", |s: &mut ScriptSession, model: &str| {
+        s.model = model.to_string();
+    });
+    let c = client.clone();
+    engine.register_fn(
+        "ask",
+        move |s: &mut ScriptSession, prompt: &str| -> Result<String, Box<EvalAltResult>> {
+            s.messages.push(ChatMessage::user(prompt));
+            let answer = exec_chat_blocking(&c, &s.model, &mut s.messages)?;
+            s.messages.push(ChatMessage::assistant(answer.clone()));
+            Ok(answer)
+        },
+    );
+
+    // save(session, name): write to the sessions dir so `/load name` can resume it
+    engine.register_fn(
+        "save",
+        |s: &mut ScriptSession, name: &str| -> Result<(), Box<EvalAltResult>> {
+            let filename = ChatSession::clean_filename(name);
+            let state = SessionState::from_messages(s.messages.clone(), s.model.clone(), Some(filename.clone()));
+            let file = File::create(get_sessions_dir().join(&filename))
+                .map_err(|e| format!("save({}) failed: {}", filename, e))?;
+            serde_json::to_writer_pretty(BufWriter::new(file), &state)
+                .map_err(|e| format!("save({}) failed: {}", filename, e))?;
+            Ok(())
+        },
+    );
+
+    engine.register_fn("read_file", |path: &str| -> Result<String, Box<EvalAltResult>> {
+        fs::read_to_string(path).map_err(|e| format!("read_file({}) failed: {}", path, e).into())
+    });
+
+    engine
+}
+
+pub async fn handle_script(
+    client: &Client,
+    model: &str,
+    file: &str,
+    args: Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = fs::read_to_string(file)
+        .map_err(|e| QueryError::Input(format!("Failed to read script '{}': {}", file, e)))?;
+    let engine = build_engine(client, model);
+    let mut scope = Scope::new();
+    let script_args: Array = args.into_iter().map(Dynamic::from).collect();
+    scope.push("ARGS", script_args);
+
+    tokio::task::block_in_place(|| engine.run_with_scope(&mut scope, &source))
+        .map_err(|e| anyhow::anyhow!("Script '{}' failed: {}", file, e))?;
+    Ok(())
+}
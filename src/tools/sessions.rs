@@ -0,0 +1,80 @@
+// tools/sessions.rs
+use crate::cli::QueryError;
+use crate::config::get_sessions_dir;
+use crate::session_browser::{list_sessions, SessionEntry};
+use chrono::{Duration, Local};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::path::Path;
+
+// "90d", "12w", "36h" or a bare number of days
+pub fn parse_age(text: &str) -> Result<Duration, QueryError> {
+    let text = text.trim();
+    let (number, unit) = match text.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&text[..i], c),
+        _ => (text, 'd'),
+    };
+    let invalid = || QueryError::Input(format!("Invalid age '{}' (use e.g. 90d, 12w or 36h)", text));
+    let n: i64 = number.parse().map_err(|_| invalid())?;
+    match unit {
+        'h' => Ok(Duration::hours(n)),
+        'd' => Ok(Duration::days(n)),
+        'w' => Ok(Duration::weeks(n)),
+        _ => Err(invalid()),
+    }
+}
+
+fn write_archive(archive: &Path, sessions_dir: &Path, entries: &[&SessionEntry]) -> std::io::Result<()> {
+    let file = File::create(archive)?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for entry in entries {
+        tar.append_path_with_name(sessions_dir.join(&entry.filename), &entry.filename)?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+// `llm sessions prune`: delete sessions not modified within `older_than`, archiving them first
+pub fn handle_prune(older_than: &str, archive: Option<String>, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let cutoff = Local::now() - parse_age(older_than)?;
+    let sessions_dir = get_sessions_dir();
+    let entries = list_sessions(&sessions_dir)?;
+    let old: Vec<&SessionEntry> = entries.iter().filter(|e| e.modified < cutoff).collect();
+    if old.is_empty() {
+        println!("No sessions older than {}.", older_than);
+        return Ok(());
+    }
+    for entry in &old {
+        println!("- {} ({})", entry.filename, entry.modified.format("%Y-%m-%d"));
+    }
+    if dry_run {
+        println!("{} sessions would be pruned.", old.len());
+        return Ok(());
+    }
+    if let Some(archive) = &archive {
+        let path = Path::new(archive);
+        if path.exists() {
+            return Err(QueryError::Input(format!("Archive '{}' already exists", archive)).into());
+        }
+        write_archive(path, &sessions_dir, &old)?;
+        println!("Archived {} sessions to '{}'", old.len(), archive);
+    }
+    for entry in &old {
+        fs::remove_file(sessions_dir.join(&entry.filename))?;
+    }
+    println!("Pruned {} sessions.", old.len());
+    Ok(())
+}
+
+// Keep at most `max_sessions` files, removing the least recently modified; returns how many went
+pub fn rotate_sessions(max_sessions: usize) -> std::io::Result<usize> {
+    let sessions_dir = get_sessions_dir();
+    let entries = list_sessions(&sessions_dir)?;
+    let mut removed = 0;
+    for entry in entries.iter().skip(max_sessions.max(1)) {
+        fs::remove_file(sessions_dir.join(&entry.filename))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,145 @@
+// tools/summarize.rs
+// `llm summarize -f notes.md --length bullet`. Presets are message templates like the ones in
+// mic_routes: `{input}` is replaced by the text. `[summary_templates]` in config adds or
+// overrides presets.
+use crate::cli::{execute_query, QueryError};
+use crate::condense;
+use crate::config::Config;
+use crate::dry_run;
+use crate::ingest;
+use crate::offline;
+use crate::output;
+use crate::ui::theme;
+use regex::Regex;
+use std::io::{self, IsTerminal, Read};
+use std::path::Path;
+
+const SUMMARY_SYSTEM_PROMPT: &str =
+    "You summarize documents accurately, using only information from the text. Answer in the language of the text.";
+
+const PRESETS: &[(&str, &str)] = &[
+    ("short", "Summarize the following text in two or three sentences.\n\n{input}"),
+    (
+        "bullet",
+        "Summarize the following text as 5 to 8 concise bullet points, most important first.\n\n{input}",
+    ),
+    (
+        "detailed",
+        "Write a detailed summary of the following text: a short overview, then one section per main \
+topic with its key points, figures and conclusions.\n\n{input}",
+    ),
+];
+
+fn template_for(config: &Config, length: &str) -> Result<String, QueryError> {
+    if let Some(template) = config.summary_templates.as_ref().and_then(|t| t.get(length)) {
+        return Ok(template.clone());
+    }
+    PRESETS
+        .iter()
+        .find(|(name, _)| *name == length)
+        .map(|(_, template)| template.to_string())
+        .ok_or_else(|| {
+            let mut names: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+            names.extend(config.summary_templates.iter().flat_map(|t| t.keys().map(String::as_str)));
+            QueryError::Input(format!("Unknown length '{}' (use {})", length, names.join(", ")))
+        })
+}
+
+// A file, an http(s) URL, or piped stdin when no source (or "-") is given
+async fn read_source(http: &reqwest::Client, source: Option<&str>) -> Result<String, QueryError> {
+    let text = match source {
+        Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+            offline::check_url(url, "Fetching the page")?;
+            let fetch_error = |e: reqwest::Error| QueryError::Input(format!("Failed to fetch {}: {}", url, e));
+            let response = http.get(url).send().await.and_then(|r| r.error_for_status()).map_err(fetch_error)?;
+            let is_html = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.contains("html"));
+            let body = response.text().await.map_err(fetch_error)?;
+            if is_html {
+                html_to_text(&body)
+            } else {
+                body
+            }
+        }
+        Some(path) if path != "-" => {
+            ingest::read_file(Path::new(path)).map_err(QueryError::Input)?
+        }
+        _ if io::stdin().is_terminal() => {
+            return Err(QueryError::Input("Nothing to summarize: give -f <file|url> or pipe stdin".to_string()))
+        }
+        _ => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(|e| QueryError::Input(format!("Failed to read stdin: {}", e)))?;
+            buf
+        }
+    };
+    if text.trim().is_empty() {
+        return Err(QueryError::Input("Nothing to summarize: the input is empty".to_string()));
+    }
+    Ok(text)
+}
+
+// Readable text of a web page: scripts and styles dropped, block ends as line breaks
+fn html_to_text(html: &str) -> String {
+    let hidden = Regex::new(r"(?is)<script.*?</script>|<style.*?</style>|<noscript.*?</noscript>|<!--.*?-->").unwrap();
+    let breaks = Regex::new(r"(?i)<br\s*/?>|</(p|div|li|tr|h[1-6]|section|article)>").unwrap();
+    let tags = Regex::new(r"(?s)<[^>]*>").unwrap();
+    let text = hidden.replace_all(html, "");
+    let text = breaks.replace_all(&text, "\n");
+    let text = tags.replace_all(&text, "");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if !line.is_empty() || lines.last().is_some_and(|last| !last.is_empty()) {
+            lines.push(line);
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_summarize(
+    client: &genai::Client,
+    http: &reqwest::Client,
+    config: &Config,
+    model: &str,
+    source: Option<String>,
+    length: &str,
+    out_file: Option<String>,
+    stream: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let template = template_for(config, length)?;
+    let text = read_source(http, source.as_deref()).await?;
+    if !output::is_quiet() && !output::is_raw() {
+        println!(
+            "Summarizing {} ({}) with {}",
+            theme::ACCENT.paint(source.as_deref().unwrap_or("stdin")),
+            length,
+            theme::ACCENT.paint(model)
+        );
+    }
+    // Over the context: the parts are condensed first and the summary is written from the notes
+    let name = source.as_deref().unwrap_or("stdin");
+    let text = condense::fit(client, model, name, text.trim().to_string(), None).await?;
+    let prompt = template.replace("{input}", &text);
+    let summary = execute_query(client, model, &prompt, stream, false, Some(SUMMARY_SYSTEM_PROMPT)).await?;
+    // Nothing was generated in a dry run
+    if let Some(path) = out_file.filter(|_| !dry_run::is_enabled()) {
+        std::fs::write(&path, format!("{}\n", summary.trim_end()))?;
+        if !output::is_quiet() {
+            eprintln!("Summary written to {}", path);
+        }
+    }
+    Ok(())
+}
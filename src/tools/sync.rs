@@ -0,0 +1,386 @@
+// tools/sync.rs
+// `llm sync push` / `llm sync pull`: the sessions directory travels as one encrypted archive,
+// llm-sessions.enc, kept at `sync_remote` (a git repo, an S3 prefix, a WebDAV folder or an rclone
+// remote). The archive is a tar.gz sealed with AES-256-GCM under a key derived from the sync
+// passphrase, so the remote only ever sees ciphertext. Both directions merge by modification
+// time: a session is taken from the other side when it is missing or newer there, and push
+// merges the remote copy in before uploading so sessions saved on other machines are kept.
+use crate::cli::QueryError;
+use crate::config::{get_cache_dir, get_sessions_dir, Config};
+use crate::endpoints;
+use crate::offline;
+use crate::output;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::digest;
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::io::{Read, Write};
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const ARCHIVE: &str = "llm-sessions.enc";
+const MAGIC: &[u8] = b"LLMSYNC1";
+const SALT_LEN: usize = 16;
+const KEY_ROUNDS: u32 = 200_000;
+
+enum Remote {
+    Git(String),
+    S3(String),
+    WebDav(String),
+    Rclone(String),
+}
+
+impl Remote {
+    fn parse(spec: &str) -> Result<Remote, QueryError> {
+        let spec = spec.trim().trim_end_matches('/');
+        if spec.starts_with("s3://") {
+            Ok(Remote::S3(spec.to_string()))
+        } else if let Some(url) = spec.strip_prefix("webdav+") {
+            Ok(Remote::WebDav(url.to_string()))
+        } else if let Some(remote) = spec.strip_prefix("rclone:") {
+            Ok(Remote::Rclone(remote.to_string()))
+        } else if let Some(url) = spec.strip_prefix("git+") {
+            Ok(Remote::Git(url.to_string()))
+        } else if spec.ends_with(".git") {
+            Ok(Remote::Git(spec.to_string()))
+        } else {
+            Err(QueryError::Input(format!(
+                "Unknown sync_remote '{}' (use a .git URL, s3://bucket/prefix, webdav+https://host/dir or rclone:remote:path)",
+                spec
+            )))
+        }
+    }
+
+    // The sealed archive on the remote, None when nothing was pushed yet
+    async fn download(&self) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        match self {
+            Remote::Git(url) => {
+                let repo = git_checkout(url)?;
+                match std::fs::read(repo.join(ARCHIVE)) {
+                    Ok(data) => Ok(Some(data)),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            Remote::S3(prefix) => {
+                let object = format!("{}/{}", prefix, ARCHIVE);
+                match run(Command::new("aws").args(["s3", "cp", &object, "-"]), None) {
+                    Ok(data) => Ok(Some(data)),
+                    Err(e) if e.contains("404") || e.contains("Not Found") => Ok(None),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            Remote::WebDav(url) => {
+                let res = webdav(endpoints::http_client().get(format!("{}/{}", url, ARCHIVE))).send().await?;
+                if res.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                if !res.status().is_success() {
+                    return Err(format!("WebDAV download failed ({})", res.status()).into());
+                }
+                Ok(Some(res.bytes().await?.to_vec()))
+            }
+            Remote::Rclone(remote) => {
+                match run(Command::new("rclone").args(["cat", &format!("{}/{}", remote, ARCHIVE)]), None) {
+                    Ok(data) => Ok(Some(data)),
+                    Err(e) if e.contains("not found") => Ok(None),
+                    Err(e) => Err(e.into()),
+                }
+            }
+        }
+    }
+
+    async fn upload(&self, data: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Remote::Git(url) => {
+                let repo = git_checkout(url)?;
+                std::fs::write(repo.join(ARCHIVE), &data)?;
+                let repo = repo.to_string_lossy().to_string();
+                run(Command::new("git").args(["-C", &repo, "add", ARCHIVE]), None)?;
+                let unchanged = Command::new("git")
+                    .args(["-C", &repo, "diff", "--cached", "--quiet"])
+                    .status()
+                    .is_ok_and(|status| status.success());
+                if !unchanged {
+                    let message = format!("Sync sessions {}", chrono::Local::now().format("%Y-%m-%d %H:%M"));
+                    run(Command::new("git").args(["-C", &repo, "commit", "-q", "-m", &message]), None)?;
+                }
+                run(Command::new("git").args(["-C", &repo, "push", "-q", "origin", "HEAD"]), None)?;
+            }
+            Remote::S3(prefix) => {
+                run(Command::new("aws").args(["s3", "cp", "-", &format!("{}/{}", prefix, ARCHIVE)]), Some(&data))?;
+            }
+            Remote::WebDav(url) => {
+                let res = webdav(endpoints::http_client().put(format!("{}/{}", url, ARCHIVE))).body(data).send().await?;
+                if !res.status().is_success() {
+                    return Err(format!("WebDAV upload failed ({})", res.status()).into());
+                }
+            }
+            Remote::Rclone(remote) => {
+                run(Command::new("rclone").args(["rcat", &format!("{}/{}", remote, ARCHIVE)]), Some(&data))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// LLM_SYNC_USER / LLM_SYNC_PASSWORD as basic auth, when set
+fn webdav(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match std::env::var("LLM_SYNC_USER") {
+        Ok(user) => request.basic_auth(user, std::env::var("LLM_SYNC_PASSWORD").ok()),
+        Err(_) => request,
+    }
+}
+
+// stdout of a finished command, or its stderr as the error
+fn run(command: &mut Command, input: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input).map_err(|e| format!("Failed to write to {}: {}", program, e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("{} failed: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} failed ({}): {}", program, output.status, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output.stdout)
+}
+
+// A clone of the sync repo in the cache dir, up to date with the remote. One clone per URL, so a
+// changed sync_remote never pushes to the old one
+fn git_checkout(url: &str) -> Result<std::path::PathBuf, String> {
+    let url_hash = hex::encode(&digest::digest(&digest::SHA256, url.as_bytes()).as_ref()[..8]);
+    let repo = get_cache_dir().join(format!("sync-repo-{}", url_hash));
+    if !repo.join(".git").exists() {
+        run(Command::new("git").args(["clone", "-q", url]).arg(&repo), None)?;
+        return Ok(repo);
+    }
+    let path = repo.to_string_lossy().to_string();
+    if let Err(e) = run(Command::new("git").args(["-C", &path, "pull", "-q", "--ff-only"]), None) {
+        // Nothing to pull from a repo nobody pushed to yet
+        let heads = run(Command::new("git").args(["-C", &path, "ls-remote", "--heads", "origin"]), None)?;
+        if !heads.is_empty() {
+            return Err(e);
+        }
+    }
+    Ok(repo)
+}
+
+// LLM_SYNC_PASSPHRASE, or the output of `sync_passphrase_command` (e.g. "pass show llm-sync")
+fn passphrase(config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+    if let Ok(passphrase) = std::env::var("LLM_SYNC_PASSPHRASE") {
+        if !passphrase.is_empty() {
+            return Ok(passphrase);
+        }
+    }
+    let command = config.sync_passphrase_command.as_deref().ok_or_else(|| {
+        QueryError::Input("Set LLM_SYNC_PASSPHRASE or `sync_passphrase_command` to encrypt synced sessions".to_string())
+    })?;
+    let output = run(Command::new("sh").arg("-c").arg(command), None)?;
+    let passphrase = String::from_utf8_lossy(&output).trim_end_matches(['\r', '\n']).to_string();
+    if passphrase.is_empty() {
+        return Err(QueryError::Input("sync_passphrase_command printed an empty passphrase".to_string()).into());
+    }
+    Ok(passphrase)
+}
+
+fn key(passphrase: &str, salt: &[u8]) -> LessSafeKey {
+    let mut key = [0u8; 32];
+    let rounds = NonZeroU32::new(KEY_ROUNDS).expect("KEY_ROUNDS is not zero");
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, rounds, salt, passphrase.as_bytes(), &mut key);
+    LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key).expect("AES-256 takes a 32-byte key"))
+}
+
+// MAGIC, salt, nonce, then the ciphertext with its tag
+fn seal(mut data: Vec<u8>, passphrase: &str) -> Result<Vec<u8>, String> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt).and_then(|_| rng.fill(&mut nonce)).map_err(|_| "No randomness available")?;
+    key(passphrase, &salt)
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(MAGIC), &mut data)
+        .map_err(|_| "Encryption failed")?;
+    Ok([MAGIC, &salt, &nonce, &data].concat())
+}
+
+fn open(sealed: &[u8], passphrase: &str) -> Result<Vec<u8>, QueryError> {
+    let header = MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if sealed.len() < header || !sealed.starts_with(MAGIC) {
+        return Err(QueryError::Input(format!("The remote {} is not a sessions archive", ARCHIVE)));
+    }
+    let salt = &sealed[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let nonce = Nonce::try_assume_unique_for_key(&sealed[MAGIC.len() + SALT_LEN..header])
+        .map_err(|_| QueryError::Input("Damaged sessions archive".to_string()))?;
+    let mut data = sealed[header..].to_vec();
+    let plain = key(passphrase, salt)
+        .open_in_place(nonce, Aad::from(MAGIC), &mut data)
+        .map_err(|_| QueryError::Input("Cannot decrypt the synced sessions: wrong passphrase or damaged archive".to_string()))?;
+    Ok(plain.to_vec())
+}
+
+// tar.gz of the files in the sessions dir, and how many there are
+fn pack(dir: &Path) -> std::io::Result<(Vec<u8>, usize)> {
+    let mut tar = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    let mut count = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            tar.append_path_with_name(entry.path(), entry.file_name())?;
+            count += 1;
+        }
+    }
+    Ok((tar.into_inner()?.finish()?, count))
+}
+
+// Unpack the sessions that are missing or older in `dir`; returns (taken, in the archive)
+fn merge(archive: &[u8], dir: &Path) -> std::io::Result<(usize, usize)> {
+    let mut tar = tar::Archive::new(GzDecoder::new(archive));
+    let (mut taken, mut total) = (0, 0);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        // Plain file names only: nothing outside the sessions dir
+        if name.contains(['/', '\\']) || name.starts_with('.') {
+            continue;
+        }
+        total += 1;
+        let target = dir.join(&name);
+        let local = std::fs::metadata(&target).and_then(|meta| meta.modified()).ok();
+        let remote = entry.header().mtime()?;
+        let newer = local
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .is_none_or(|time| remote > time.as_secs());
+        if newer {
+            // unpack keeps the modification time, so the next sync compares correctly
+            entry.unpack(&target)?;
+            taken += 1;
+        } else {
+            entry.read_to_end(&mut Vec::new())?;
+        }
+    }
+    Ok((taken, total))
+}
+
+fn remote(config: &Config) -> Result<Remote, QueryError> {
+    let spec = config.sync_remote.as_deref().ok_or_else(|| {
+        QueryError::Input("No sync_remote configured (e.g. sync_remote = \"git@github.com:me/llm-sync.git\")".to_string())
+    })?;
+    let remote = Remote::parse(spec)?;
+    match &remote {
+        Remote::Git(url) | Remote::WebDav(url) => offline::check_url(url, "Sync")?,
+        // Where these end up is up to the aws/rclone config
+        Remote::S3(_) | Remote::Rclone(_) if offline::is_enabled() => {
+            return Err(QueryError::Input("Offline mode: sync to s3:// or rclone: remotes is off".to_string()))
+        }
+        _ => {}
+    }
+    Ok(remote)
+}
+
+// `llm sync push`: merge the remote copy in, then upload every local session
+pub async fn handle_push(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let remote = remote(config)?;
+    let passphrase = passphrase(config)?;
+    let dir = get_sessions_dir();
+    let merged = match remote.download().await? {
+        Some(sealed) => merge(&open(&sealed, &passphrase)?, &dir)?.0,
+        None => 0,
+    };
+    let (archive, count) = pack(&dir)?;
+    remote.upload(seal(archive, &passphrase)?).await?;
+    if !output::is_quiet() {
+        println!("Pushed {} sessions to {}", count, config.sync_remote.as_deref().unwrap_or_default());
+        if merged > 0 {
+            println!("({} newer sessions from the remote were merged in first)", merged);
+        }
+    }
+    Ok(())
+}
+
+// `llm sync pull`: take the sessions that are missing or older here
+pub async fn handle_pull(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let remote = remote(config)?;
+    let passphrase = passphrase(config)?;
+    let spec = config.sync_remote.as_deref().unwrap_or_default();
+    let Some(sealed) = remote.download().await? else {
+        println!("Nothing has been pushed to {} yet", spec);
+        return Ok(());
+    };
+    let (taken, total) = merge(&open(&sealed, &passphrase)?, &get_sessions_dir())?;
+    if !output::is_quiet() {
+        println!("Pulled {} of {} sessions from {} (the others are up to date here)", taken, total, spec);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_dir;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn sealed_archives_open_only_with_the_passphrase_and_unmodified() {
+        let sealed = seal(b"sessions".to_vec(), "correct horse").unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert!(!sealed.windows(8).any(|w| w == b"sessions"));
+        assert_eq!(open(&sealed, "correct horse").unwrap(), b"sessions");
+        assert!(open(&sealed, "wrong horse").is_err());
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(open(&tampered, "correct horse").is_err());
+        assert!(open(b"not an archive", "correct horse").is_err());
+    }
+
+    fn set_age(path: &Path, secs_ago: u64) {
+        let time = SystemTime::now() - Duration::from_secs(secs_ago);
+        std::fs::File::options().write(true).open(path).unwrap().set_modified(time).unwrap();
+    }
+
+    #[test]
+    fn merge_takes_newer_sessions_and_stays_in_the_sessions_dir() {
+        let here = temp_dir("sync-here");
+        let there = temp_dir("sync-there");
+        std::fs::write(there.join("new.json"), "remote").unwrap();
+        std::fs::write(there.join("newer.json"), "remote").unwrap();
+        std::fs::write(there.join("older.json"), "remote").unwrap();
+        set_age(&there.join("older.json"), 3600);
+        std::fs::write(here.join("newer.json"), "local").unwrap();
+        std::fs::write(here.join("older.json"), "local").unwrap();
+        set_age(&here.join("newer.json"), 3600);
+        let (archive, count) = pack(&there).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(merge(&archive, &here).unwrap(), (2, 3));
+        assert_eq!(std::fs::read_to_string(here.join("new.json")).unwrap(), "remote");
+        assert_eq!(std::fs::read_to_string(here.join("newer.json")).unwrap(), "remote");
+        assert_eq!(std::fs::read_to_string(here.join("older.json")).unwrap(), "local");
+
+        // Entries with a directory part or a leading dot are skipped
+        let mut tar = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for name in ["../escaped.json", "sub/nested.json", ".hidden", "ok.json"] {
+            let mut header = tar::Header::new_gnu();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(2);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+            header.set_cksum();
+            tar.append(&header, &b"{}"[..]).unwrap();
+        }
+        let archive = tar.into_inner().unwrap().finish().unwrap();
+        assert_eq!(merge(&archive, &here).unwrap(), (1, 1));
+        assert!(here.join("ok.json").exists());
+        assert!(!here.parent().unwrap().join("escaped.json").exists());
+        assert!(!here.join("sub").exists());
+        assert!(!here.join(".hidden").exists());
+    }
+}
@@ -0,0 +1,82 @@
+// tools/translate.rs
+// `llm translate --to ja -f notes.md` and the interactive `/translate <lang>`
+use crate::cli::{execute_query, QueryError};
+use crate::ingest;
+use crate::output;
+use crate::ui::theme;
+use std::io::{self, IsTerminal, Read};
+use std::path::Path;
+
+pub const TRANSLATE_SYSTEM_PROMPT: &str = "You are a professional translator. Translate the text faithfully \
+into the requested language. Keep markdown, code blocks, URLs and names unchanged. Reply with the translation only.";
+
+// Target languages offered for completion; other names are passed through as given
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("ja", "Japanese"),
+    ("zh-TW", "Traditional Chinese"),
+    ("zh-CN", "Simplified Chinese"),
+    ("ko", "Korean"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("es", "Spanish"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("ru", "Russian"),
+    ("vi", "Vietnamese"),
+    ("th", "Thai"),
+];
+
+// "ja" -> "Japanese", "Klingon" -> "Klingon"
+pub fn language_name(lang: &str) -> &str {
+    LANGUAGES
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(lang))
+        .map(|(_, name)| *name)
+        .unwrap_or(lang)
+}
+
+pub fn translation_prompt(lang: &str, text: &str) -> String {
+    format!("Translate the following text into {}:\n\n{}", language_name(lang), text.trim_end())
+}
+
+// Text from -f, the command line, or piped stdin
+fn read_source(file: Option<String>, text: Vec<String>) -> Result<String, QueryError> {
+    let source = match file {
+        Some(path) => ingest::read_file(Path::new(&path)).map_err(QueryError::Input)?,
+        None if !text.is_empty() => text.join(" "),
+        None if !io::stdin().is_terminal() => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(|e| QueryError::Input(format!("Failed to read stdin: {}", e)))?;
+            buf
+        }
+        None => return Err(QueryError::Input("Nothing to translate: give text, -f <file> or pipe stdin".to_string())),
+    };
+    if source.trim().is_empty() {
+        return Err(QueryError::Input("Nothing to translate: the input is empty".to_string()));
+    }
+    Ok(source)
+}
+
+pub async fn handle_translate(
+    client: &genai::Client,
+    model: &str,
+    to: &str,
+    file: Option<String>,
+    text: Vec<String>,
+    stream: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source = read_source(file, text)?;
+    if !output::is_quiet() && !output::is_raw() {
+        println!(
+            "Translating to {} with {}",
+            theme::ACCENT.paint(language_name(to)),
+            theme::ACCENT.paint(model)
+        );
+    }
+    let prompt = translation_prompt(to, &source);
+    execute_query(client, model, &prompt, stream, false, Some(TRANSLATE_SYSTEM_PROMPT)).await?;
+    Ok(())
+}
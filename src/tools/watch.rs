@@ -0,0 +1,68 @@
+// tools/watch.rs
+// `llm watch -f prompt.md`: ask again whenever the file is saved, to iterate on a prompt in an
+// editor with the answers in another pane. The file is polled like the mic.md bridge; a save is
+// sent once the file has stopped changing for DEBOUNCE, since editors often write in steps.
+use crate::cli::{error_message, execute_query, QueryError};
+use crate::i18n;
+use crate::output;
+use crate::ui::theme;
+use chrono::Local;
+use genai::Client;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tokio::time::sleep;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+// Modification time and content; None while the file is missing or unreadable
+fn snapshot(path: &Path) -> Option<(SystemTime, String)> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    let content = std::fs::read_to_string(path).ok()?;
+    Some((modified, content))
+}
+
+// The next saved version that differs from `previous`, once it has settled
+async fn next_version(path: &Path, previous: Option<&(SystemTime, String)>) -> (SystemTime, String) {
+    loop {
+        if let Some(mut current) = snapshot(path).filter(|s| Some(s) != previous && !s.1.trim().is_empty()) {
+            loop {
+                sleep(DEBOUNCE).await;
+                match snapshot(path) {
+                    Some(now) if now == current => return current,
+                    Some(now) => current = now,
+                    None => break,
+                }
+            }
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+pub async fn handle_watch(client: &Client, model: &str, file: &str, stream: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new(file);
+    if !path.is_file() {
+        return Err(QueryError::Input(format!("'{}' does not exist", file)).into());
+    }
+    if !output::is_quiet() {
+        eprintln!("Watching {} with {} (Ctrl-C to stop)", file, model);
+    }
+    let mut last = None;
+    for run in 1.. {
+        let version = next_version(path, last.as_ref()).await;
+        if output::is_raw() {
+            println!("--- run {} ---", run);
+        } else {
+            println!(
+                "{}",
+                theme::DIM.paint(format!("─── run {} · {} · {} ───", run, file, Local::now().format("%H:%M:%S")))
+            );
+        }
+        // A failed run is reported and the watch goes on
+        if let Err(e) = execute_query(client, model, version.1.trim(), stream, false, None).await {
+            println!("{} {}", i18n::error_label(), error_message(e.as_ref()));
+        }
+        last = Some(version);
+    }
+    Ok(())
+}
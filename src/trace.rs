@@ -0,0 +1,119 @@
+// trace.rs
+// -v / -vv: a small `tracing` subscriber printing events to stderr. -v shows this crate's debug
+// events (model routing, endpoint resolution, request setup); -vv adds its trace events (stream
+// chunks) and debug events from the libraries underneath (genai, reqwest, hyper).
+use crate::ui::theme;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+// Header names whose values never reach the log
+const SECRET_HEADERS: &[&str] = &["authorization", "x-api-key", "api-key", "x-goog-api-key", "cookie", "proxy-authorization"];
+
+struct StderrSubscriber {
+    own_level: Level,            // events from this crate
+    dependency_level: Option<Level>, // events from other crates, None = off
+    next_span: AtomicU64,
+}
+
+impl StderrSubscriber {
+    fn level_for(&self, target: &str) -> Option<Level> {
+        if target == env!("CARGO_CRATE_NAME") || target.starts_with(concat!(env!("CARGO_CRATE_NAME"), "::")) {
+            Some(self.own_level)
+        } else {
+            self.dependency_level
+        }
+    }
+}
+
+// `message` first, then key=value pairs
+#[derive(Default)]
+struct Fields {
+    message: String,
+    pairs: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.pairs, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.pairs, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+impl Subscriber for StderrSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.level_for(metadata.target()).is_some_and(|max| *metadata.level() <= max)
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        let line = format!(
+            "{} {}{}\n",
+            theme::DIM.paint_err(format!("[{:<5} {}]", metadata.level(), metadata.target())),
+            fields.message,
+            fields.pairs
+        );
+        let _ = std::io::stderr().write_all(line.as_bytes());
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+// Install the subscriber for -v (1) or -vv (2 and more); 0 leaves tracing off
+pub fn init(verbosity: u8) {
+    let subscriber = match verbosity {
+        0 => return,
+        1 => StderrSubscriber {
+            own_level: Level::DEBUG,
+            dependency_level: None,
+            next_span: AtomicU64::new(1),
+        },
+        _ => StderrSubscriber {
+            own_level: Level::TRACE,
+            dependency_level: Some(Level::DEBUG),
+            next_span: AtomicU64::new(1),
+        },
+    };
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
+
+// Headers as "name: value" with credentials replaced, for logging
+pub fn redact_headers(headers: &[(String, String)]) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            if SECRET_HEADERS.contains(&name.to_lowercase().as_str()) {
+                format!("{}: <redacted {} chars>", name, value.len())
+            } else {
+                format!("{}: {}", name, value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,121 @@
+// transcribe.rs
+use crate::cli::{execute_query, QueryError};
+use crate::config::Config;
+use crate::offline;
+use genai::Client;
+use std::path::Path;
+use std::process::Command;
+
+const DEFAULT_OPENAI_MODEL: &str = "whisper-1";
+
+enum Backend {
+    // Local tool, e.g. `whisper-cli -m {model} -nt -f {file}`; stdout is the transcript
+    Command(String),
+    // OpenAI-compatible /audio/transcriptions endpoint
+    OpenAI { url: String },
+}
+
+/// Speech-to-text backend selected by `transcribe_backend` / `transcribe_command` in config.
+pub struct Transcriber {
+    backend: Backend,
+    model: Option<String>,
+}
+
+impl Transcriber {
+    pub fn from_config(config: &Config, model: Option<String>) -> Result<Self, Box<dyn std::error::Error>> {
+        let model = model.or_else(|| config.transcribe_model.clone());
+        let backend = match config.transcribe_backend.as_deref() {
+            Some("openai") => Backend::OpenAI {
+                url: config
+                    .transcribe_url
+                    .clone()
+                    .unwrap_or_else(|| "https://api.openai.com/v1/audio/transcriptions".to_string()),
+            },
+            Some("command") | None => match &config.transcribe_command {
+                Some(cmd) => Backend::Command(cmd.clone()),
+                None => {
+                    return Err("No transcription backend configured: set `transcribe_command` \
+                                (e.g. \"whisper-cli -m {model} -nt -f {file}\") or `transcribe_backend = \"openai\"`"
+                        .into())
+                }
+            },
+            Some(other) => return Err(format!("Unknown transcribe_backend '{}'", other).into()),
+        };
+        Ok(Transcriber { backend, model })
+    }
+
+    // Blocking; call from a plain thread or spawn_blocking
+    pub fn transcribe_file(&self, path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+        match &self.backend {
+            Backend::Command(template) => {
+                // The file and model go in as positional arguments, so the shell never parses them
+                let cmdline = template.replace("{file}", "\"$1\"").replace("{model}", "\"$2\"");
+                let output = Command::new("sh")
+                    .arg("-c")
+                    .arg(&cmdline)
+                    .arg("sh")
+                    .arg(path)
+                    .arg(self.model.as_deref().unwrap_or(""))
+                    .output()?;
+                if !output.status.success() {
+                    return Err(format!(
+                        "Transcription command failed ({}): {}",
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )
+                    .into());
+                }
+                Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+            }
+            Backend::OpenAI { url } => {
+                offline::check_url(url, "Transcription")?;
+                let api_key = std::env::var("OPENAI_API_KEY")
+                    .map_err(|_| "OPENAI_API_KEY is required for transcribe_backend = \"openai\"")?;
+                let form = reqwest::blocking::multipart::Form::new()
+                    .text("model", self.model.clone().unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string()))
+                    .text("response_format", "text")
+                    .file("file", path)?;
+                let res = reqwest::blocking::Client::new()
+                    .post(url)
+                    .bearer_auth(api_key)
+                    .multipart(form)
+                    .send()?;
+                let status = res.status();
+                let body = res.text()?;
+                if !status.is_success() {
+                    return Err(format!("Transcription request failed ({}): {}", status, body.trim()).into());
+                }
+                Ok(body.trim().to_string())
+            }
+        }
+    }
+}
+
+// `llm transcribe <file> [-m whisper-model] [--ask "..."]`
+pub async fn handle_transcribe(
+    client: &Client,
+    model: &str,
+    config: &Config,
+    file: &str,
+    whisper_model: Option<String>,
+    ask: Option<String>,
+    stream: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::path::PathBuf::from(file);
+    if !path.exists() {
+        return Err(QueryError::Input(format!("Audio file '{}' does not exist", file)).into());
+    }
+    let transcriber = Transcriber::from_config(config, whisper_model)?;
+    let text = tokio::task::spawn_blocking(move || transcriber.transcribe_file(&path).map_err(|e| e.to_string()))
+        .await?
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+    match ask {
+        Some(instruction) => {
+            let question = format!("{}\n\nTranscript:\n{}", instruction, text);
+            execute_query(client, model, &question, stream, false, None).await?;
+        }
+        None => println!("{}", text),
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+// transcript.rs
+use crate::redact;
+use chrono::Local;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Append-only markdown transcript, written as the session proceeds (independent of /save).
+pub struct TranscriptLog {
+    path: PathBuf,
+}
+
+impl TranscriptLog {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let log = TranscriptLog {
+            path: expand_home(path),
+        };
+        if let Some(parent) = log.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        log.append(&format!(
+            "\n# Transcript started {}\n",
+            Local::now().format("%Y-%m-%d %H:%M:%S")
+        ))?;
+        Ok(log)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn log_user(&self, content: &str) {
+        self.log_entry("User", content);
+    }
+
+    pub fn log_assistant(&self, model: &str, content: &str) {
+        self.log_entry(&format!("Assistant ({})", model), content);
+    }
+
+    fn log_entry(&self, who: &str, content: &str) {
+        let entry = format!(
+            "\n## {} — {}\n\n{}\n",
+            who,
+            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            redact::mask(content.trim_end())
+        );
+        if let Err(e) = self.append(&entry) {
+            eprintln!("Failed to write transcript {}: {}", self.path.display(), e);
+        }
+    }
+
+    // Open/append/close per entry so everything is on disk even if the process dies
+    fn append(&self, text: &str) -> std::io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(text.as_bytes())?;
+        file.flush()
+    }
+}
+
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,104 @@
+// typeahead.rs
+// Keys typed while an answer is rendering are collected here instead of being echoed into the
+// output; finished lines are queued and sent once the current exchange is done.
+use std::collections::VecDeque;
+
+pub struct TypeAhead {
+    #[cfg(unix)]
+    saved: Option<libc::termios>,
+    pending: Vec<u8>, // the line being typed, not yet submitted
+    in_escape: bool,  // skipping an arrow-key/function-key sequence
+}
+
+impl TypeAhead {
+    // Turn off echo and line buffering on a terminal stdin; output processing is left alone
+    pub fn start() -> Self {
+        #[cfg(unix)]
+        let saved = unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::isatty(libc::STDIN_FILENO) == 1 && libc::tcgetattr(libc::STDIN_FILENO, &mut termios) == 0 {
+                let saved = termios;
+                termios.c_lflag &= !(libc::ECHO | libc::ICANON);
+                termios.c_cc[libc::VMIN] = 0;
+                termios.c_cc[libc::VTIME] = 0;
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+                Some(saved)
+            } else {
+                None
+            }
+        };
+        TypeAhead {
+            #[cfg(unix)]
+            saved,
+            pending: Vec::new(),
+            in_escape: false,
+        }
+    }
+
+    // Read whatever was typed so far without blocking; complete lines go to `queue`
+    pub fn poll(&mut self, queue: &mut VecDeque<String>) {
+        #[cfg(unix)]
+        {
+            if self.saved.is_none() {
+                return;
+            }
+            let mut buf = [0u8; 256];
+            loop {
+                let n = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr().cast(), buf.len()) };
+                if n <= 0 {
+                    break;
+                }
+                for &byte in &buf[..n as usize] {
+                    self.push_byte(byte, queue);
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = queue;
+    }
+
+    fn push_byte(&mut self, byte: u8, queue: &mut VecDeque<String>) {
+        if self.in_escape {
+            // CSI sequences end with a letter or '~'
+            if byte.is_ascii_alphabetic() || byte == b'~' {
+                self.in_escape = false;
+            }
+            return;
+        }
+        match byte {
+            0x1b => self.in_escape = true,
+            b'\n' | b'\r' => {
+                let line = String::from_utf8_lossy(&self.pending).trim().to_string();
+                self.pending.clear();
+                if !line.is_empty() {
+                    queue.push_back(line);
+                }
+            }
+            0x7f | 0x08 => {
+                let mut text = String::from_utf8_lossy(&self.pending).into_owned();
+                text.pop();
+                self.pending = text.into_bytes();
+            }
+            0x15 => self.pending.clear(), // Ctrl-U
+            b if b < 0x20 && b != b'\t' => {}
+            b => self.pending.push(b),
+        }
+    }
+
+    // Restore the terminal; returns the unfinished line so the next prompt can start with it
+    pub fn finish(mut self, queue: &mut VecDeque<String>) -> String {
+        self.poll(queue);
+        String::from_utf8_lossy(&std::mem::take(&mut self.pending)).into_owned()
+    }
+}
+
+impl Drop for TypeAhead {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(saved) = self.saved.take() {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &saved);
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod spinner;
+pub mod text_width;
+pub mod theme;
@@ -0,0 +1,65 @@
+// ui/spinner.rs
+use crate::output;
+use crate::ui::theme;
+use std::io::{IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Spinner with elapsed time on the current line, drawn by a background task until `stop`
+/// (or drop) erases it. Nothing is drawn when stdout is not a terminal or output is quiet/raw.
+pub struct Spinner {
+    running: Option<Arc<Mutex<bool>>>,
+}
+
+impl Spinner {
+    pub fn start(message: &str) -> Self {
+        // A spinner redraws with cursor movement, which a dumb terminal or NO_COLOR reader gets as noise
+        if !std::io::stdout().is_terminal() || output::is_quiet() || !theme::colors() {
+            return Spinner { running: None };
+        }
+        let running = Arc::new(Mutex::new(true));
+        let task_running = running.clone();
+        let message = message.to_string();
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let mut frame = 0;
+            loop {
+                {
+                    // Holding the lock while drawing keeps stop() from erasing mid-frame
+                    let running = task_running.lock().unwrap();
+                    if !*running {
+                        break;
+                    }
+                    print!(
+                        "\r\x1b[2K{} {} {}",
+                        theme::INFO.paint(FRAMES[frame]),
+                        message,
+                        theme::MUTED.paint(format!("{:.1}s", started.elapsed().as_secs_f32()))
+                    );
+                    let _ = std::io::stdout().flush();
+                }
+                frame = (frame + 1) % FRAMES.len();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        });
+        Spinner { running: Some(running) }
+    }
+
+    // Erase the spinner line so the answer starts on a clean line
+    pub fn stop(&mut self) {
+        if let Some(running) = self.running.take() {
+            let mut running = running.lock().unwrap();
+            *running = false;
+            print!("\r\x1b[2K");
+            let _ = std::io::stdout().flush();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,112 @@
+// ui/text_width.rs
+// Terminal columns rather than chars: CJK and most emoji take two columns, combining marks none,
+// and ANSI escape sequences (colors) none. Used wherever text is truncated, wrapped or padded to
+// fit the terminal.
+use unicode_width::UnicodeWidthChar;
+
+// Length of the escape sequence starting at `s` (which begins with ESC), 0 if it isn't one
+fn escape_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    match bytes.get(1) {
+        // CSI: ESC [ parameters final-byte
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(s.len(), |end| end + 3),
+        Some(_) => 2,
+        None => 1,
+    }
+}
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+// Columns `text` takes on a terminal
+pub fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '\x1b' { escape_len(rest) } else { c.len_utf8() };
+        if c != '\x1b' {
+            width += char_width(c);
+        }
+        rest = &rest[len..];
+    }
+    width
+}
+
+// The start of `text` that fits in `width` columns; escape sequences are kept, so colors still
+// get reset
+pub fn truncate(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '\x1b' {
+            let len = escape_len(rest);
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        used += char_width(c);
+        if used <= width {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+// The end of plain `text` that fits in `width` columns
+pub fn tail(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in text.char_indices().rev() {
+        used += char_width(c);
+        if used > width {
+            return &text[i + c.len_utf8()..];
+        }
+    }
+    text
+}
+
+// Plain `text` cut into lines of at most `width` columns; a wide char never straddles two lines
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(2);
+    let mut lines = vec![String::new()];
+    let mut used = 0;
+    for c in text.chars() {
+        let w = char_width(c);
+        if used + w > width {
+            lines.push(String::new());
+            used = 0;
+        }
+        lines.last_mut().unwrap().push(c);
+        used += w;
+    }
+    lines
+}
+
+// `text` followed by spaces up to `width` columns
+pub fn pad(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(display_width(text))))
+}
+
+// Nerd-font icons live in the private use areas; a screen reader has nothing to say for them
+fn is_icon(c: char) -> bool {
+    matches!(c as u32, 0xE000..=0xF8FF | 0xF0000..=0xFFFFD | 0x100000..=0x10FFFD)
+}
+
+// `text` without escape sequences and icon glyphs
+pub fn plain(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '\x1b' { escape_len(rest) } else { c.len_utf8() };
+        if c != '\x1b' && !is_icon(c) {
+            out.push(c);
+        }
+        rest = &rest[len..];
+    }
+    out
+}
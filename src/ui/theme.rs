@@ -0,0 +1,89 @@
+// ui/theme.rs
+// Every color the tool prints comes from a style here. Styling is left out when NO_COLOR is set
+// (https://no-color.org), TERM is "dumb", the stream is not a terminal, or output is raw
+// (--raw, --accessible). Cursor movement (spinner, pinned status line) is not styling and stays
+// with its callers.
+use crate::output;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style(&'static str);
+
+pub const ACCENT: Style = Style("93"); // model names and values in headers
+pub const VALUE: Style = Style("33"); // names and values in messages
+pub const HEADING: Style = Style("1;33");
+pub const BOLD: Style = Style("1");
+pub const DIM: Style = Style("2");
+pub const MUTED: Style = Style("90");
+pub const GOOD: Style = Style("32");
+pub const PROMPT: Style = Style("92");
+pub const BAD: Style = Style("31");
+pub const INFO: Style = Style("36");
+pub const KEYWORD: Style = Style("1;36");
+pub const PATH: Style = Style("4;36");
+pub const TOOL: Style = Style("35");
+pub const MARKER: Style = Style("1;35");
+pub const LINK: Style = Style("34");
+pub const REMOVED: Style = Style("31;9");
+pub const REASONING: Style = Style("2;3");
+pub const SELECTED: Style = Style("7");
+// The interactive title bar: segments, and arrows colored like the segment they leave
+pub const TITLE: Style = Style("30;43");
+pub const TITLE_ARROW: Style = Style("33;44");
+pub const TITLE_MODEL: Style = Style("30;44");
+pub const TITLE_MODEL_ARROW: Style = Style("34;47");
+pub const TITLE_MODEL_END: Style = Style("34");
+pub const TITLE_STREAM: Style = Style("30;47");
+pub const TITLE_STREAM_END: Style = Style("37");
+
+// Whether stdout / stderr can show colors at all, checked once
+struct Terminal {
+    stdout: bool,
+    stderr: bool,
+}
+
+static TERMINAL: OnceLock<Terminal> = OnceLock::new();
+
+fn terminal() -> &'static Terminal {
+    TERMINAL.get_or_init(|| {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+        let capable = !no_color && !dumb;
+        Terminal {
+            stdout: capable && std::io::stdout().is_terminal(),
+            stderr: capable && std::io::stderr().is_terminal(),
+        }
+    })
+}
+
+// Colors on stdout
+pub fn colors() -> bool {
+    terminal().stdout && !output::is_raw()
+}
+
+// Colors on stderr (warnings, traces, the one-shot footer)
+pub fn colors_on_stderr() -> bool {
+    terminal().stderr && !output::is_raw()
+}
+
+impl Style {
+    fn apply(self, text: impl Display, on: bool) -> String {
+        if on {
+            format!("\x1b[{}m{}\x1b[0m", self.0, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    // `text` in this style, for stdout
+    pub fn paint(self, text: impl Display) -> String {
+        self.apply(text, colors())
+    }
+
+    // `text` in this style, for stderr
+    pub fn paint_err(self, text: impl Display) -> String {
+        self.apply(text, colors_on_stderr())
+    }
+}
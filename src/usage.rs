@@ -0,0 +1,215 @@
+// usage.rs
+// Per-exchange timing/token stats: the optional footer (`show_stats`) and the usage ledger,
+// one JSON line per answered request
+use crate::config::{get_data_dir, Config, ModelPrice};
+use crate::ui::text_width;
+use chrono::Local;
+use genai::chat::Usage;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const LEDGER_FILE: &str = "usage.jsonl";
+
+// USD per million input/output tokens; the longest matching model name prefix wins
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gemini-2.0-flash-lite", 0.075, 0.30),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("deepseek-reasoner", 0.55, 2.19),
+    ("deepseek-chat", 0.27, 1.10),
+    ("grok-2", 2.00, 10.00),
+    ("qwen-max", 1.60, 6.40),
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("claude-3-5-haiku", 0.80, 4.00),
+    ("claude-3-5-sonnet", 3.00, 15.00),
+    ("claude-3-7-sonnet", 3.00, 15.00),
+];
+
+struct UsageSettings {
+    show_stats: bool,
+    ledger: PathBuf,
+    prices: HashMap<String, ModelPrice>,
+}
+
+static SETTINGS: OnceLock<UsageSettings> = OnceLock::new();
+
+// `show_stats`, `usage_ledger` and `[prices]` from config; call once at startup
+pub fn init(config: &Config) {
+    let _ = SETTINGS.set(UsageSettings {
+        show_stats: config.show_stats.unwrap_or(false),
+        ledger: config
+            .usage_ledger
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| get_data_dir().join(LEDGER_FILE)),
+        prices: config.prices.clone().unwrap_or_default(),
+    });
+}
+
+pub fn show_stats() -> bool {
+    SETTINGS.get().is_some_and(|s| s.show_stats)
+}
+
+/// Timing and token counts of one answered request.
+#[derive(Clone, Debug)]
+pub struct ExchangeStats {
+    pub model: String,
+    pub first_token: Option<Duration>, // only when a stream measured it
+    pub total: Duration,
+    pub prompt_tokens: Option<i32>,
+    pub completion_tokens: Option<i32>,
+}
+
+impl ExchangeStats {
+    pub fn new(model: &str, usage: &Usage, first_token: Option<Duration>, total: Duration) -> Self {
+        ExchangeStats {
+            model: model.to_string(),
+            first_token,
+            total,
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+        }
+    }
+
+    // Output rate over the generation time (after the first token when streamed)
+    pub fn tokens_per_sec(&self) -> Option<f64> {
+        let generating = match self.first_token {
+            Some(first_token) if self.total > first_token => self.total - first_token,
+            _ => self.total,
+        };
+        let tokens = self.completion_tokens? as f64;
+        (generating.as_secs_f64() > 0.0).then(|| tokens / generating.as_secs_f64())
+    }
+
+    pub fn cost(&self) -> Option<f64> {
+        let price = price_for(&self.model)?;
+        let input = self.prompt_tokens.unwrap_or(0) as f64;
+        let output = self.completion_tokens.unwrap_or(0) as f64;
+        Some((input * price.input + output * price.output) / 1_000_000.0)
+    }
+
+    // ttft 0.8s · total 3.2s · 512 in / 230 out · 95.8 tok/s · $0.0003 (no ttft when not streamed)
+    pub fn footer(&self) -> String {
+        let mut parts: Vec<String> = self
+            .first_token
+            .map(|first_token| format!("ttft {:.1}s", first_token.as_secs_f64()))
+            .into_iter()
+            .collect();
+        parts.push(format!("total {:.1}s", self.total.as_secs_f64()));
+        if let (Some(input), Some(output)) = (self.prompt_tokens, self.completion_tokens) {
+            parts.push(format!("{} in / {} out", input, output));
+        }
+        if let Some(rate) = self.tokens_per_sec() {
+            parts.push(format!("{:.1} tok/s", rate));
+        }
+        if let Some(cost) = self.cost() {
+            parts.push(format!("${:.4}", cost));
+        }
+        parts.join(" · ")
+    }
+}
+
+// `[prices]` from config first, then the built-in table; local models are free
+fn price_for(model: &str) -> Option<ModelPrice> {
+    if let Some(price) = SETTINGS.get().and_then(|s| s.prices.get(model)) {
+        return Some(price.clone());
+    }
+    if model.starts_with(crate::local::MODEL_PREFIX) {
+        return Some(ModelPrice { input: 0.0, output: 0.0 });
+    }
+    let name = model.rsplit('/').next().unwrap_or(model);
+    PRICES
+        .iter()
+        .filter(|(prefix, _, _)| name.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+        .map(|(_, input, output)| ModelPrice {
+            input: *input,
+            output: *output,
+        })
+}
+
+// Append one line to the usage ledger; failures are reported but never fatal
+pub fn record(stats: &ExchangeStats) {
+    let Some(settings) = SETTINGS.get() else {
+        return;
+    };
+    append(
+        settings,
+        serde_json::json!({
+            "timestamp": Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "model": stats.model,
+            "prompt_tokens": stats.prompt_tokens,
+            "completion_tokens": stats.completion_tokens,
+            "ttft_ms": stats.first_token.map(|d| d.as_millis() as u64),
+            "duration_ms": stats.total.as_millis() as u64,
+            "cost_usd": stats.cost(),
+        }),
+    );
+}
+
+// A /rate, kept in the ledger next to the requests it judges
+pub fn record_rating(model: &str, score: u8, comment: Option<&str>) {
+    let Some(settings) = SETTINGS.get() else {
+        return;
+    };
+    append(
+        settings,
+        serde_json::json!({
+            "timestamp": Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "model": model,
+            "rating": score,
+            "comment": comment,
+        }),
+    );
+}
+
+fn append(settings: &UsageSettings, entry: serde_json::Value) {
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&settings.ledger)
+        .and_then(|mut file| writeln!(file, "{}", entry));
+    if let Err(e) = result {
+        eprintln!("Failed to write usage ledger {}: {}", settings.ledger.display(), e);
+    }
+}
+
+// `llm usage ratings`: average /rate score per model from the ledger, best first
+pub fn print_ratings() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(settings) = SETTINGS.get() else {
+        return Ok(());
+    };
+    let data = match std::fs::read_to_string(&settings.ledger) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {}: {}", settings.ledger.display(), e).into()),
+    };
+    let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+    for entry in data.lines().filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok()) {
+        if let (Some(model), Some(score)) = (entry["model"].as_str(), entry["rating"].as_u64()) {
+            let total = totals.entry(model.to_string()).or_default();
+            total.0 += score;
+            total.1 += 1;
+        }
+    }
+    if totals.is_empty() {
+        println!("No ratings yet; rate answers with /rate 1-5 in interactive mode.");
+        return Ok(());
+    }
+    let mut rows: Vec<(String, f64, u64)> = totals
+        .into_iter()
+        .map(|(model, (sum, count))| (model, sum as f64 / count as f64, count))
+        .collect();
+    rows.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.2.cmp(&a.2)));
+    let width = rows.iter().map(|r| text_width::display_width(&r.0)).max().unwrap_or(0).max(5);
+    println!("{}  {:>7}  {:>7}", text_width::pad("model", width), "average", "ratings");
+    for (model, average, count) in rows {
+        println!("{}  {:>7.2}  {:>7}", text_width::pad(&model, width), average, count);
+    }
+    Ok(())
+}
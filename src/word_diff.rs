@@ -0,0 +1,101 @@
+// word_diff.rs
+// Word-level diff of two answers for /diff and `rewrite`: words and the whitespace between them
+// are the tokens, compared with similar's word diff.
+use crate::ui::theme;
+use similar::{ChangeTag, TextDiff};
+use std::time::Duration;
+
+// Long answers fall back to a coarser diff rather than stalling /diff
+const DEADLINE: Duration = Duration::from_secs(1);
+
+#[derive(Debug, PartialEq)]
+pub enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+pub fn diff<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let diff = TextDiff::configure().timeout(DEADLINE).diff_words(old, new);
+    diff.iter_all_changes()
+        .map(|change| match change.tag() {
+            ChangeTag::Equal => Change::Same(change.value()),
+            ChangeTag::Delete => Change::Removed(change.value()),
+            ChangeTag::Insert => Change::Added(change.value()),
+        })
+        .collect()
+}
+
+// Removed text in red, added text in green; `[-...-]` / `{+...+}` markers without colors.
+// Neighbouring changes of one kind are shown as one run.
+pub fn render(changes: &[Change]) -> String {
+    let colors = theme::colors();
+    let mut out = String::new();
+    let (mut removed, mut added) = (String::new(), String::new());
+    let flush = |out: &mut String, removed: &mut String, added: &mut String| {
+        if !removed.is_empty() {
+            let run = if colors { theme::REMOVED.paint(&removed) } else { format!("[-{}-]", removed) };
+            out.push_str(&run);
+            removed.clear();
+        }
+        if !added.is_empty() {
+            let run = if colors { theme::GOOD.paint(&added) } else { format!("{{+{}+}}", added) };
+            out.push_str(&run);
+            added.clear();
+        }
+    };
+    for change in changes {
+        match change {
+            Change::Same(text) => {
+                flush(&mut out, &mut removed, &mut added);
+                out.push_str(text);
+            }
+            Change::Removed(text) => removed.push_str(text),
+            Change::Added(text) => added.push_str(text),
+        }
+    }
+    flush(&mut out, &mut removed, &mut added);
+    out
+}
+
+// Words removed and added, for the summary line
+pub fn counts(changes: &[Change]) -> (usize, usize) {
+    let is_word = |t: &&str| !t.trim().is_empty();
+    let removed = changes.iter().filter(|c| matches!(c, Change::Removed(t) if is_word(t))).count();
+    let added = changes.iter().filter(|c| matches!(c, Change::Added(t) if is_word(t))).count();
+    (removed, added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(old: &str, new: &str) -> String {
+        let changes = diff(old, new);
+        let mut out = String::new();
+        for change in &changes {
+            match change {
+                Change::Same(t) => out.push_str(t),
+                Change::Removed(t) => out.push_str(&format!("[-{}-]", t)),
+                Change::Added(t) => out.push_str(&format!("{{+{}+}}", t)),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn insertions_deletions_and_replacements_are_word_level() {
+        assert_eq!(plain("a quick fox", "a quick brown fox"), "a quick {+brown+}{+ +}fox");
+        assert_eq!(plain("a quick brown fox", "a brown fox"), "a [-quick-][- -]brown fox");
+        assert_eq!(plain("a quick fox", "a slow fox"), "a [-quick-]{+slow+} fox");
+        assert_eq!(counts(&diff("a quick fox", "a slow fox")), (1, 1));
+        assert_eq!(plain("same words", "same words"), "same words");
+    }
+
+    #[test]
+    fn empty_inputs() {
+        assert!(diff("", "").is_empty());
+        assert_eq!(diff("", "new text"), vec![Change::Added("new"), Change::Added(" "), Change::Added("text")]);
+        assert_eq!(counts(&diff("old text", "")), (2, 0));
+    }
+}